@@ -0,0 +1,163 @@
+use crate::metadata::{scan_jar_metadata, PLATFORM_IDS};
+use crate::mods::{disable_content, enable_content, list_content};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// In-progress bisect, kept in the server directory so it survives restarts.
+pub const BISECT_FILE: &str = "oxidemc.bisect.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BisectState {
+    /// Content folder being bisected, relative to the server (`mods` or `plugins`).
+    pub content_dir: String,
+    /// Jars that could still be the culprit.
+    pub suspects: Vec<String>,
+    /// Jars disabled for the current round: half the suspects, plus anything that
+    /// depends on them (so a missing library isn't mistaken for the crash).
+    pub disabled: Vec<String>,
+    pub round: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BisectOutcome {
+    /// Start the server again and report whether it crashed.
+    Continue(BisectState),
+    /// The culprit; everything has been re-enabled.
+    Found(String),
+}
+
+pub fn read_bisect(dir: &Path) -> Option<BisectState> {
+    let text = fs::read_to_string(dir.join(BISECT_FILE)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn write_bisect(dir: &Path, state: &BisectState) -> Result<(), Box<dyn Error>> {
+    fs::write(dir.join(BISECT_FILE), serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+/// Begin bisecting every enabled jar in `content_subdir`.
+pub fn start_bisect(dir: &PathBuf, content_subdir: &str) -> Result<BisectOutcome, Box<dyn Error>> {
+    if read_bisect(dir).is_some() {
+        return Err("A bisect is already in progress".into());
+    }
+    let suspects: Vec<String> = list_content(&dir.join(content_subdir))?
+        .into_iter()
+        .filter(|c| c.enabled)
+        .map(|c| c.name)
+        .collect();
+    if suspects.is_empty() {
+        return Err(format!("No enabled jars in {}", content_subdir).into());
+    }
+    next_round(
+        dir,
+        BisectState {
+            content_dir: content_subdir.to_string(),
+            suspects,
+            disabled: vec![],
+            round: 0,
+        },
+    )
+}
+
+/// Record whether the server crashed with the current round's jars disabled and
+/// narrow the suspects down.
+pub fn bisect_step(dir: &PathBuf, crashed: bool) -> Result<BisectOutcome, Box<dyn Error>> {
+    let mut state = read_bisect(dir).ok_or("No bisect in progress")?;
+    // Crashed: the culprit is still enabled. Started fine: it's one of the disabled jars.
+    state
+        .suspects
+        .retain(|s| state.disabled.contains(s) != crashed);
+    restore(dir, &state)?;
+    state.disabled.clear();
+    if state.suspects.is_empty() {
+        fs::remove_file(dir.join(BISECT_FILE))?;
+        return Err(
+            "Every suspect has been ruled out; the crash may need more than one mod".into(),
+        );
+    }
+    next_round(dir, state)
+}
+
+/// Re-enable everything the bisect disabled and forget the session.
+pub fn abort_bisect(dir: &PathBuf) -> Result<(), Box<dyn Error>> {
+    if let Some(state) = read_bisect(dir) {
+        restore(dir, &state)?;
+        fs::remove_file(dir.join(BISECT_FILE))?;
+    }
+    Ok(())
+}
+
+fn restore(dir: &PathBuf, state: &BisectState) -> Result<(), Box<dyn Error>> {
+    let content_dir = dir.join(&state.content_dir);
+    for name in &state.disabled {
+        let disabled = format!("{}{}", name, crate::mods::DISABLED_SUFFIX);
+        if content_dir.join(&disabled).exists() {
+            enable_content(dir, &content_dir, &disabled)?;
+        }
+    }
+    Ok(())
+}
+
+fn next_round(dir: &PathBuf, mut state: BisectState) -> Result<BisectOutcome, Box<dyn Error>> {
+    // A bisect started with a single jar hasn't tested it yet; disable it for one run to
+    // confirm instead of naming it straight away
+    if state.suspects.len() == 1 && state.round > 0 {
+        let _ = fs::remove_file(dir.join(BISECT_FILE));
+        return Ok(BisectOutcome::Found(state.suspects.remove(0)));
+    }
+    let content_dir = dir.join(&state.content_dir);
+    let disabled = if state.suspects.len() == 1 {
+        with_dependents(&content_dir, state.suspects.clone())
+    } else {
+        let (first, second) = state.suspects.split_at(state.suspects.len() / 2);
+        // Disabling a library takes its dependents with it; if that would switch off every
+        // suspect the round can't narrow anything down, so try the other half, then the
+        // bare half.
+        let covers_all = |set: &Vec<String>| state.suspects.iter().all(|s| set.contains(s));
+        [first.to_vec(), second.to_vec()]
+            .into_iter()
+            .map(|half| with_dependents(&content_dir, half))
+            .find(|set| !covers_all(set))
+            .unwrap_or_else(|| first.to_vec())
+    };
+    for name in &disabled {
+        disable_content(dir, &content_dir, name)?;
+    }
+    state.disabled = disabled;
+    state.round += 1;
+    write_bisect(dir, &state)?;
+    Ok(BisectOutcome::Continue(state))
+}
+
+/// `jars` plus every enabled jar that (transitively) requires one of them.
+fn with_dependents(content_dir: &PathBuf, mut jars: Vec<String>) -> Vec<String> {
+    let metadata = scan_jar_metadata(content_dir);
+    loop {
+        let ids: Vec<&str> = metadata
+            .iter()
+            .filter(|(f, _)| jars.contains(f))
+            .flat_map(|(_, m)| {
+                std::iter::once(m.id.as_str()).chain(m.provides.iter().map(|p| p.as_str()))
+            })
+            .collect();
+        let dependents: Vec<String> = metadata
+            .iter()
+            .filter(|(f, _)| !jars.contains(f))
+            .filter(|(_, m)| {
+                m.dependencies.iter().any(|d| {
+                    d.required
+                        && !PLATFORM_IDS.contains(&d.id.as_str())
+                        && ids.iter().any(|id| id.eq_ignore_ascii_case(&d.id))
+                })
+            })
+            .map(|(f, _)| f.clone())
+            .collect();
+        if dependents.is_empty() {
+            return jars;
+        }
+        jars.extend(dependents);
+    }
+}
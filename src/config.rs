@@ -1,68 +1,44 @@
+use crate::properties::{update_properties, Properties};
+use crate::schema::property_spec;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::error::Error;
-use std::fs::{self, File};
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::PathBuf;
+use std::fs;
+use std::path::Path;
 
-pub fn configure_file(dir: &PathBuf, filename: &str, name: &str, value: &str) -> Result<(), ()> {
-    println!("Configuring {} in {}, the dir is {}", name, filename, dir.display());
-    let path = dir.join(filename);
-
-    // If file doesn't exist, create it with the key=value
-    if !path.exists() {
-        let mut file = File::create(&path).unwrap();
-        writeln!(file, "{}={}", name, value).unwrap();
-        return Ok(());
-    }
-
-    let input = File::open(&path).unwrap();
-    let buffered_input = BufReader::new(input);
-
-    let temp_path = path.with_extension("tmp");
-
-    let output = File::create(&temp_path).unwrap();
-    let mut buffered_output = BufWriter::new(output);
-
-    let mut found = false;
-    for line in buffered_input.lines() {
-        let line = line.unwrap();
-        let trimmed = line.trim_start();
-
-        if trimmed.starts_with(format!("{}=", name).as_str()) {
-            writeln!(buffered_output, "{}={}", name, value).unwrap();
-            found = true;
-        } else {
-            writeln!(buffered_output, "{}", line).unwrap();
-        }
-    }
-
-    // If the key wasn't found in the file, append it
-    if !found {
-        writeln!(buffered_output, "{}={}", name, value).unwrap();
-    }
-
-    buffered_output.flush().unwrap();
-    fs::rename(&temp_path, &path).unwrap();
+pub fn configure_file(
+    dir: &Path,
+    filename: &str,
+    name: &str,
+    value: &str,
+) -> Result<(), Box<dyn Error>> {
+    configure_properties(dir, filename, &[(name, value)])
+}
 
-    Ok(())
+/// Set several keys of a properties file (`server.properties`, `eula.txt`) in one write,
+/// keeping comments and ordering.
+pub fn configure_properties(
+    dir: &Path,
+    filename: &str,
+    edits: &[(&str, &str)],
+) -> Result<(), Box<dyn Error>> {
+    update_properties(&dir.join(filename), edits)
 }
 
-pub fn read_property(dir: &PathBuf, filename: &str, name: &str) -> Result<String, String> {
+/// Unescaped value of a key in a properties file.
+pub fn read_property(dir: &Path, filename: &str, name: &str) -> Result<String, String> {
     let path = dir.join(filename);
-    let file = File::open(&path).map_err(|e| e.to_string())?;
-    let reader = BufReader::new(file);
-    for line in reader.lines() {
-        let line = line.map_err(|e| e.to_string())?;
-        let trimmed = line.trim_start();
-        if trimmed.starts_with(&format!("{}=", name)) {
-            return Ok(trimmed[name.len() + 1..].to_string());
-        }
+    if !path.exists() {
+        return Err(format!("{} not found", path.display()));
     }
-    Err(format!("Property '{}' not found", name))
+    let props = Properties::load(&path).map_err(|e| e.to_string())?;
+    props
+        .get(name)
+        .map(str::to_string)
+        .ok_or_else(|| format!("Property '{}' not found", name))
 }
 
-pub fn read_oxide_config(dir: &PathBuf, key: &str) -> Result<String, Box<dyn Error>> {
+pub fn read_oxide_config(dir: &Path, key: &str) -> Result<String, Box<dyn Error>> {
     let path = dir.join("oxidemc.json");
     let text = fs::read_to_string(&path)?;
     let json: Value = serde_json::from_str(&text)?;
@@ -72,7 +48,7 @@ pub fn read_oxide_config(dir: &PathBuf, key: &str) -> Result<String, Box<dyn Err
         .ok_or_else(|| format!("Key '{}' not found", key).into())
 }
 
-pub fn write_oxide_config(dir: &PathBuf, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+pub fn write_oxide_config(dir: &Path, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
     let path = dir.join("oxidemc.json");
     let mut map: serde_json::Map<String, Value> = if path.exists() {
         serde_json::from_str(&fs::read_to_string(&path)?)?
@@ -84,21 +60,27 @@ pub fn write_oxide_config(dir: &PathBuf, key: &str, value: &str) -> Result<(), B
     Ok(())
 }
 
+/// `server.properties` keys a preset carries when they differ from the default.
+pub const PRESET_PROPERTIES: &[&str] = &[
+    "difficulty",
+    "gamemode",
+    "pvp",
+    "max-players",
+    "view-distance",
+    "simulation-distance",
+    "spawn-protection",
+    "level-type",
+    "level-seed",
+    "max-world-size",
+    "online-mode",
+    "server-port",
+    "motd",
+];
+
 /// Default Minecraft server.properties values — only settings that differ are saved in presets.
 pub fn default_server_properties() -> HashMap<&'static str, &'static str> {
-    HashMap::from([
-        ("difficulty", "easy"),
-        ("gamemode", "survival"),
-        ("pvp", "true"),
-        ("max-players", "20"),
-        ("view-distance", "10"),
-        ("simulation-distance", "10"),
-        ("spawn-protection", "16"),
-        ("level-type", "minecraft\\:normal"),
-        ("level-seed", ""),
-        ("max-world-size", "29999984"),
-        ("online-mode", "true"),
-        ("server-port", "25565"),
-        ("motd", "A Minecraft Server"),
-    ])
+    PRESET_PROPERTIES
+        .iter()
+        .filter_map(|key| Some((*key, property_spec(key)?.default)))
+        .collect()
 }
@@ -0,0 +1,206 @@
+use crate::metadata::{
+    compare_versions, scan_jar_metadata, version_satisfies, ContentMetadata, PLATFORM_IDS,
+};
+use crate::mods::disable_content;
+use crate::utils::sha1_file;
+use ferinth::structures::project::ProjectSupportRange;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// Folders the doctor looks in for jars.
+const CONTENT_DIRS: [&str; 2] = ["mods", "plugins"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum IssueKind {
+    ClientOnly,
+    DuplicateId,
+    MissingDependency,
+    Incompatible,
+}
+
+/// A problem that is likely to stop the server from starting.
+#[derive(Clone, Debug)]
+pub struct DoctorIssue {
+    pub kind: IssueKind,
+    pub message: String,
+    /// Files (relative to the server directory) that can be disabled to fix it.
+    pub files: Vec<String>,
+}
+
+/// Metadata for every jar in the content folders, keyed by path relative to the server.
+pub fn scan_content(dir: &Path) -> Vec<(String, ContentMetadata)> {
+    CONTENT_DIRS
+        .iter()
+        .flat_map(|sub| {
+            scan_jar_metadata(&dir.join(sub))
+                .into_iter()
+                .map(move |(name, m)| (format!("{}/{}", sub, name), m))
+        })
+        .collect()
+}
+
+/// Check installed content for client-only mods, duplicate IDs, unmet dependencies and
+/// declared incompatibilities. `remote_client_only` holds files Modrinth lists as
+/// unsupported on servers.
+pub fn diagnose(
+    content: &[(String, ContentMetadata)],
+    remote_client_only: &HashSet<String>,
+) -> Vec<DoctorIssue> {
+    let mut issues = Vec::new();
+
+    for (file, m) in content {
+        if m.environment.as_deref() == Some("client") {
+            issues.push(DoctorIssue {
+                kind: IssueKind::ClientOnly,
+                message: format!("{} ({}) is a client-only mod", m.name, file),
+                files: vec![file.clone()],
+            });
+        }
+    }
+    let mut remote: Vec<&String> = remote_client_only
+        .iter()
+        .filter(|f| !issues.iter().any(|i| i.files.contains(f)))
+        .collect();
+    remote.sort();
+    for file in remote {
+        issues.push(DoctorIssue {
+            kind: IssueKind::ClientOnly,
+            message: format!("{} is marked client-only on Modrinth", file),
+            files: vec![file.clone()],
+        });
+    }
+
+    // Keep the newest copy of a duplicated mod; offer to disable the rest.
+    let mut by_id: HashMap<&str, Vec<&(String, ContentMetadata)>> = HashMap::new();
+    for entry in content {
+        by_id.entry(entry.1.id.as_str()).or_default().push(entry);
+    }
+    let mut duplicates: Vec<_> = by_id.into_iter().filter(|(_, v)| v.len() > 1).collect();
+    duplicates.sort_by(|a, b| a.0.cmp(b.0));
+    for (id, mut copies) in duplicates {
+        copies.sort_by(|a, b| compare_versions(&b.1.version, &a.1.version).then(a.0.cmp(&b.0)));
+        let names: Vec<&str> = copies.iter().map(|(f, _)| f.as_str()).collect();
+        issues.push(DoctorIssue {
+            kind: IssueKind::DuplicateId,
+            message: format!("{} is installed more than once: {}", id, names.join(", ")),
+            files: copies[1..].iter().map(|(f, _)| f.clone()).collect(),
+        });
+    }
+
+    let has = |id: &str| -> Option<&ContentMetadata> {
+        content.iter().map(|(_, m)| m).find(|m| {
+            m.id.eq_ignore_ascii_case(id) || m.provides.iter().any(|p| p.eq_ignore_ascii_case(id))
+        })
+    };
+    for (file, m) in content {
+        for dep in m.dependencies.iter().filter(|d| d.required) {
+            if PLATFORM_IDS.contains(&dep.id.as_str()) || has(&dep.id).is_some() {
+                continue;
+            }
+            issues.push(DoctorIssue {
+                kind: IssueKind::MissingDependency,
+                message: format!(
+                    "{} ({}) requires {}, which is not installed",
+                    m.name, file, dep.id
+                ),
+                files: vec![file.clone()],
+            });
+        }
+        for incompatible in &m.incompatibilities {
+            let Some(other) = has(&incompatible.id) else {
+                continue;
+            };
+            if other.id == m.id
+                || version_satisfies(&m.loader, &incompatible.versions, &other.version)
+                    == Some(false)
+            {
+                continue;
+            }
+            issues.push(DoctorIssue {
+                kind: IssueKind::Incompatible,
+                message: format!(
+                    "{} ({}) {} {} {}",
+                    m.name,
+                    file,
+                    if incompatible.required {
+                        "breaks with"
+                    } else {
+                        "conflicts with"
+                    },
+                    other.name,
+                    other.version
+                ),
+                files: vec![file.clone()],
+            });
+        }
+    }
+    issues
+}
+
+/// Jars in `mods/` whose Modrinth project says it doesn't run on servers.
+pub async fn modrinth_client_only(dir: &Path) -> Result<HashSet<String>, Box<dyn Error>> {
+    let mods_dir = dir.join("mods");
+    let mut by_hash = HashMap::new();
+    for name in crate::utils::list_entries(&mods_dir).unwrap_or_default() {
+        if name.ends_with(".jar") {
+            by_hash.insert(sha1_file(&mods_dir.join(&name))?, format!("mods/{}", name));
+        }
+    }
+    if by_hash.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let client = ferinth::Ferinth::<()>::new("OxideMC", Some(env!("CARGO_PKG_VERSION")), None);
+    let versions = client
+        .version_get_from_multiple_hashes(by_hash.keys().cloned().collect())
+        .await?;
+    let project_ids: Vec<&str> = versions.values().map(|v| v.project_id.as_str()).collect();
+    let client_only: HashSet<String> = client
+        .project_get_multiple(&project_ids)
+        .await?
+        .into_iter()
+        .filter(|p| p.server_side == ProjectSupportRange::Unsupported)
+        .map(|p| p.id)
+        .collect();
+
+    Ok(versions
+        .iter()
+        .filter(|(_, v)| client_only.contains(&v.project_id))
+        .filter_map(|(hash, _)| by_hash.get(hash).cloned())
+        .collect())
+}
+
+/// Run every check. Modrinth lookups are best-effort; offline servers still get the
+/// metadata-based checks.
+pub async fn run_doctor(dir: &Path) -> Vec<DoctorIssue> {
+    let content = scan_content(dir);
+    let remote = modrinth_client_only(dir).await.unwrap_or_default();
+    diagnose(&content, &remote)
+}
+
+/// Disable every file named by `issues`. Returns the files that were disabled and the
+/// ones that failed.
+pub fn disable_issue_files(
+    dir: &PathBuf,
+    issues: &[DoctorIssue],
+) -> (Vec<String>, Vec<(String, String)>) {
+    let mut files: Vec<&String> = issues.iter().flat_map(|i| &i.files).collect();
+    files.sort();
+    files.dedup();
+
+    let mut disabled = Vec::new();
+    let mut failed = Vec::new();
+    for rel in files {
+        let path = dir.join(rel);
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str()))
+        else {
+            continue;
+        };
+        match disable_content(dir, parent, name) {
+            Ok(_) => disabled.push(rel.clone()),
+            Err(e) => failed.push((rel.clone(), e.to_string())),
+        }
+    }
+    (disabled, failed)
+}
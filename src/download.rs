@@ -126,31 +126,8 @@ pub async fn get_jar_url(platform: &str, version: &str) -> Result<String, String
             .map(|s| s.to_string())
             .ok_or_else(|| format!("No download URL found for Paper {}", version))
     } else if platform == "Fabric" {
-        let json_text = reqwest::get(&format!(
-            "https://meta.fabricmc.net/v2/versions/loader/{}",
-            version
-        ))
-        .await
-        .map_err(|e| format!("Failed to fetch Fabric loader info: {}", e))?
-        .text()
-        .await
-        .map_err(|e| format!("Failed to read response: {}", e))?;
-
-        let json: Value = serde_json::from_str(&json_text)
-            .map_err(|e| format!("Failed to parse JSON: {}", e))?;
-
-        let fabric_version = json
-            .as_array()
-            .and_then(|a| a.first())
-            .and_then(|v| v.get("loader"))
-            .and_then(|l| l.get("version"))
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| "Failed to find Fabric loader version".to_string())?;
-
-        Ok(format!(
-            "https://meta.fabricmc.net/v2/versions/loader/{}/{}/1.1.0/server/jar",
-            version, fabric_version
-        ))
+        let fabric_version = latest_fabric_loader(version).await?;
+        Ok(fabric_server_jar_url(version, &fabric_version))
     } else if platform == "Forge" {
         todo!("Forge not implemented");
     } else {
@@ -158,6 +135,38 @@ pub async fn get_jar_url(platform: &str, version: &str) -> Result<String, String
     }
 }
 
+/// Newest Fabric loader version for a Minecraft version.
+pub async fn latest_fabric_loader(game_version: &str) -> Result<String, String> {
+    let json_text = reqwest::get(&format!(
+        "https://meta.fabricmc.net/v2/versions/loader/{}",
+        game_version
+    ))
+    .await
+    .map_err(|e| format!("Failed to fetch Fabric loader info: {}", e))?
+    .text()
+    .await
+    .map_err(|e| format!("Failed to read response: {}", e))?;
+
+    let json: Value =
+        serde_json::from_str(&json_text).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    json.as_array()
+        .and_then(|a| a.first())
+        .and_then(|v| v.get("loader"))
+        .and_then(|l| l.get("version"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| "Failed to find Fabric loader version".to_string())
+}
+
+/// Fabric server launcher for a specific game + loader version.
+pub fn fabric_server_jar_url(game_version: &str, loader_version: &str) -> String {
+    format!(
+        "https://meta.fabricmc.net/v2/versions/loader/{}/{}/1.1.0/server/jar",
+        game_version, loader_version
+    )
+}
+
 pub async fn download_url(url: &str, dir: &PathBuf, filename: &str) -> Result<(), Box<dyn Error>> {
     let client = reqwest::Client::new();
     let mut res = client.get(url).send().await?;
@@ -168,7 +177,7 @@ pub async fn download_url(url: &str, dir: &PathBuf, filename: &str) -> Result<()
 
     let total_size = res.content_length().ok_or("Failed to get content length")?;
 
-    create_dir_all(&dir)?;
+    create_dir_all(dir)?;
 
     let file_path = dir.join(filename);
     let mut file = File::create(&file_path)?;
@@ -193,6 +202,99 @@ pub async fn download_url(url: &str, dir: &PathBuf, filename: &str) -> Result<()
     Ok(())
 }
 
+/// Download `url` into `dir` as `temp_name`, returning the file name the server suggests
+/// (from `Content-Disposition`, falling back to the last segment of the final URL).
+pub async fn download_url_suggested(
+    url: &str,
+    dir: &PathBuf,
+    temp_name: &str,
+) -> Result<String, Box<dyn Error>> {
+    let client = reqwest::Client::new();
+    let mut res = client.get(url).send().await?;
+
+    if !res.status().is_success() {
+        return Err(format!("Server returned error: {}", res.status()).into());
+    }
+
+    let suggested = res
+        .headers()
+        .get(reqwest::header::CONTENT_DISPOSITION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(content_disposition_filename)
+        .or_else(|| url_filename(res.url().as_str()))
+        .ok_or("Could not work out a file name for this download")?;
+
+    create_dir_all(dir)?;
+    let file_path = dir.join(temp_name);
+    let mut file = File::create(&file_path)?;
+
+    let pb = progress_bar(res.content_length().unwrap_or(0));
+    pb.start(format!("Downloading {}", suggested));
+    while let Some(chunk) = res.chunk().await? {
+        file.write_all(&chunk)?;
+        pb.inc(chunk.len() as u64);
+    }
+    pb.stop(format!("Downloaded {}", suggested));
+    Ok(suggested)
+}
+
+/// File name from a `Content-Disposition` header, preferring the RFC 5987 `filename*` form.
+pub fn content_disposition_filename(header: &str) -> Option<String> {
+    let mut plain = None;
+    for param in header.split(';').map(str::trim) {
+        let Some((key, value)) = param.split_once('=') else {
+            continue;
+        };
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename*" => {
+                // charset'language'percent-encoded-name
+                if let Some(encoded) = value.trim().splitn(3, '\'').nth(2) {
+                    return sanitize_filename(&percent_decode(encoded));
+                }
+            }
+            "filename" => plain = sanitize_filename(value.trim().trim_matches('"')),
+            _ => {}
+        }
+    }
+    plain
+}
+
+/// Last path segment of a URL, without query string or fragment.
+pub fn url_filename(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let last = url.path_segments()?.rfind(|s| !s.is_empty())?;
+    sanitize_filename(&percent_decode(last))
+}
+
+/// Keep only the final path component so a server can't pick where the file lands.
+fn sanitize_filename(name: &str) -> Option<String> {
+    let name = name.rsplit(['/', '\\']).next()?.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(b) = u8::from_str_radix(hex, 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
 pub fn convert_to_items(input: &[String]) -> Vec<(String, String, String)> {
     input
         .iter()
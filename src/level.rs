@@ -0,0 +1,336 @@
+use crate::config::read_property;
+use crate::nbt::{read_nbt, read_nbt_file, write_nbt_file, Tag};
+use crate::players::java_date;
+use crate::rcon::{run_server_commands, server_running};
+use crate::worlds::world_locked;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Furthest a border center may be from 0, and the widest border the game allows.
+const MAX_BORDER_CENTER: f64 = 29_999_984.0;
+const MAX_BORDER_SIZE: f64 = 59_999_968.0;
+
+/// `<server>/<level-name>`, the world the server loads.
+pub fn world_dir(dir: &Path) -> PathBuf {
+    let level_name = read_property(dir, "server.properties", "level-name")
+        .unwrap_or_else(|_| "world".to_string());
+    dir.join(level_name)
+}
+
+/// The world border as saved in `level.dat`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldBorder {
+    pub center_x: f64,
+    pub center_z: f64,
+    /// Side length in blocks (the game's "diameter").
+    pub size: f64,
+    pub damage_per_block: f64,
+    pub safe_zone: f64,
+    pub warning_blocks: f64,
+    pub warning_time: f64,
+}
+
+/// The saved state of an existing world, from `level.dat`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WorldInfo {
+    pub level_name: Option<String>,
+    pub seed: Option<i64>,
+    pub data_version: Option<i64>,
+    /// Version that last saved the world, e.g. "1.21.4".
+    pub game_version: Option<String>,
+    pub spawn: Option<[i64; 3]>,
+    /// Game rules in file order, values as the game shows them.
+    pub game_rules: Vec<(String, String)>,
+    pub border: Option<WorldBorder>,
+    /// Unix time in milliseconds.
+    pub last_played: Option<i64>,
+}
+
+/// Read `level.dat` of the server's current world.
+pub fn read_world_info(dir: &Path) -> Result<WorldInfo, Box<dyn Error>> {
+    let path = world_dir(dir).join("level.dat");
+    if !path.exists() {
+        return Err(format!(
+            "{} not found; the world is created on first start",
+            path.display()
+        )
+        .into());
+    }
+    Ok(world_info(&read_nbt_file(&path)?))
+}
+
+/// Pull the displayed fields out of a `level.dat` root compound.
+pub fn world_info(root: &Tag) -> WorldInfo {
+    let data = root.get("Data").unwrap_or(root);
+    let int = |path: &str| data.path(path).and_then(Tag::as_i64);
+    let float = |path: &str| data.path(path).and_then(Tag::as_f64);
+
+    // 1.21.9 groups the spawn into a compound; older versions use SpawnX/Y/Z
+    let spawn = match data.path("spawn.pos") {
+        Some(Tag::IntArray(pos)) if pos.len() == 3 => {
+            Some([pos[0] as i64, pos[1] as i64, pos[2] as i64])
+        }
+        _ => match (int("SpawnX"), int("SpawnY"), int("SpawnZ")) {
+            (Some(x), Some(y), Some(z)) => Some([x, y, z]),
+            _ => None,
+        },
+    };
+
+    let game_rules = data
+        .get("GameRules")
+        .or_else(|| data.get("game_rules"))
+        .and_then(Tag::as_compound)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|(rule, value)| {
+            let value = match value {
+                Tag::String(s) => s.clone(),
+                Tag::Byte(b) => (*b != 0).to_string(),
+                other => other.as_f64()?.to_string(),
+            };
+            Some((rule.clone(), value))
+        })
+        .collect();
+
+    let border = float("BorderSize").map(|size| WorldBorder {
+        center_x: float("BorderCenterX").unwrap_or(0.0),
+        center_z: float("BorderCenterZ").unwrap_or(0.0),
+        size,
+        damage_per_block: float("BorderDamagePerBlock").unwrap_or(0.2),
+        safe_zone: float("BorderSafeZone").unwrap_or(5.0),
+        warning_blocks: float("BorderWarningBlocks").unwrap_or(5.0),
+        warning_time: float("BorderWarningTime").unwrap_or(15.0),
+    });
+
+    WorldInfo {
+        level_name: data
+            .get("LevelName")
+            .and_then(Tag::as_str)
+            .map(str::to_string),
+        // 1.16 moved the seed into WorldGenSettings
+        seed: int("WorldGenSettings.seed").or_else(|| int("RandomSeed")),
+        data_version: int("DataVersion"),
+        game_version: data
+            .path("Version.Name")
+            .and_then(Tag::as_str)
+            .map(str::to_string),
+        spawn,
+        game_rules,
+        border,
+        last_played: int("LastPlayed"),
+    }
+}
+
+/// Multi-line description of a world for the world menu.
+pub fn world_details(info: &WorldInfo) -> String {
+    let mut lines = vec![info.level_name.as_deref().unwrap_or("world").to_string()];
+    if let Some(seed) = info.seed {
+        lines.push(format!("Seed: {}", seed));
+    }
+    match (&info.game_version, info.data_version) {
+        (Some(version), Some(data)) => lines.push(format!(
+            "Saved by: Minecraft {} (DataVersion {})",
+            version, data
+        )),
+        (Some(version), None) => lines.push(format!("Saved by: Minecraft {}", version)),
+        (None, Some(data)) => lines.push(format!("DataVersion: {}", data)),
+        (None, None) => {}
+    }
+    if let Some(ms) = info.last_played {
+        lines.push(format!(
+            "Last played: {} UTC",
+            &java_date((ms.max(0) / 1000) as u64)[..16]
+        ));
+    }
+    if let Some([x, y, z]) = info.spawn {
+        lines.push(format!("Spawn: {}, {}, {}", x, y, z));
+    }
+    if let Some(border) = &info.border {
+        lines.push(format!(
+            "World border: {} blocks wide, centered on {}, {}",
+            border.size, border.center_x, border.center_z
+        ));
+    }
+    if !info.game_rules.is_empty() {
+        lines.push(String::new());
+        lines.push(format!("Game rules ({}):", info.game_rules.len()));
+        for (rule, value) in &info.game_rules {
+            lines.push(format!("  {} = {}", rule, value));
+        }
+    }
+    lines.join("\n")
+}
+
+/// A change to a world's border or game rules.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorldChange {
+    BorderCenter { x: f64, z: f64 },
+    BorderSize(f64),
+    GameRule { rule: String, value: String },
+}
+
+impl WorldChange {
+    /// The console command that makes this change on a running server.
+    pub fn command(&self) -> String {
+        match self {
+            WorldChange::BorderCenter { x, z } => format!("worldborder center {} {}", x, z),
+            WorldChange::BorderSize(size) => format!("worldborder set {}", size),
+            WorldChange::GameRule { rule, value } => format!("gamerule {} {}", rule, value),
+        }
+    }
+
+    /// Reject values the game would refuse, and anything that could smuggle a second
+    /// command over RCON.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            WorldChange::BorderCenter { x, z } => {
+                if !(x.abs() <= MAX_BORDER_CENTER && z.abs() <= MAX_BORDER_CENTER) {
+                    return Err(format!(
+                        "The border center must be within {} blocks of 0",
+                        MAX_BORDER_CENTER
+                    ));
+                }
+            }
+            WorldChange::BorderSize(size) => {
+                if !(1.0..=MAX_BORDER_SIZE).contains(size) {
+                    return Err(format!(
+                        "The border size must be between 1 and {}",
+                        MAX_BORDER_SIZE
+                    ));
+                }
+            }
+            WorldChange::GameRule { rule, value } => {
+                let rule_ok = !rule.is_empty()
+                    && rule
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '.'));
+                if !rule_ok {
+                    return Err(format!("'{}' is not a game rule name", rule));
+                }
+                if value.is_empty() || !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                {
+                    return Err(format!("'{}' is not a valid value for {}", value, rule));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Make this change to the `Data` compound of `level.dat`.
+    fn apply_to(&self, data: &mut Tag) -> Result<(), String> {
+        match self {
+            WorldChange::BorderCenter { x, z } => {
+                data.set("BorderCenterX", Tag::Double(*x));
+                data.set("BorderCenterZ", Tag::Double(*z));
+            }
+            WorldChange::BorderSize(size) => {
+                data.set("BorderSize", Tag::Double(*size));
+                // Stop any border animation in progress from moving it again
+                data.set("BorderSizeLerpTarget", Tag::Double(*size));
+                data.set("BorderSizeLerpTime", Tag::Long(0));
+            }
+            WorldChange::GameRule { rule, value } => {
+                let rules_key = if data.get("game_rules").is_some() {
+                    "game_rules"
+                } else {
+                    "GameRules"
+                };
+                let rules = data
+                    .get_mut(rules_key)
+                    .ok_or("level.dat has no game rules")?;
+                let current = rules
+                    .get(rule)
+                    .ok_or_else(|| format!("Unknown game rule '{}'", rule))?;
+                rules.set(rule, game_rule_tag(rule, current, value)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// New value for a game rule, checked against and stored like the current one: strings in
+/// `GameRules`, typed tags in the newer `game_rules`.
+fn game_rule_tag(rule: &str, current: &Tag, value: &str) -> Result<Tag, String> {
+    let is_bool = match current {
+        Tag::String(s) => s == "true" || s == "false",
+        Tag::Byte(_) => true,
+        _ => false,
+    };
+    if is_bool {
+        let flag: bool = value
+            .parse()
+            .map_err(|_| format!("{} must be true or false", rule))?;
+        return Ok(match current {
+            Tag::Byte(_) => Tag::Byte(flag as i8),
+            _ => Tag::String(flag.to_string()),
+        });
+    }
+    let number: i32 = value
+        .parse()
+        .map_err(|_| format!("{} must be a whole number", rule))?;
+    Ok(match current {
+        Tag::String(_) => Tag::String(number.to_string()),
+        _ => Tag::Int(number),
+    })
+}
+
+/// Apply world changes over RCON while the server runs, otherwise straight to `level.dat`
+/// (all of them or none). Returns a line per change for the user.
+pub fn apply_world_changes(
+    dir: &Path,
+    changes: &[WorldChange],
+) -> Result<Vec<String>, Box<dyn Error>> {
+    for change in changes {
+        change.validate()?;
+    }
+    if changes.is_empty() {
+        return Ok(vec![]);
+    }
+    if server_running(dir) {
+        let commands: Vec<String> = changes.iter().map(WorldChange::command).collect();
+        return run_server_commands(dir, &commands);
+    }
+
+    let world = world_dir(dir);
+    if world_locked(&world) {
+        return Err(
+            "The server has the world open but isn't answering on its port; wait for it to stop or stop it first"
+                .into(),
+        );
+    }
+    let path = world.join("level.dat");
+    if !path.exists() {
+        return Err("The world doesn't exist yet; start the server once to create it".into());
+    }
+    let (name, mut root) = read_nbt(&fs::read(&path)?)?;
+    let data = root
+        .get_mut("Data")
+        .ok_or("level.dat has no Data section")?;
+    for change in changes {
+        change.apply_to(data)?;
+    }
+    write_nbt_file(&path, &name, &root)?;
+    Ok(changes
+        .iter()
+        .map(|change| match change {
+            WorldChange::BorderCenter { x, z } => format!("World border centered on {}, {}", x, z),
+            WorldChange::BorderSize(size) => format!("World border set to {} blocks wide", size),
+            WorldChange::GameRule { rule, value } => format!("Game rule {} set to {}", rule, value),
+        })
+        .collect())
+}
+
+/// A preset's `gamerules` as world changes, in name order.
+pub fn game_rule_changes(rules: &HashMap<String, String>) -> Vec<WorldChange> {
+    let mut names: Vec<&String> = rules.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|rule| WorldChange::GameRule {
+            rule: rule.clone(),
+            value: rules[rule].clone(),
+        })
+        .collect()
+}
@@ -0,0 +1,135 @@
+use crate::utils::{to_hex, write_atomic};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const LOCKFILE_NAME: &str = "oxidemc.lock";
+
+/// Exact record of every file OxideMC installed, stored as `oxidemc.lock` in the server directory.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct LockFile {
+    #[serde(default)]
+    pub files: Vec<LockEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LockEntry {
+    /// Path relative to the server directory, e.g. `mods/sodium-fabric-0.6.jar`.
+    pub path: String,
+    #[serde(flatten)]
+    pub origin: FileOrigin,
+    pub sha512: String,
+    pub size: u64,
+}
+
+/// Where an installed file came from.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FileOrigin {
+    /// `modrinth`, `curseforge`, `url` or `file`.
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl LockFile {
+    pub fn find(&self, path: &str) -> Option<&LockEntry> {
+        self.files.iter().find(|e| e.path == path)
+    }
+
+    /// Insert or replace the entry for `entry.path`.
+    pub fn upsert(&mut self, entry: LockEntry) {
+        self.files.retain(|e| e.path != entry.path);
+        self.files.push(entry);
+        self.files.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        self.files.retain(|e| e.path != path);
+    }
+}
+
+/// Read `oxidemc.lock`, returning an empty lockfile if there is none yet.
+pub fn read_lockfile(dir: &Path) -> Result<LockFile, Box<dyn Error>> {
+    let path = dir.join(LOCKFILE_NAME);
+    if !path.exists() {
+        return Ok(LockFile::default());
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(&path)?)?)
+}
+
+pub fn write_lockfile(dir: &Path, lock: &LockFile) -> Result<(), Box<dyn Error>> {
+    write_atomic(
+        &dir.join(LOCKFILE_NAME),
+        serde_json::to_string_pretty(lock)?,
+    )
+}
+
+/// Path of `file_name` inside `install_dir`, relative to the server directory.
+pub fn lock_path(dir: &PathBuf, install_dir: &Path, file_name: &str) -> String {
+    let full = install_dir.join(file_name);
+    full.strip_prefix(dir)
+        .unwrap_or(&full)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Hash a freshly installed file and record it in the lockfile.
+pub fn record_install(
+    dir: &PathBuf,
+    install_dir: &Path,
+    file_name: &str,
+    origin: FileOrigin,
+) -> Result<LockEntry, Box<dyn Error>> {
+    let bytes = fs::read(install_dir.join(file_name))?;
+    let entry = LockEntry {
+        path: lock_path(dir, install_dir, file_name),
+        origin,
+        sha512: to_hex(&Sha512::digest(&bytes)),
+        size: bytes.len() as u64,
+    };
+    let mut lock = read_lockfile(dir)?;
+    lock.upsert(entry.clone());
+    write_lockfile(dir, &lock)?;
+    Ok(entry)
+}
+
+/// Drop the lockfile entry for a file that was removed or replaced.
+pub fn forget_install(
+    dir: &PathBuf,
+    install_dir: &Path,
+    file_name: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut lock = read_lockfile(dir)?;
+    let path = lock_path(dir, install_dir, file_name);
+    if lock.find(&path).is_some() {
+        lock.remove(&path);
+        write_lockfile(dir, &lock)?;
+    }
+    Ok(())
+}
+
+/// Move the lockfile entry for a file that was renamed in place (e.g. disabled).
+pub fn rename_install(
+    dir: &PathBuf,
+    install_dir: &Path,
+    old_name: &str,
+    new_name: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut lock = read_lockfile(dir)?;
+    let old_path = lock_path(dir, install_dir, old_name);
+    let Some(mut entry) = lock.find(&old_path).cloned() else {
+        return Ok(());
+    };
+    lock.remove(&old_path);
+    entry.path = lock_path(dir, install_dir, new_name);
+    lock.upsert(entry);
+    write_lockfile(dir, &lock)
+}
@@ -1,17 +1,51 @@
+pub mod bisect;
 pub mod config;
+pub mod doctor;
 pub mod download;
+pub mod level;
+pub mod lockfile;
+pub mod metadata;
+pub mod modpack;
 pub mod mods;
+pub mod nbt;
+pub mod playerdata;
+pub mod players;
+pub mod pregen;
 pub mod preset;
+pub mod properties;
+pub mod rcon;
+pub mod region;
+pub mod schema;
 pub mod server;
+pub mod storage;
 pub mod utils;
+pub mod worlds;
+pub mod yaml;
 
 // Re-export everything so tests (and other modules) can use `use super::*`
+pub use bisect::*;
 pub use config::*;
+pub use doctor::*;
 pub use download::*;
+pub use level::*;
+pub use lockfile::*;
+pub use metadata::*;
+pub use modpack::*;
 pub use mods::*;
+pub use nbt::*;
+pub use playerdata::*;
+pub use players::*;
+pub use pregen::*;
 pub use preset::*;
+pub use properties::*;
+pub use rcon::*;
+pub use region::*;
+pub use schema::*;
 pub use server::*;
+pub use storage::*;
 pub use utils::*;
+pub use worlds::*;
+pub use yaml::*;
 
 use cliclack::{input, log, select};
 use std::env;
@@ -24,6 +58,8 @@ async fn main() {
         match args[1].to_lowercase().as_str() {
             "install" | "--install" | "i" | "-i" => "install".to_string(),
             "configure" | "--configure" | "c" | "-c" => "configure".to_string(),
+            "players" | "--players" | "p" | "-p" => "players".to_string(),
+            "pregen" | "--pregen" => "pregen".to_string(),
             _ => todo!("WHAT?"),
         }
     } else {
@@ -36,12 +72,42 @@ async fn main() {
     };
 
     if action == "install" {
-        let _oxide = OxideMC::setup().await;
+        if let Some(pack) = arg_value(&args, "--mrpack") {
+            if let Err(e) = OxideMC::setup_from_mrpack(&PathBuf::from(pack)).await {
+                eprintln!("Failed to install modpack: {}", e);
+                std::process::exit(1);
+            }
+        } else if let Some(pack) = arg_value(&args, "--curseforge") {
+            if let Err(e) = OxideMC::setup_from_curseforge(&PathBuf::from(pack)).await {
+                eprintln!("Failed to install modpack: {}", e);
+                std::process::exit(1);
+            }
+        } else {
+            let _oxide = OxideMC::setup().await;
+        }
     } else if action == "configure" {
-        let dir: PathBuf = input("Enter the path to your server directory:")
-            .interact()
-            .unwrap();
+        let dir = server_dir_arg(&args);
         match OxideMC::open(&dir) {
+            Ok(oxide) if args.iter().any(|a| a == "--set") => {
+                // `configure --dir <path> --set key=value [--set key=value ...]`
+                let edits: Result<Vec<(&str, &str)>, String> = args
+                    .iter()
+                    .zip(args.iter().skip(1))
+                    .filter(|(flag, _)| *flag == "--set")
+                    .map(|(_, edit)| {
+                        edit.split_once('=')
+                            .ok_or_else(|| format!("Expected key=value, got '{}'", edit))
+                    })
+                    .collect();
+                match edits {
+                    Ok(edits) if oxide.set_properties(&edits) => {}
+                    Ok(_) => std::process::exit(1),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
             Ok(oxide) => {
                 log::info(format!(
                     "Server found: {} {}",
@@ -55,8 +121,97 @@ async fn main() {
                 std::process::exit(1);
             }
         }
+    } else if action == "players" {
+        // `players --dir <path> show Steve`, `players --dir <path> op Steve --level 2`, ...
+        let command = match parse_player_command(&args[2..]) {
+            Ok(command) => command,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        let dir = server_dir_arg(&args);
+        let result = match command {
+            PlayerCommand::List(list) => player_list_rows(&dir, list).map(|rows| {
+                rows.iter()
+                    .map(|(name, details)| format!("{}\t{}", name, details))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }),
+            PlayerCommand::ListPlayers => list_players(&dir).map(|players| {
+                players
+                    .iter()
+                    .map(|p| format!("{}\t{}", p.name.as_deref().unwrap_or("?"), p.uuid))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }),
+            PlayerCommand::ShowPlayer(name) => find_player(&dir, &name).map(|p| player_details(&p)),
+            PlayerCommand::Change(change) => apply_player_change(&dir, &change).await,
+        };
+        match result {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => println!("{}", output),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if action == "pregen" {
+        // `pregen --dir <path> --radius 5000 [--center x,z]`
+        let radius = match arg_value(&args, "--radius").map(|r| r.parse::<f64>()) {
+            Some(Ok(radius)) => radius,
+            _ => {
+                eprintln!("Usage: pregen --dir <path> --radius <blocks> [--center x,z]");
+                std::process::exit(1);
+            }
+        };
+        let dir = server_dir_arg(&args);
+        let center = match arg_value(&args, "--center") {
+            Some(center) => center
+                .split_once(',')
+                .and_then(|(x, z)| Some((x.trim().parse().ok()?, z.trim().parse().ok()?)))
+                .unwrap_or_else(|| {
+                    eprintln!("Expected --center x,z, got '{}'", center);
+                    std::process::exit(1);
+                }),
+            // The world spawn, or 0,0 before the world exists
+            None => read_world_info(&dir)
+                .ok()
+                .and_then(|info| info.spawn)
+                .map_or((0.0, 0.0), |[x, _, z]| (x as f64, z as f64)),
+        };
+        match OxideMC::open(&dir) {
+            Ok(oxide) => {
+                if oxide.pregen(center, radius).await.is_err() {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to open server: {}", e);
+                std::process::exit(1);
+            }
+        }
     }
 }
 
+/// Server directory from `--dir`, or asked for interactively.
+fn server_dir_arg(args: &[String]) -> PathBuf {
+    match arg_value(args, "--dir") {
+        Some(dir) => PathBuf::from(dir),
+        None => input("Enter the path to your server directory:")
+            .interact()
+            .unwrap(),
+    }
+}
+
+/// Value following a `--flag` on the command line, if present.
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
+
 #[cfg(test)]
+#[allow(clippy::unnecessary_to_owned)] // older tests pass `&tmp.path().to_path_buf()` as the dir
 mod tests;
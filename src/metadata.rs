@@ -0,0 +1,734 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// What a mod or plugin jar says about itself.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ContentMetadata {
+    /// `fabric`, `quilt`, `forge`, `neoforge`, `bukkit` or `paper`.
+    pub loader: String,
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<DeclaredDependency>,
+    /// Supported Minecraft versions as declared: a semver predicate for Fabric/Quilt,
+    /// a Maven range for Forge/NeoForge, or the minimum `api-version` for plugins.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_versions: Option<String>,
+    /// Extra IDs this jar satisfies: Fabric `provides` and mods bundled in `META-INF/jars`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provides: Vec<String>,
+    /// Mods this one refuses to run with. `required` marks a hard break rather than a
+    /// "works badly with" conflict.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub incompatibilities: Vec<DeclaredDependency>,
+    /// `client`, `server` or `*` (both) where the jar says so.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DeclaredDependency {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub versions: String,
+    pub required: bool,
+}
+
+/// IDs that refer to the game, the loader or the JVM rather than another mod.
+pub const PLATFORM_IDS: [&str; 8] = [
+    "minecraft",
+    "java",
+    "fabricloader",
+    "fabric-loader",
+    "quilt_loader",
+    "forge",
+    "neoforge",
+    "javafml",
+];
+
+/// Open a jar and parse the first metadata file found in it. `None` means the jar
+/// carries no metadata OxideMC understands.
+pub fn read_jar_metadata(path: &PathBuf) -> Result<Option<ContentMetadata>, Box<dyn Error>> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let a = &mut archive;
+
+    let metadata = if let Some(text) = read_entry(a, "fabric.mod.json") {
+        let mut metadata = parse_fabric_mod_json(&text)?;
+        metadata.provides.extend(nested_fabric_ids(a, &text));
+        metadata
+    } else if let Some(text) = read_entry(a, "quilt.mod.json") {
+        parse_quilt_mod_json(&text)?
+    } else if let Some(text) = read_entry(a, "META-INF/neoforge.mods.toml") {
+        let manifest = read_entry(a, "META-INF/MANIFEST.MF");
+        parse_mods_toml(&text, "neoforge", manifest.as_deref())?
+    } else if let Some(text) = read_entry(a, "META-INF/mods.toml") {
+        let manifest = read_entry(a, "META-INF/MANIFEST.MF");
+        parse_mods_toml(&text, "forge", manifest.as_deref())?
+    } else if let Some(text) = read_entry(a, "paper-plugin.yml") {
+        parse_plugin_yml(&text, "paper")?
+    } else if let Some(text) = read_entry(a, "plugin.yml") {
+        parse_plugin_yml(&text, "bukkit")?
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(metadata))
+}
+
+fn read_entry<R: Read + std::io::Seek>(archive: &mut ZipArchive<R>, name: &str) -> Option<String> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut text = String::new();
+    entry.read_to_string(&mut text).ok()?;
+    Some(text)
+}
+
+/// IDs of the mods a Fabric jar bundles through its `jars` list (jar-in-jar).
+fn nested_fabric_ids(archive: &mut ZipArchive<File>, fabric_mod_json: &str) -> Vec<String> {
+    let Ok(json) = serde_json::from_str::<Value>(fabric_mod_json) else {
+        return vec![];
+    };
+    let mut ids = Vec::new();
+    for nested in json
+        .get("jars")
+        .and_then(|j| j.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let Some(file) = nested.get("file").and_then(|f| f.as_str()) else {
+            continue;
+        };
+        let mut bytes = Vec::new();
+        let Ok(mut entry) = archive.by_name(file) else {
+            continue;
+        };
+        if entry.read_to_end(&mut bytes).is_err() {
+            continue;
+        }
+        let Ok(mut inner) = ZipArchive::new(std::io::Cursor::new(bytes)) else {
+            continue;
+        };
+        if let Some(metadata) = read_entry(&mut inner, "fabric.mod.json")
+            .and_then(|text| parse_fabric_mod_json(&text).ok())
+        {
+            ids.push(metadata.id);
+            ids.extend(metadata.provides);
+        }
+    }
+    ids
+}
+
+/// Fabric `depends` values are a version predicate or a list of alternatives.
+fn fabric_predicate(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect::<Vec<_>>()
+            .join(" || "),
+        _ => String::new(),
+    }
+}
+
+pub fn parse_fabric_mod_json(text: &str) -> Result<ContentMetadata, Box<dyn Error>> {
+    let json: Value = serde_json::from_str(text)?;
+    let id = json
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or("fabric.mod.json has no id")?
+        .to_string();
+
+    let mut dependencies = Vec::new();
+    let mut game_versions = None;
+    for (key, required) in [("depends", true), ("recommends", false)] {
+        let Some(deps) = json.get(key).and_then(|d| d.as_object()) else {
+            continue;
+        };
+        for (dep, versions) in deps {
+            let versions = fabric_predicate(versions);
+            if dep == "minecraft" {
+                game_versions = Some(versions);
+                continue;
+            }
+            dependencies.push(DeclaredDependency {
+                id: dep.clone(),
+                versions,
+                required,
+            });
+        }
+    }
+
+    let mut incompatibilities = Vec::new();
+    for (key, hard) in [("breaks", true), ("conflicts", false)] {
+        for (dep, versions) in json
+            .get(key)
+            .and_then(|d| d.as_object())
+            .into_iter()
+            .flatten()
+        {
+            incompatibilities.push(DeclaredDependency {
+                id: dep.clone(),
+                versions: fabric_predicate(versions),
+                required: hard,
+            });
+        }
+    }
+
+    let provides = json
+        .get("provides")
+        .and_then(|p| p.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p.as_str().map(|s| s.to_string()))
+        .collect();
+
+    Ok(ContentMetadata {
+        loader: "fabric".to_string(),
+        name: json
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&id)
+            .to_string(),
+        version: json
+            .get("version")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        id,
+        dependencies,
+        game_versions,
+        provides,
+        incompatibilities,
+        environment: json
+            .get("environment")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+    })
+}
+
+pub fn parse_quilt_mod_json(text: &str) -> Result<ContentMetadata, Box<dyn Error>> {
+    let json: Value = serde_json::from_str(text)?;
+    let loader = json
+        .get("quilt_loader")
+        .ok_or("quilt.mod.json has no quilt_loader section")?;
+    let id = loader
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or("quilt.mod.json has no id")?
+        .to_string();
+
+    let list = |key: &str| -> Vec<(String, String, bool)> {
+        loader
+            .get(key)
+            .and_then(|d| d.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(quilt_dependency)
+            .collect()
+    };
+
+    let mut dependencies = Vec::new();
+    let mut game_versions = None;
+    for (dep_id, versions, required) in list("depends") {
+        if dep_id == "minecraft" {
+            game_versions = Some(versions);
+        } else {
+            dependencies.push(DeclaredDependency {
+                id: dep_id,
+                versions,
+                required,
+            });
+        }
+    }
+    let incompatibilities = list("breaks")
+        .into_iter()
+        .map(|(id, versions, hard)| DeclaredDependency {
+            id,
+            versions,
+            required: hard,
+        })
+        .collect();
+    // Quilt calls the server side `dedicated_server`.
+    let environment = json
+        .pointer("/minecraft/environment")
+        .and_then(|v| v.as_str())
+        .map(|e| if e == "dedicated_server" { "server" } else { e }.to_string());
+
+    Ok(ContentMetadata {
+        loader: "quilt".to_string(),
+        name: loader
+            .pointer("/metadata/name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&id)
+            .to_string(),
+        version: loader
+            .get("version")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        id,
+        dependencies,
+        game_versions,
+        incompatibilities,
+        environment,
+        ..Default::default()
+    })
+}
+
+/// A Quilt dependency is either a bare ID or an object with id / versions / optional.
+fn quilt_dependency(dep: &Value) -> Option<(String, String, bool)> {
+    match dep {
+        Value::String(s) => Some((s.clone(), String::new(), true)),
+        Value::Object(o) => Some((
+            o.get("id")?.as_str()?.to_string(),
+            o.get("versions").map(fabric_predicate).unwrap_or_default(),
+            !o.get("optional").and_then(|v| v.as_bool()).unwrap_or(false),
+        )),
+        _ => None,
+    }
+}
+
+/// Parse Forge's `mods.toml` or NeoForge's `neoforge.mods.toml`. `${file.jarVersion}` is
+/// resolved from the jar manifest's `Implementation-Version` when it is available.
+pub fn parse_mods_toml(
+    text: &str,
+    loader: &str,
+    manifest: Option<&str>,
+) -> Result<ContentMetadata, Box<dyn Error>> {
+    let doc: toml::Value = toml::from_str(text)?;
+    let first = doc
+        .get("mods")
+        .and_then(|m| m.as_array())
+        .and_then(|m| m.first())
+        .ok_or("mods.toml has no [[mods]] entry")?;
+    let id = first
+        .get("modId")
+        .and_then(|v| v.as_str())
+        .ok_or("mods.toml has no modId")?
+        .to_string();
+
+    let mut version = first
+        .get("version")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    if version == "${file.jarVersion}" {
+        if let Some(implementation) = manifest.and_then(|m| {
+            m.lines()
+                .find_map(|l| l.strip_prefix("Implementation-Version:"))
+                .map(|v| v.trim().to_string())
+        }) {
+            version = implementation;
+        }
+    }
+
+    let mut dependencies = Vec::new();
+    let mut incompatibilities = Vec::new();
+    let mut game_versions = None;
+    let mut client_only = doc
+        .get("clientSideOnly")
+        .or_else(|| first.get("clientSideOnly"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    for dep in doc
+        .get("dependencies")
+        .and_then(|d| d.get(&id))
+        .and_then(|d| d.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let dep_id = dep
+            .get("modId")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let versions = dep
+            .get("versionRange")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let kind = dep
+            .get("type")
+            .and_then(|v| v.as_str())
+            .map(|k| k.to_ascii_lowercase());
+        if matches!(kind.as_deref(), Some("incompatible" | "discouraged")) {
+            incompatibilities.push(DeclaredDependency {
+                id: dep_id,
+                versions,
+                required: kind.as_deref() == Some("incompatible"),
+            });
+            continue;
+        }
+        // Forge uses `mandatory`, NeoForge uses `type = "required"`.
+        let required = match kind {
+            Some(kind) => kind == "required",
+            None => dep
+                .get("mandatory")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        };
+        // A client-side requirement on the game or loader means the mod is client-only.
+        let side = dep.get("side").and_then(|v| v.as_str()).unwrap_or("BOTH");
+        if side.eq_ignore_ascii_case("client")
+            && matches!(dep_id.as_str(), "minecraft" | "forge" | "neoforge")
+        {
+            client_only = true;
+        }
+        if dep_id == "minecraft" {
+            game_versions = Some(versions);
+        } else if !dep_id.is_empty() {
+            dependencies.push(DeclaredDependency {
+                id: dep_id,
+                versions,
+                required,
+            });
+        }
+    }
+
+    Ok(ContentMetadata {
+        loader: loader.to_string(),
+        name: first
+            .get("displayName")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&id)
+            .to_string(),
+        version,
+        id,
+        dependencies,
+        game_versions,
+        incompatibilities,
+        environment: client_only.then(|| "client".to_string()),
+        ..Default::default()
+    })
+}
+
+/// Parse a Bukkit `plugin.yml` or a `paper-plugin.yml`.
+pub fn parse_plugin_yml(text: &str, loader: &str) -> Result<ContentMetadata, Box<dyn Error>> {
+    let doc: serde_yaml::Value = serde_yaml::from_str(text)?;
+    let str_field = |key: &str| -> Option<String> {
+        doc.get(key).and_then(|v| match v {
+            serde_yaml::Value::String(s) => Some(s.clone()),
+            serde_yaml::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+    };
+    let name = str_field("name").ok_or("plugin.yml has no name")?;
+
+    let mut dependencies = Vec::new();
+    let list = |key: &str| -> Vec<String> {
+        doc.get(key)
+            .and_then(|v| v.as_sequence())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect()
+    };
+    for (key, required) in [("depend", true), ("softdepend", false)] {
+        for id in list(key) {
+            dependencies.push(DeclaredDependency {
+                id,
+                versions: String::new(),
+                required,
+            });
+        }
+    }
+    // paper-plugin.yml: `dependencies: { server: { Name: { required: true } } }`,
+    // or the older list form `dependencies: [ { name: Name, required: true } ]`.
+    match doc.get("dependencies") {
+        Some(serde_yaml::Value::Mapping(groups)) => {
+            for (group, deps) in groups {
+                if group.as_str() != Some("server") {
+                    continue;
+                }
+                for (id, spec) in deps.as_mapping().into_iter().flatten() {
+                    let Some(id) = id.as_str() else {
+                        continue;
+                    };
+                    dependencies.push(DeclaredDependency {
+                        id: id.to_string(),
+                        versions: String::new(),
+                        required: spec
+                            .get("required")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(true),
+                    });
+                }
+            }
+        }
+        Some(serde_yaml::Value::Sequence(deps)) => {
+            for dep in deps {
+                let Some(id) = dep.get("name").and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                dependencies.push(DeclaredDependency {
+                    id: id.to_string(),
+                    versions: String::new(),
+                    required: dep
+                        .get("required")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(true),
+                });
+            }
+        }
+        _ => {}
+    }
+
+    Ok(ContentMetadata {
+        loader: loader.to_string(),
+        id: name.clone(),
+        name,
+        version: str_field("version").unwrap_or_default(),
+        dependencies,
+        game_versions: str_field("api-version"),
+        environment: Some("server".to_string()),
+        ..Default::default()
+    })
+}
+
+/// Numeric parts of a version, ignoring pre-release/build suffixes (`1.21-rc1` -> `[1, 21]`).
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map_while(|p| p.parse().ok())
+        .collect()
+}
+
+/// Compare two dotted versions, treating missing parts as zero (`1.21` == `1.21.0`).
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let (a, b) = (version_parts(a), version_parts(b));
+    for i in 0..a.len().max(b.len()) {
+        let ord = a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0));
+        if ord.is_ne() {
+            return ord;
+        }
+    }
+    std::cmp::Ordering::Equal
+}
+
+/// Does `version` satisfy a Fabric/Quilt predicate such as `>=1.20 <1.21`, `~1.20.1`,
+/// `1.21.x` or `*`? Alternatives are joined with `||`.
+fn matches_semver_predicate(predicate: &str, version: &str) -> Option<bool> {
+    use std::cmp::Ordering::*;
+    let mut any_parsed = false;
+    for alternative in predicate.split("||") {
+        let mut all = true;
+        for term in alternative.split_whitespace() {
+            let op_len = term
+                .find(|c: char| c.is_ascii_digit() || c == '*' || c == 'x' || c == 'X')
+                .unwrap_or(term.len());
+            let (op, target) = term.split_at(op_len);
+            if target == "*" || (target.is_empty() && op.is_empty()) {
+                continue;
+            }
+            if version_parts(target).is_empty() && !target.contains(['x', 'X', '*']) {
+                return None;
+            }
+            let ord = compare_versions(version, target);
+            let ok = if target.contains(['x', 'X', '*']) {
+                let prefix = version_parts(target);
+                version_parts(version).starts_with(&prefix)
+            } else {
+                match op {
+                    ">=" => ord != Less,
+                    "<=" => ord != Greater,
+                    ">" => ord == Greater,
+                    "<" => ord == Less,
+                    "~" => {
+                        // Same major.minor, at least the given patch.
+                        let t = version_parts(target);
+                        ord != Less && version_parts(version).get(..2) == t.get(..2)
+                    }
+                    "^" => {
+                        let t = version_parts(target);
+                        ord != Less && version_parts(version).first() == t.first()
+                    }
+                    "" | "=" => ord == Equal,
+                    _ => return None,
+                }
+            };
+            all &= ok;
+        }
+        any_parsed = true;
+        if all {
+            return Some(true);
+        }
+    }
+    any_parsed.then_some(false)
+}
+
+/// Does `version` fall inside a Maven range like `[1.20.1,1.21)` or `[1.20,)`?
+/// Several ranges may be separated by commas: `[1.18,1.19),[1.20,)`.
+fn matches_maven_range(range: &str, version: &str) -> Option<bool> {
+    use std::cmp::Ordering::*;
+    let range = range.trim();
+    if range.is_empty() || range == "*" {
+        return Some(true);
+    }
+    if !range.starts_with(['[', '(']) {
+        // A bare version is a soft requirement: "this version or anything else".
+        return Some(true);
+    }
+    let mut rest = range;
+    while !rest.is_empty() {
+        let end = rest.find([']', ')'])?;
+        let (spec, tail) = rest.split_at(end + 1);
+        rest = tail.trim_start_matches(',').trim();
+        if spec.len() < 2 || !spec.starts_with(['[', '(']) {
+            return None;
+        }
+        let inclusive_low = spec.starts_with('[');
+        let inclusive_high = spec.ends_with(']');
+        let inner = &spec[1..spec.len() - 1];
+        let ok = match inner.split_once(',') {
+            None => compare_versions(version, inner.trim()) == Equal,
+            Some((low, high)) => {
+                let low = low.trim();
+                let high = high.trim();
+                let low_ok = low.is_empty()
+                    || match compare_versions(version, low) {
+                        Greater => true,
+                        Equal => inclusive_low,
+                        Less => false,
+                    };
+                let high_ok = high.is_empty()
+                    || match compare_versions(version, high) {
+                        Less => true,
+                        Equal => inclusive_high,
+                        Greater => false,
+                    };
+                low_ok && high_ok
+            }
+        };
+        if ok {
+            return Some(true);
+        }
+    }
+    Some(false)
+}
+
+/// Does `version` satisfy a version requirement written in `loader`'s syntax?
+/// An empty requirement matches everything.
+pub fn version_satisfies(loader: &str, requirement: &str, version: &str) -> Option<bool> {
+    let requirement = requirement.trim();
+    if requirement.is_empty() || requirement == "*" {
+        return Some(true);
+    }
+    match loader {
+        "forge" | "neoforge" => matches_maven_range(requirement, version),
+        _ => matches_semver_predicate(requirement, version),
+    }
+}
+
+/// Whether the content supports Minecraft `version`. `None` when it doesn't say,
+/// or says it in a way OxideMC can't evaluate.
+pub fn supports_game_version(metadata: &ContentMetadata, version: &str) -> Option<bool> {
+    let declared = metadata.game_versions.as_deref()?.trim();
+    if declared.is_empty() || declared.contains("${") {
+        return None;
+    }
+    match metadata.loader.as_str() {
+        "fabric" | "quilt" => matches_semver_predicate(declared, version),
+        "forge" | "neoforge" => matches_maven_range(declared, version),
+        // Plugins declare the oldest API they were built against.
+        _ => Some(compare_versions(version, declared).is_ge()),
+    }
+}
+
+/// Loaders whose content a server platform can run.
+pub fn platform_loaders(platform: &str) -> &'static [&'static str] {
+    match platform {
+        "Fabric" => &["fabric"],
+        "Quilt" => &["quilt", "fabric"],
+        "Forge" => &["forge"],
+        "NeoForge" => &["neoforge"],
+        "Paper" => &["paper", "bukkit"],
+        _ => &[],
+    }
+}
+
+/// Warnings for one piece of content on a server: wrong loader, unsupported game
+/// version, or required dependencies that aren't installed (`installed_ids`).
+pub fn compatibility_warnings(
+    file_name: &str,
+    metadata: &ContentMetadata,
+    platform: &str,
+    version: &str,
+    installed_ids: &[String],
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let label = format!("{} ({})", metadata.name, file_name);
+    if !platform_loaders(platform).contains(&metadata.loader.as_str()) {
+        warnings.push(format!(
+            "{} is a {} {}, which {} servers can't load",
+            label,
+            metadata.loader,
+            if matches!(metadata.loader.as_str(), "bukkit" | "paper") {
+                "plugin"
+            } else {
+                "mod"
+            },
+            platform
+        ));
+    }
+    if supports_game_version(metadata, version) == Some(false) {
+        warnings.push(format!(
+            "{} does not support Minecraft {} (needs {})",
+            label,
+            version,
+            metadata.game_versions.as_deref().unwrap_or_default()
+        ));
+    }
+    for dep in metadata.dependencies.iter().filter(|d| d.required) {
+        if PLATFORM_IDS.contains(&dep.id.as_str())
+            || installed_ids
+                .iter()
+                .any(|id| id.eq_ignore_ascii_case(&dep.id))
+        {
+            continue;
+        }
+        warnings.push(format!(
+            "{} requires {}, which is not installed",
+            label, dep.id
+        ));
+    }
+    warnings
+}
+
+/// Metadata for every jar in `content_dir`, keyed by file name. Unreadable jars are skipped.
+pub fn scan_jar_metadata(content_dir: &PathBuf) -> Vec<(String, ContentMetadata)> {
+    let Ok(entries) = crate::utils::list_entries(content_dir) else {
+        return vec![];
+    };
+    entries
+        .into_iter()
+        .filter(|name| name.ends_with(".jar"))
+        .filter_map(|name| {
+            let metadata = read_jar_metadata(&content_dir.join(&name)).ok()??;
+            Some((name, metadata))
+        })
+        .collect()
+}
+
+/// Compatibility warnings for every mod and plugin installed on a server.
+pub fn server_compatibility_warnings(dir: &Path, platform: &str, version: &str) -> Vec<String> {
+    let installed: Vec<(String, ContentMetadata)> = ["mods", "plugins"]
+        .iter()
+        .flat_map(|sub| scan_jar_metadata(&dir.join(sub)))
+        .collect();
+    let ids: Vec<String> = installed
+        .iter()
+        .flat_map(|(_, m)| std::iter::once(m.id.clone()).chain(m.provides.iter().cloned()))
+        .collect();
+    installed
+        .iter()
+        .flat_map(|(file, m)| compatibility_warnings(file, m, platform, version, &ids))
+        .collect()
+}
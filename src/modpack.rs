@@ -0,0 +1,724 @@
+use crate::config::{read_oxide_config, read_property};
+use crate::download::{download_url, latest_fabric_loader};
+use crate::lockfile::{forget_install, read_lockfile, record_install, FileOrigin};
+use crate::mods::curseforge_file_url;
+use crate::preset::InstallReport;
+use crate::utils::{list_entries, to_hex};
+use ferinth::structures::version::Version;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha512};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, create_dir_all, File};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// `modrinth.index.json` from a `.mrpack` (format version 1).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MrpackIndex {
+    pub format_version: u32,
+    pub game: String,
+    pub version_id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub files: Vec<MrpackFile>,
+    pub dependencies: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MrpackFile {
+    pub path: String,
+    pub hashes: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<MrpackEnv>,
+    pub downloads: Vec<String>,
+    pub file_size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MrpackEnv {
+    pub client: String,
+    pub server: String,
+}
+
+pub fn read_mrpack_index(pack: &PathBuf) -> Result<MrpackIndex, Box<dyn Error>> {
+    let mut archive = ZipArchive::new(File::open(pack)?)?;
+    let entry = archive
+        .by_name("modrinth.index.json")
+        .map_err(|_| "Not a Modrinth modpack: modrinth.index.json is missing")?;
+    let index: MrpackIndex = serde_json::from_reader(entry)?;
+    if index.game != "minecraft" {
+        return Err(format!("Unsupported modpack game '{}'", index.game).into());
+    }
+    Ok(index)
+}
+
+/// Work out the (platform, Minecraft version, loader version) a modpack needs.
+pub fn mrpack_platform(index: &MrpackIndex) -> Result<(String, String, Option<String>), String> {
+    let game_version = index
+        .dependencies
+        .get("minecraft")
+        .ok_or("Modpack does not declare a Minecraft version")?
+        .clone();
+    for (key, name) in [
+        ("fabric-loader", "Fabric"),
+        ("forge", "Forge"),
+        ("neoforge", "NeoForge"),
+    ] {
+        if let Some(loader) = index.dependencies.get(key) {
+            return Ok((name.to_string(), game_version, Some(loader.clone())));
+        }
+    }
+    if index.dependencies.contains_key("quilt-loader") {
+        return Err("Quilt modpacks are not supported yet".to_string());
+    }
+    Ok(("Vanilla".to_string(), game_version, None))
+}
+
+/// Reject absolute paths and `..` so a pack can't write outside the server directory.
+pub fn safe_relative_path(path: &str) -> Option<PathBuf> {
+    let p = PathBuf::from(path);
+    if p.as_os_str().is_empty()
+        || p.components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+    Some(p)
+}
+
+/// Lockfile origin for a download URL. Modrinth CDN URLs carry the project and version IDs.
+pub fn origin_from_url(url: &str) -> FileOrigin {
+    if let Some(rest) = url.strip_prefix("https://cdn.modrinth.com/data/") {
+        let parts: Vec<&str> = rest.split('/').collect();
+        if parts.len() >= 4 && parts[1] == "versions" {
+            return FileOrigin {
+                source: "modrinth".to_string(),
+                project_id: Some(parts[0].to_string()),
+                version_id: Some(parts[2].to_string()),
+                url: Some(url.to_string()),
+                ..Default::default()
+            };
+        }
+    }
+    FileOrigin {
+        source: "url".to_string(),
+        url: Some(url.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Check a file against the sha512 (preferred) or sha1 hash listed for it.
+pub fn verify_hashes(path: &PathBuf, hashes: &HashMap<String, String>) -> Result<(), String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let (algorithm, expected, actual) = if let Some(expected) = hashes.get("sha512") {
+        ("sha512", expected, to_hex(&Sha512::digest(&bytes)))
+    } else if let Some(expected) = hashes.get("sha1") {
+        ("sha1", expected, to_hex(&Sha1::digest(&bytes)))
+    } else {
+        return Err("No sha512 or sha1 hash listed".to_string());
+    };
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(format!("{} mismatch", algorithm))
+    }
+}
+
+/// Copy every file under `prefix` in a zip into `dest`, keeping the relative layout.
+pub fn extract_zip_dir(
+    archive_path: &PathBuf,
+    prefix: &str,
+    dest: &Path,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut archive = ZipArchive::new(File::open(archive_path)?)?;
+    let mut extracted = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        let Ok(rel) = name.strip_prefix(prefix) else {
+            continue;
+        };
+        if rel.as_os_str().is_empty() {
+            continue;
+        }
+        let target = dest.join(rel);
+        if entry.is_dir() {
+            create_dir_all(&target)?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut File::create(&target)?)?;
+        extracted.push(rel.to_string_lossy().replace('\\', "/"));
+    }
+    Ok(extracted)
+}
+
+/// Download a modpack's server-side files into `dir`, verifying hashes, then apply
+/// `overrides/` and `server-overrides/` (in that order, so server overrides win).
+pub async fn apply_mrpack(pack: &PathBuf, dir: &PathBuf) -> Result<InstallReport, Box<dyn Error>> {
+    let index = read_mrpack_index(pack)?;
+    let mut report = InstallReport::default();
+
+    for file in &index.files {
+        if file.env.as_ref().is_some_and(|e| e.server == "unsupported") {
+            report.skipped.push(format!("{} (client only)", file.path));
+            continue;
+        }
+        let Some(rel) = safe_relative_path(&file.path) else {
+            report
+                .failed
+                .push((file.path.clone(), "Unsafe path in modpack".to_string()));
+            continue;
+        };
+        let target = dir.join(&rel);
+        let parent = target.parent().map(PathBuf::from).unwrap_or(dir.clone());
+        let file_name = target
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_string();
+
+        let mut result: Result<String, String> = Err("No download URLs".to_string());
+        for url in &file.downloads {
+            result = match download_url(url, &parent, &file_name).await {
+                Ok(()) => match verify_hashes(&target, &file.hashes) {
+                    Ok(()) => Ok(url.clone()),
+                    Err(e) => {
+                        let _ = fs::remove_file(&target);
+                        Err(e)
+                    }
+                },
+                Err(e) => Err(e.to_string()),
+            };
+            if result.is_ok() {
+                break;
+            }
+        }
+        match result {
+            Ok(url) => {
+                record_install(dir, &parent, &file_name, origin_from_url(&url))?;
+                report.installed.push(file.path.clone());
+            }
+            Err(e) => {
+                forget_install(dir, &parent, &file_name)?;
+                report.failed.push((file.path.clone(), e));
+            }
+        }
+    }
+
+    for prefix in ["overrides/", "server-overrides/"] {
+        for name in extract_zip_dir(pack, prefix, dir)? {
+            report
+                .installed
+                .push(format!("{} ({})", name, prefix.trim_end_matches('/')));
+        }
+    }
+    Ok(report)
+}
+
+/// Content folders whose jars are listed (or bundled) in an exported modpack.
+const EXPORT_CONTENT_DIRS: [&str; 2] = ["mods", "plugins"];
+
+/// Outcome of `export_mrpack`.
+#[derive(Debug, Default)]
+pub struct ExportReport {
+    /// Files listed in the index with a Modrinth download URL.
+    pub linked: Vec<String>,
+    /// Files copied into the pack because no download could be found.
+    pub bundled: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// `dependencies` block of `modrinth.index.json` for a server.
+pub fn mrpack_dependencies(
+    platform: &str,
+    version: &str,
+    loader_version: Option<&str>,
+) -> Result<HashMap<String, String>, String> {
+    let mut deps = HashMap::from([("minecraft".to_string(), version.to_string())]);
+    match platform {
+        "Fabric" => {
+            let loader = loader_version.ok_or("Fabric loader version is unknown")?;
+            deps.insert("fabric-loader".to_string(), loader.to_string());
+        }
+        "Vanilla" | "Paper" => {}
+        other => return Err(format!("Exporting {} servers is not supported", other)),
+    }
+    Ok(deps)
+}
+
+/// Hash every jar in the content folders and turn it into an index entry, using the
+/// lockfile or `known` (Modrinth versions keyed by sha1) for the download URL.
+/// Anything that can't be resolved ends up in `report.bundled`.
+pub fn plan_mrpack_files(
+    dir: &Path,
+    known: &HashMap<String, Version>,
+) -> Result<(Vec<MrpackFile>, ExportReport), Box<dyn Error>> {
+    let lock = read_lockfile(dir)?;
+    let mut files = Vec::new();
+    let mut report = ExportReport::default();
+
+    for content_dir in EXPORT_CONTENT_DIRS {
+        let path = dir.join(content_dir);
+        if !path.is_dir() {
+            continue;
+        }
+        for name in list_entries(&path)? {
+            if !name.ends_with(".jar") {
+                continue;
+            }
+            let rel = format!("{}/{}", content_dir, name);
+            let bytes = fs::read(path.join(&name))?;
+            let sha1 = to_hex(&Sha1::digest(&bytes));
+            let sha512 = to_hex(&Sha512::digest(&bytes));
+
+            let locked_url = lock
+                .find(&rel)
+                .filter(|l| l.sha512 == sha512)
+                .and_then(|l| l.origin.url.clone())
+                .filter(|u| u.starts_with("https://cdn.modrinth.com/"));
+            let known_url = known.get(&sha1).and_then(|v| {
+                v.files
+                    .iter()
+                    .find(|f| f.hashes.sha1 == sha1)
+                    .map(|f| f.url.to_string())
+            });
+            let Some(url) = locked_url.or(known_url) else {
+                report.warnings.push(format!(
+                    "{} is not on Modrinth; bundling it in the pack",
+                    rel
+                ));
+                report.bundled.push(rel);
+                continue;
+            };
+
+            // Plugins only mean something to the server.
+            let env = (content_dir == "plugins").then(|| MrpackEnv {
+                client: "unsupported".to_string(),
+                server: "required".to_string(),
+            });
+            files.push(MrpackFile {
+                path: rel.clone(),
+                hashes: HashMap::from([("sha1".to_string(), sha1), ("sha512".to_string(), sha512)]),
+                env,
+                downloads: vec![url],
+                file_size: bytes.len() as u64,
+            });
+            report.linked.push(rel);
+        }
+    }
+    Ok((files, report))
+}
+
+/// Write a `.mrpack`: the index, and bundled files and `config/` under `overrides/`.
+/// `server.properties` stays out; it holds the RCON password, bind address and seed.
+pub fn write_mrpack(
+    dir: &Path,
+    dest: &PathBuf,
+    index: &MrpackIndex,
+    bundled: &[String],
+) -> Result<(), Box<dyn Error>> {
+    let mut zip = ZipWriter::new(File::create(dest)?);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("modrinth.index.json", options)?;
+    zip.write_all(serde_json::to_string_pretty(index)?.as_bytes())?;
+
+    let mut overrides: Vec<String> = bundled.to_vec();
+    overrides.extend(walk_files(&dir.join("config"), "config")?);
+    for rel in overrides {
+        zip.start_file(format!("overrides/{}", rel), options)?;
+        zip.write_all(&fs::read(dir.join(&rel))?)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Relative paths (prefixed with `rel`) of every file under `path`.
+fn walk_files(path: &PathBuf, rel: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    if !path.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut files = Vec::new();
+    for name in list_entries(path)? {
+        let child = path.join(&name);
+        let child_rel = format!("{}/{}", rel, name);
+        if child.is_dir() {
+            files.extend(walk_files(&child, &child_rel)?);
+        } else {
+            files.push(child_rel);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Build a Modrinth modpack from an existing server. Installed jars are resolved to
+/// Modrinth downloads by hash; anything else is bundled and reported as a warning.
+pub async fn export_mrpack(
+    dir: &Path,
+    platform: &str,
+    version: &str,
+    name: &str,
+    dest: &PathBuf,
+) -> Result<ExportReport, Box<dyn Error>> {
+    let loader_version = match platform {
+        "Fabric" => Some(match read_oxide_config(dir, "fabric_loader") {
+            Ok(v) => v,
+            Err(_) => latest_fabric_loader(version).await?,
+        }),
+        _ => None,
+    };
+    let dependencies = mrpack_dependencies(platform, version, loader_version.as_deref())?;
+
+    // Only ask Modrinth about jars the lockfile can't already vouch for.
+    let (_, offline) = plan_mrpack_files(dir, &HashMap::new())?;
+    let mut known = HashMap::new();
+    let mut lookup_warning = None;
+    if !offline.bundled.is_empty() {
+        let mut hashes = Vec::new();
+        for rel in &offline.bundled {
+            hashes.push(to_hex(&Sha1::digest(fs::read(dir.join(rel))?)));
+        }
+        let client = ferinth::Ferinth::<()>::new("OxideMC", Some(env!("CARGO_PKG_VERSION")), None);
+        match client.version_get_from_multiple_hashes(hashes).await {
+            Ok(found) => known = found,
+            Err(e) => lookup_warning = Some(format!("Could not reach Modrinth: {}", e)),
+        }
+    }
+
+    let (files, mut report) = plan_mrpack_files(dir, &known)?;
+    report.warnings.extend(lookup_warning);
+    if platform == "Paper" {
+        report.warnings.push(
+            "Paper is not a modpack loader; importing this pack creates a Vanilla server"
+                .to_string(),
+        );
+    }
+
+    let index = MrpackIndex {
+        format_version: 1,
+        game: "minecraft".to_string(),
+        version_id: "1.0.0".to_string(),
+        name: name.to_string(),
+        summary: Some(format!(
+            "{} {} server exported by OxideMC",
+            platform, version
+        )),
+        files,
+        dependencies,
+    };
+    write_mrpack(dir, dest, &index, &report.bundled)?;
+    Ok(report)
+}
+
+/// `manifest.json` from a CurseForge modpack zip.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseManifest {
+    pub minecraft: CurseMinecraft,
+    pub manifest_type: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub files: Vec<CurseManifestFile>,
+    #[serde(default = "default_overrides")]
+    pub overrides: String,
+}
+
+fn default_overrides() -> String {
+    "overrides".to_string()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseMinecraft {
+    pub version: String,
+    #[serde(default)]
+    pub mod_loaders: Vec<CurseModLoader>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CurseModLoader {
+    /// e.g. `forge-47.2.0`, `neoforge-21.1.77` or `fabric-0.16.9`.
+    pub id: String,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CurseManifestFile {
+    #[serde(rename = "projectID")]
+    pub project_id: i32,
+    #[serde(rename = "fileID")]
+    pub file_id: i32,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// A CurseForge file whose author disabled third-party downloads.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManualDownload {
+    pub name: String,
+    pub file_name: String,
+    pub url: String,
+}
+
+/// Read `manifest.json`, or `None` for a zip that doesn't have one (e.g. a server pack).
+pub fn read_curseforge_manifest(pack: &PathBuf) -> Result<Option<CurseManifest>, Box<dyn Error>> {
+    let mut archive = ZipArchive::new(File::open(pack)?)?;
+    let Ok(entry) = archive.by_name("manifest.json") else {
+        return Ok(None);
+    };
+    let manifest: CurseManifest = serde_json::from_reader(entry)?;
+    if manifest.manifest_type != "minecraftModpack" {
+        return Err(format!("Unsupported manifest type '{}'", manifest.manifest_type).into());
+    }
+    Ok(Some(manifest))
+}
+
+/// (platform, Minecraft version, loader version) from `minecraft.modLoaders`,
+/// preferring the loader marked primary.
+pub fn curseforge_platform(
+    manifest: &CurseManifest,
+) -> Result<(String, String, Option<String>), String> {
+    let game_version = manifest.minecraft.version.clone();
+    let loaders = &manifest.minecraft.mod_loaders;
+    let Some(loader) = loaders.iter().find(|l| l.primary).or(loaders.first()) else {
+        return Ok(("Vanilla".to_string(), game_version, None));
+    };
+    let (name, version) = loader
+        .id
+        .split_once('-')
+        .ok_or_else(|| format!("Unrecognised mod loader '{}'", loader.id))?;
+    let platform = match name {
+        "forge" => "Forge",
+        "neoforge" => "NeoForge",
+        "fabric" => "Fabric",
+        "quilt" => "Quilt",
+        _ => return Err(format!("Unrecognised mod loader '{}'", loader.id)),
+    };
+    Ok((
+        platform.to_string(),
+        game_version,
+        Some(version.to_string()),
+    ))
+}
+
+/// Server folder (relative to the server directory) for a CurseForge project class, or
+/// why files of that class are skipped.
+pub fn curseforge_class_dir(
+    class_id: Option<i32>,
+    level_name: &str,
+) -> Result<PathBuf, &'static str> {
+    match class_id {
+        // Projects CurseForge didn't return are almost always mods
+        Some(6) | None => Ok(PathBuf::from("mods")),
+        Some(5) => Ok(PathBuf::from("plugins")),
+        Some(6945) => Ok(Path::new(level_name).join("datapacks")),
+        // Resource packs and shader packs do nothing on a server.
+        Some(12) | Some(6552) => Err("client only"),
+        Some(17) => Err("a world; import it from the Worlds menu"),
+        Some(_) => Err("not server content"),
+    }
+}
+
+/// How to install a mod loader OxideMC can't download a server jar for.
+pub fn loader_install_hint(platform: &str, version: &str, loader_version: &str) -> String {
+    match platform {
+        "Forge" => format!(
+            "Install Forge {1} for Minecraft {0} by running https://maven.minecraftforge.net/net/minecraftforge/forge/{0}-{1}/forge-{0}-{1}-installer.jar with --installServer",
+            version, loader_version
+        ),
+        "NeoForge" => format!(
+            "Install NeoForge {1} for Minecraft {0} by running https://maven.neoforged.net/releases/net/neoforged/neoforge/{1}/neoforge-{1}-installer.jar with --installServer",
+            version, loader_version
+        ),
+        _ => format!(
+            "Install the {} {} server for Minecraft {}",
+            platform, loader_version, version
+        ),
+    }
+}
+
+/// Download every required file in a CurseForge modpack into `dir` and copy its overrides.
+/// Files that can only be downloaded from the CurseForge website are returned separately.
+pub async fn apply_curseforge_pack(
+    pack: &PathBuf,
+    manifest: &CurseManifest,
+    dir: &PathBuf,
+    api_key: &str,
+) -> Result<(InstallReport, Vec<ManualDownload>), Box<dyn Error>> {
+    let mut report = InstallReport::default();
+    let mut manual = Vec::new();
+    let client = furse::Furse::new(api_key);
+
+    let level_name = read_property(dir, "server.properties", "level-name")
+        .unwrap_or_else(|_| "world".to_string());
+    let wanted: Vec<&CurseManifestFile> = manifest.files.iter().filter(|f| f.required).collect();
+    for optional in manifest.files.iter().filter(|f| !f.required) {
+        report
+            .skipped
+            .push(format!("project {} (optional)", optional.project_id));
+    }
+
+    if !wanted.is_empty() {
+        let files = client
+            .get_files(wanted.iter().map(|f| f.file_id).collect())
+            .await?;
+        let projects: HashMap<i32, furse::structures::mod_structs::Mod> = client
+            .get_mods(wanted.iter().map(|f| f.project_id).collect())
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect();
+
+        for entry in &wanted {
+            let project = projects.get(&entry.project_id);
+            let label = project
+                .map(|p| p.name.clone())
+                .unwrap_or_else(|| format!("project {}", entry.project_id));
+            let Some(file) = files.iter().flatten().find(|f| f.id == entry.file_id) else {
+                report
+                    .failed
+                    .push((label, "File not found on CurseForge".to_string()));
+                continue;
+            };
+            let subdir = match curseforge_class_dir(project.and_then(|p| p.class_id), &level_name) {
+                Ok(subdir) => subdir,
+                Err(reason) => {
+                    report.skipped.push(format!("{} ({})", label, reason));
+                    continue;
+                }
+            };
+            let Some(url) = &file.download_url else {
+                let website = project
+                    .map(|p| p.links.website_url.to_string())
+                    .unwrap_or_else(|| {
+                        format!("https://www.curseforge.com/projects/{}", entry.project_id)
+                    });
+                manual.push(ManualDownload {
+                    name: label,
+                    file_name: file.file_name.clone(),
+                    url: curseforge_file_url(&website, file.id),
+                });
+                continue;
+            };
+
+            let install_dir = dir.join(subdir);
+            create_dir_all(&install_dir)?;
+            let target = install_dir.join(&file.file_name);
+            let hashes: HashMap<String, String> = file
+                .hashes
+                .iter()
+                .filter(|h| h.algo == furse::structures::file_structs::HashAlgo::Sha1)
+                .map(|h| ("sha1".to_string(), h.value.clone()))
+                .collect();
+            let result = match download_url(url.as_str(), &install_dir, &file.file_name).await {
+                Ok(()) if hashes.is_empty() => Ok(()),
+                Ok(()) => verify_hashes(&target, &hashes).inspect_err(|_| {
+                    let _ = fs::remove_file(&target);
+                }),
+                Err(e) => Err(e.to_string()),
+            };
+            match result {
+                Ok(()) => {
+                    record_install(
+                        dir,
+                        &install_dir,
+                        &file.file_name,
+                        FileOrigin {
+                            source: "curseforge".to_string(),
+                            project_id: Some(entry.project_id.to_string()),
+                            version_id: Some(file.id.to_string()),
+                            version: Some(file.display_name.clone()),
+                            url: Some(url.to_string()),
+                        },
+                    )?;
+                    report.installed.push(file.file_name.clone());
+                }
+                Err(e) => report.failed.push((label, e)),
+            }
+        }
+    }
+
+    let overrides = format!("{}/", manifest.overrides.trim_end_matches('/'));
+    for name in extract_zip_dir(pack, &overrides, dir)? {
+        report.installed.push(format!("{} (overrides)", name));
+    }
+    Ok((report, manual))
+}
+
+/// Folder inside a server pack zip that holds the server, or `""` if it is at the root.
+/// Server packs are often zipped with a single top-level directory.
+pub fn server_pack_root(pack: &PathBuf) -> Result<String, Box<dyn Error>> {
+    let mut archive = ZipArchive::new(File::open(pack)?)?;
+    let mut root: Option<String> = None;
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        let mut components = name.components();
+        let first = components.next();
+        let nested = components.next().is_some();
+        let Some(Component::Normal(first)) = first else {
+            continue;
+        };
+        if !nested && !entry.is_dir() {
+            // A file at the top level means the pack isn't wrapped in a folder.
+            return Ok(String::new());
+        }
+        let first = first.to_string_lossy().to_string();
+        match &root {
+            Some(r) if *r != first => return Ok(String::new()),
+            _ => root = Some(first),
+        }
+    }
+    Ok(root.map(|r| format!("{}/", r)).unwrap_or_default())
+}
+
+/// Loader a ready-made server pack was built for, judged by the files it ships.
+pub fn detect_server_pack_platform(dir: &PathBuf) -> Option<&'static str> {
+    let entries = list_entries(dir).unwrap_or_default();
+    let has = |prefix: &str| entries.iter().any(|e| e.starts_with(prefix));
+    if dir.join("libraries/net/neoforged").exists() || has("neoforge-") {
+        Some("NeoForge")
+    } else if dir.join("libraries/net/minecraftforge").exists() || has("forge-") {
+        Some("Forge")
+    } else if dir.join(".fabric").exists() || has("fabric-server-launch") {
+        Some("Fabric")
+    } else if dir.join("plugins").exists() {
+        Some("Paper")
+    } else {
+        None
+    }
+}
+
+/// Minecraft version from a server pack's `variables.txt` (`MINECRAFT_VERSION=...`), if any.
+pub fn server_pack_game_version(dir: &Path) -> Option<String> {
+    let text = fs::read_to_string(dir.join("variables.txt")).ok()?;
+    text.lines()
+        .filter_map(|l| l.split_once('='))
+        .find(|(k, _)| k.trim() == "MINECRAFT_VERSION")
+        .map(|(_, v)| v.trim().trim_matches('"').to_string())
+        .filter(|v| !v.is_empty())
+}
@@ -1,34 +1,288 @@
 use crate::config::{read_oxide_config, write_oxide_config};
-use crate::download::download_url;
+use crate::download::{download_url, download_url_suggested};
+use crate::lockfile::{forget_install, record_install, rename_install, FileOrigin};
+use crate::metadata::compare_versions;
+use crate::utils::{format_count, list_entries, sha1_file};
 use cliclack::input;
 use ferinth::structures::{
-    project::ProjectType,
+    project::{ProjectSupportRange, ProjectType},
     search::{Facet, SearchHit, Sort},
+    version::{DependencyType, LatestVersionBody, Version},
 };
+use furse::structures::{
+    common_structs::ModLoaderType,
+    file_structs::{File, FileReleaseType},
+    mod_structs::Mod,
+};
+use std::collections::{HashMap, VecDeque};
 use std::env;
 use std::error::Error;
-use std::fs::create_dir_all;
-use std::path::PathBuf;
+use std::fs::{self, create_dir_all};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+/// Where an installed file was found when checking for updates.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UpdateSource {
+    Modrinth,
+    CurseForge,
+}
+
+/// A newer build of an installed mod/plugin, ready to be applied with `apply_update`.
+#[derive(Clone, Debug)]
+pub struct ModUpdate {
+    pub dir: PathBuf,
+    pub file_name: String,
+    pub source: UpdateSource,
+    pub project_id: String,
+    pub version_id: String,
+    pub current_version: String,
+    pub new_version: String,
+    pub new_file_name: String,
+    pub url: String,
+}
+
+/// Modrinth loader name for a content type on a given platform.
+pub fn modrinth_loader(platform: &str, content_type: &str) -> Option<&'static str> {
+    match (content_type, platform) {
+        ("mod", "Fabric") => Some("fabric"),
+        ("mod", "Forge") => Some("forge"),
+        ("plugin", "Paper") => Some("paper"),
+        _ => None,
+    }
+}
+
+/// Search results shown per page in the Modrinth and CurseForge pickers.
+pub const SEARCH_PAGE_SIZE: usize = 10;
+
+/// Which side a project must run on to show up in a Modrinth search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SideFilter {
+    Any,
+    /// Runs on a dedicated server (server side required or optional).
+    Server,
+    /// Only needed on the server; players don't have to install anything.
+    ServerOnly,
+    /// Has to be installed on the server and on every client.
+    ClientAndServer,
+}
+
+/// A Modrinth search: the query text plus filters, sort order and the page to fetch.
+#[derive(Clone, Debug)]
+pub struct ModrinthSearch {
+    pub query: String,
+    pub project_type: ProjectType,
+    pub loader: Option<String>,
+    pub game_version: Option<String>,
+    pub categories: Vec<String>,
+    pub side: SideFilter,
+    pub sort: Sort,
+    pub page: usize,
+}
+
+impl ModrinthSearch {
+    pub fn new(query: &str, project_type: ProjectType) -> Self {
+        ModrinthSearch {
+            query: query.to_string(),
+            project_type,
+            loader: None,
+            game_version: None,
+            categories: vec![],
+            side: SideFilter::Any,
+            sort: Sort::Relevance,
+            page: 0,
+        }
+    }
+}
+
+/// One page of search results.
+#[derive(Clone, Debug)]
+pub struct SearchPage<T> {
+    pub hits: Vec<T>,
+    pub page: usize,
+    pub total_hits: usize,
+}
+
+impl<T> SearchPage<T> {
+    pub fn page_count(&self) -> usize {
+        self.total_hits.div_ceil(SEARCH_PAGE_SIZE).max(1)
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.page + 1 < self.page_count()
+    }
+}
+
+/// Facets for a search. Each inner list is OR-ed, the outer list is AND-ed, so every
+/// selected category has to match.
+pub fn modrinth_facets(search: &ModrinthSearch) -> Vec<Vec<Facet>> {
+    let mut facets: Vec<Vec<Facet>> = vec![vec![Facet::ProjectType(search.project_type)]];
+    if let Some(l) = &search.loader {
+        facets.push(vec![Facet::Categories(l.clone())]);
+    }
+    if let Some(gv) = &search.game_version {
+        facets.push(vec![Facet::Versions(gv.clone())]);
+    }
+    for category in &search.categories {
+        facets.push(vec![Facet::Categories(category.clone())]);
+    }
+    let side = |side: &str, operation: &str| {
+        vec![Facet::Custom {
+            _type: side.to_string(),
+            operation: operation.to_string(),
+            value: "unsupported".to_string(),
+        }]
+    };
+    match search.side {
+        SideFilter::Any => {}
+        SideFilter::Server => facets.push(side("server_side", "!=")),
+        SideFilter::ServerOnly => {
+            facets.push(side("server_side", "!="));
+            facets.push(side("client_side", "="));
+        }
+        SideFilter::ClientAndServer => {
+            facets.push(side("server_side", "!="));
+            facets.push(side("client_side", "!="));
+        }
+    }
+    facets
+}
 
 pub async fn search_modrinth(
-    query: &str,
-    project_type: ProjectType,
-    loader: Option<&str>,
-    game_version: Option<&str>,
-) -> Result<Vec<SearchHit>, Box<dyn Error>> {
+    search: &ModrinthSearch,
+) -> Result<SearchPage<SearchHit>, Box<dyn Error>> {
+    let client = ferinth::Ferinth::<()>::new("OxideMC", Some(env!("CARGO_PKG_VERSION")), None);
+    let response = client
+        .search_paged(
+            &search.query,
+            search.sort,
+            SEARCH_PAGE_SIZE,
+            search.page * SEARCH_PAGE_SIZE,
+            modrinth_facets(search),
+        )
+        .await?;
+    Ok(SearchPage {
+        hits: response.hits,
+        page: search.page,
+        total_hits: response.total_hits,
+    })
+}
+
+/// Category names Modrinth offers for a project type, for the search filter menu.
+pub async fn modrinth_categories(project_type: ProjectType) -> Result<Vec<String>, Box<dyn Error>> {
     let client = ferinth::Ferinth::<()>::new("OxideMC", Some(env!("CARGO_PKG_VERSION")), None);
-    let mut facets: Vec<Vec<Facet>> = vec![vec![Facet::ProjectType(project_type)]];
-    if let Some(l) = loader {
-        facets.push(vec![Facet::Categories(l.to_string())]);
+    let mut names: Vec<String> = client
+        .tag_list_categories()
+        .await?
+        .into_iter()
+        .filter(|c| c.project_type == project_type && c.header != "resolutions")
+        .map(|c| c.name)
+        .collect();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// "1.16.5 – 1.21.4 (23 releases)" for a project's supported game versions. Snapshots are
+/// left out unless the project supports nothing else.
+pub fn game_version_span(versions: &[String]) -> String {
+    let is_release = |v: &&String| v.chars().all(|c| c.is_ascii_digit() || c == '.');
+    let mut releases: Vec<&String> = versions.iter().filter(is_release).collect();
+    if releases.is_empty() {
+        return versions.join(", ");
     }
-    if let Some(gv) = game_version {
-        facets.push(vec![Facet::Versions(gv.to_string())]);
+    releases.sort_by(|a, b| compare_versions(a, b));
+    releases.dedup();
+    match (releases.first(), releases.last()) {
+        (Some(first), Some(last)) if releases.len() > 1 => {
+            format!("{} – {} ({} releases)", first, last, releases.len())
+        }
+        _ => releases[0].to_string(),
     }
-    let response = client.search(query, &Sort::Relevance, facets).await?;
-    Ok(response.hits)
 }
 
+fn side_label(range: &ProjectSupportRange) -> &'static str {
+    match range {
+        ProjectSupportRange::Required => "required",
+        ProjectSupportRange::Optional => "optional",
+        ProjectSupportRange::Unsupported => "unsupported",
+        _ => "unknown",
+    }
+}
+
+/// Multi-line summary of a search hit, shown before it is added to an install.
+pub fn modrinth_details(hit: &SearchHit) -> String {
+    let mut lines = vec![
+        format!("{} by {}", hit.title, hit.author),
+        hit.description.clone(),
+        String::new(),
+        format!(
+            "Downloads: {} · Followers: {}",
+            format_count(hit.downloads),
+            format_count(hit.follows)
+        ),
+        format!("Minecraft: {}", game_version_span(&hit.game_versions)),
+        format!(
+            "Server: {} · Client: {}",
+            side_label(&hit.server_side),
+            side_label(&hit.client_side)
+        ),
+        format!(
+            "License: {}",
+            if hit.license.is_empty() {
+                "unknown"
+            } else {
+                &hit.license
+            }
+        ),
+        format!("Updated: {}", hit.date_modified.format("%Y-%m-%d")),
+    ];
+    if !hit.display_categories.is_empty() {
+        lines.push(format!("Categories: {}", hit.display_categories.join(", ")));
+    }
+    lines.join("\n")
+}
+
+/// Choose the newest version matching `game_version` and `loader`. Unless `strict`, fall back
+/// to the newest version for the loader alone.
+pub fn pick_modrinth_version<'a>(
+    versions: &'a [Version],
+    loader: Option<&str>,
+    game_version: &str,
+    strict: bool,
+) -> Option<&'a Version> {
+    let loader_ok = |v: &Version| loader.is_none_or(|l| v.loaders.iter().any(|vl| vl == l));
+    versions
+        .iter()
+        .find(|v| v.game_versions.iter().any(|gv| gv == game_version) && loader_ok(v))
+        .or_else(|| {
+            if strict {
+                None
+            } else {
+                versions.iter().find(|v| loader_ok(v))
+            }
+        })
+}
+
+/// The build `pinned` names: a version ID, or a version number on a build for `loader` and
+/// `game_version`. Projects reuse one version number across loaders.
+pub fn pick_pinned_modrinth_version<'a>(
+    versions: &'a [Version],
+    pinned: &str,
+    loader: Option<&str>,
+    game_version: &str,
+) -> Option<&'a Version> {
+    versions.iter().find(|v| v.id == pinned).or_else(|| {
+        versions
+            .iter()
+            .find(|v| v.version_number == pinned && modrinth_version_fits(v, loader, game_version))
+    })
+}
+
+/// Install the best matching Modrinth build into `install_dir` and record it in `oxidemc.lock`.
 pub async fn install_modrinth(
+    dir: &PathBuf,
     project_id: &str,
     loader: Option<&str>,
     game_version: &str,
@@ -38,46 +292,438 @@ pub async fn install_modrinth(
     let versions = client.version_list(project_id).await?;
 
     // Try best match: game version + loader, then fall back to loader only
-    let best = versions
-        .iter()
-        .find(|v| {
-            v.game_versions.iter().any(|gv| gv == game_version)
-                && loader.map_or(true, |l| v.loaders.iter().any(|vl| vl == l))
-        })
-        .or_else(|| {
-            versions
-                .iter()
-                .find(|v| loader.map_or(true, |l| v.loaders.iter().any(|vl| vl == l)))
-        })
+    let best = pick_modrinth_version(&versions, loader, game_version, false)
         .ok_or("No compatible version found")?;
 
-    let file = best
+    install_modrinth_version(dir, best, install_dir).await
+}
+
+/// Download a specific Modrinth version's primary file and record it in `oxidemc.lock`.
+pub async fn install_modrinth_version(
+    dir: &PathBuf,
+    version: &Version,
+    install_dir: &PathBuf,
+) -> Result<String, Box<dyn Error>> {
+    let file = version
         .files
         .iter()
         .find(|f| f.primary)
-        .or_else(|| best.files.first())
+        .or_else(|| version.files.first())
         .ok_or("No downloadable file")?;
 
     create_dir_all(install_dir)?;
     download_url(file.url.as_str(), install_dir, &file.filename).await?;
+    record_install(
+        dir,
+        install_dir,
+        &file.filename,
+        FileOrigin {
+            source: "modrinth".to_string(),
+            project_id: Some(version.project_id.clone()),
+            version_id: Some(version.id.clone()),
+            version: Some(version.version_number.clone()),
+            url: Some(file.url.to_string()),
+        },
+    )?;
     Ok(file.filename.clone())
 }
 
+/// One file in an install plan, pulled in directly or as a dependency.
+#[derive(Clone, Debug)]
+pub struct PlannedInstall {
+    pub title: String,
+    pub version: Version,
+    pub required_by: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct InstallPlan {
+    pub installs: Vec<PlannedInstall>,
+    pub warnings: Vec<String>,
+}
+
+/// Map of Modrinth project ID to file name for the jars already in `install_dir`.
+pub async fn installed_modrinth_projects(
+    install_dir: &PathBuf,
+) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let mut by_hash: HashMap<String, String> = HashMap::new();
+    for name in list_entries(install_dir)? {
+        if name.ends_with(".jar") {
+            by_hash.insert(sha1_file(&install_dir.join(&name))?, name);
+        }
+    }
+    if by_hash.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let client = ferinth::Ferinth::<()>::new("OxideMC", Some(env!("CARGO_PKG_VERSION")), None);
+    let versions = client
+        .version_get_from_multiple_hashes(by_hash.keys().cloned().collect())
+        .await?;
+    Ok(versions
+        .into_iter()
+        .filter_map(|(hash, v)| Some((v.project_id, by_hash.get(&hash)?.clone())))
+        .collect())
+}
+
+/// Boxed future returned by `ModrinthApi` methods.
+pub type ModrinthFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, Box<dyn Error>>> + 'a>>;
+
+/// The Modrinth lookups install planning needs, so a plan can be resolved against canned
+/// data as well as the live API.
+pub trait ModrinthApi {
+    /// Every version of a project, newest first.
+    fn project_versions<'a>(&'a self, project_id: &'a str) -> ModrinthFuture<'a, Vec<Version>>;
+    fn version<'a>(&'a self, version_id: &'a str) -> ModrinthFuture<'a, Version>;
+    /// Map of project ID to title.
+    fn project_titles<'a>(
+        &'a self,
+        ids: &'a [&'a str],
+    ) -> ModrinthFuture<'a, HashMap<String, String>>;
+}
+
+impl ModrinthApi for ferinth::Ferinth<()> {
+    fn project_versions<'a>(&'a self, project_id: &'a str) -> ModrinthFuture<'a, Vec<Version>> {
+        Box::pin(async move { Ok(self.version_list(project_id).await?) })
+    }
+
+    fn version<'a>(&'a self, version_id: &'a str) -> ModrinthFuture<'a, Version> {
+        Box::pin(async move { Ok(self.version_get(version_id).await?) })
+    }
+
+    fn project_titles<'a>(
+        &'a self,
+        ids: &'a [&'a str],
+    ) -> ModrinthFuture<'a, HashMap<String, String>> {
+        Box::pin(async move {
+            Ok(self
+                .project_get_multiple(ids)
+                .await?
+                .into_iter()
+                .map(|p| (p.id, p.title))
+                .collect())
+        })
+    }
+}
+
+/// Whether `version` is built for `loader` (when given) and exactly `game_version`.
+fn modrinth_version_fits(version: &Version, loader: Option<&str>, game_version: &str) -> bool {
+    loader.is_none_or(|l| version.loaders.iter().any(|vl| vl == l))
+        && version.game_versions.iter().any(|gv| gv == game_version)
+}
+
+/// Resolve `project_ids` plus their required dependencies (recursively) into a list of
+/// versions to install. Dependencies must match the server's game version and loader;
+/// projects already present in `install_dir` are not installed again.
+pub async fn plan_modrinth_install(
+    install_dir: &PathBuf,
+    project_ids: &[String],
+    loader: Option<&str>,
+    game_version: &str,
+) -> Result<InstallPlan, Box<dyn Error>> {
+    let client = ferinth::Ferinth::<()>::new("OxideMC", Some(env!("CARGO_PKG_VERSION")), None);
+    let installed = installed_modrinth_projects(install_dir).await?;
+    resolve_modrinth_plan(&client, &installed, project_ids, loader, game_version).await
+}
+
+/// `plan_modrinth_install` against `api`, given the installed projects (ID to file name).
+pub async fn resolve_modrinth_plan(
+    api: &dyn ModrinthApi,
+    installed: &HashMap<String, String>,
+    project_ids: &[String],
+    loader: Option<&str>,
+    game_version: &str,
+) -> Result<InstallPlan, Box<dyn Error>> {
+    // (project, pinned version, required by)
+    let mut queue: VecDeque<(String, Option<String>, Option<String>)> = project_ids
+        .iter()
+        .map(|id| (id.clone(), None, None))
+        .collect();
+    let mut planned: Vec<(Version, Option<String>)> = Vec::new();
+    let mut missing: Vec<(String, Option<String>)> = Vec::new();
+    let mut mismatched: Vec<(Version, String)> = Vec::new();
+    let mut incompatible: Vec<(String, String)> = Vec::new();
+
+    while let Some((project_id, pinned, required_by)) = queue.pop_front() {
+        if planned.iter().any(|(v, _)| v.project_id == project_id) {
+            continue;
+        }
+        if required_by.is_some() && installed.contains_key(&project_id) {
+            continue;
+        }
+        let versions = api.project_versions(&project_id).await?;
+        let chosen = match &pinned {
+            Some(id) => versions.iter().find(|v| &v.id == id),
+            None => pick_modrinth_version(&versions, loader, game_version, required_by.is_some()),
+        };
+        let Some(version) = chosen.cloned() else {
+            missing.push((project_id, required_by));
+            continue;
+        };
+        // A dependency pinned to one build still has to run on this server
+        if let (Some(_), Some(parent)) = (&pinned, &required_by) {
+            if !modrinth_version_fits(&version, loader, game_version) {
+                mismatched.push((version, parent.clone()));
+                continue;
+            }
+        }
+
+        for dep in &version.dependencies {
+            let dep_project = match (&dep.project_id, &dep.version_id) {
+                (Some(p), _) => p.clone(),
+                (None, Some(v)) => api.version(v).await?.project_id,
+                (None, None) => continue,
+            };
+            match dep.dependency_type {
+                DependencyType::Required => queue.push_back((
+                    dep_project,
+                    dep.version_id.clone(),
+                    Some(version.project_id.clone()),
+                )),
+                DependencyType::Incompatible
+                    if installed.contains_key(&dep_project)
+                        || planned.iter().any(|(v, _)| v.project_id == dep_project) =>
+                {
+                    incompatible.push((version.project_id.clone(), dep_project));
+                }
+                _ => {}
+            }
+        }
+        planned.push((version, required_by));
+    }
+
+    // Resolve titles for everything we mention
+    let mut ids: Vec<String> = planned.iter().map(|(v, _)| v.project_id.clone()).collect();
+    ids.extend(missing.iter().map(|(p, _)| p.clone()));
+    ids.extend(mismatched.iter().map(|(v, _)| v.project_id.clone()));
+    ids.extend(incompatible.iter().map(|(_, p)| p.clone()));
+    ids.sort();
+    ids.dedup();
+    let id_refs: Vec<&str> = ids.iter().map(|s| s.as_str()).collect();
+    let titles: HashMap<String, String> = if id_refs.is_empty() {
+        HashMap::new()
+    } else {
+        api.project_titles(&id_refs).await?
+    };
+    let title = |id: &str| titles.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+    let mut plan = InstallPlan::default();
+    for (project, required_by) in missing {
+        plan.warnings.push(match required_by {
+            Some(parent) => format!(
+                "No {} build of {} (required by {}) for {}",
+                loader.unwrap_or("compatible"),
+                title(&project),
+                title(&parent),
+                game_version
+            ),
+            None => format!("No compatible version of {} found", title(&project)),
+        });
+    }
+    for (version, parent) in mismatched {
+        plan.warnings.push(format!(
+            "{} requires {} {}, which isn't a {} build for {}",
+            title(&parent),
+            title(&version.project_id),
+            version.version_number,
+            loader.unwrap_or("compatible"),
+            game_version
+        ));
+    }
+    for (project, other) in incompatible {
+        let what = installed
+            .get(&other)
+            .cloned()
+            .unwrap_or_else(|| title(&other));
+        plan.warnings.push(format!(
+            "{} is incompatible with {}",
+            title(&project),
+            what
+        ));
+    }
+    plan.installs = planned
+        .into_iter()
+        .map(|(version, required_by)| PlannedInstall {
+            title: title(&version.project_id),
+            required_by: required_by.map(|p| title(&p)),
+            version,
+        })
+        .collect();
+    Ok(plan)
+}
+
+/// CurseForge's game ID for Minecraft.
+const CURSEFORGE_MINECRAFT: i32 = 432;
+const CURSEFORGE_API: &str = "https://api.curseforge.com/v1";
+
+/// CurseForge class (top-level category) holding a content type.
+pub fn curseforge_class_id(content_type: &str) -> i32 {
+    match content_type {
+        "plugin" => 5,
+        "resourcepack" => 12,
+        "datapack" => 6945,
+        _ => 6,
+    }
+}
+
+/// Content type for a CurseForge class; the inverse of `curseforge_class_id`.
+pub fn curseforge_content_type(class_id: i32) -> &'static str {
+    match class_id {
+        5 => "plugin",
+        12 => "resourcepack",
+        6945 => "datapack",
+        _ => "mod",
+    }
+}
+
+/// CurseForge loader for a content type on a given platform. Plugins, datapacks and
+/// resource packs aren't tagged with a loader.
+pub fn curseforge_loader(platform: &str, content_type: &str) -> Option<ModLoaderType> {
+    match (content_type, platform) {
+        ("mod", "Fabric") => Some(ModLoaderType::Fabric),
+        ("mod", "Forge") => Some(ModLoaderType::Forge),
+        _ => None,
+    }
+}
+
+/// A CurseForge search: keywords plus class, game version, loader and page.
+#[derive(Clone, Debug)]
+pub struct CurseForgeSearch {
+    pub query: String,
+    pub class_id: i32,
+    pub game_version: Option<String>,
+    pub loader: Option<ModLoaderType>,
+    pub page: usize,
+}
+
+/// The `/mods/search` URL for a search against `base`.
+pub fn curseforge_search_url(base: &str, search: &CurseForgeSearch) -> String {
+    let mut url = reqwest::Url::parse(&format!("{}/mods/search", base)).unwrap();
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("gameId", &CURSEFORGE_MINECRAFT.to_string())
+            .append_pair("classId", &search.class_id.to_string())
+            .append_pair("searchFilter", &search.query)
+            .append_pair("index", &(search.page * SEARCH_PAGE_SIZE).to_string())
+            .append_pair("pageSize", &SEARCH_PAGE_SIZE.to_string());
+        if let Some(gv) = &search.game_version {
+            query.append_pair("gameVersion", gv);
+        }
+        if let Some(loader) = search.loader {
+            query.append_pair("modLoaderType", &(loader as u8).to_string());
+        }
+    }
+    url.to_string()
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgePagination {
+    total_count: usize,
+}
+
+#[derive(serde::Deserialize)]
+struct CurseForgeSearchResponse {
+    data: Vec<Mod>,
+    pagination: CurseForgePagination,
+}
+
+pub async fn search_curseforge(
+    search: &CurseForgeSearch,
+    api_key: &str,
+) -> Result<SearchPage<Mod>, Box<dyn Error>> {
+    let response = reqwest::Client::new()
+        .get(curseforge_search_url(CURSEFORGE_API, search))
+        .header("x-api-key", api_key)
+        .send()
+        .await?
+        .error_for_status()?;
+    let body: CurseForgeSearchResponse = serde_json::from_str(&response.text().await?)?;
+    Ok(SearchPage {
+        hits: body.data,
+        page: search.page,
+        // The API refuses to page past 10,000 results
+        total_hits: body.pagination.total_count.min(10_000),
+    })
+}
+
+/// Multi-line summary of a CurseForge project, shown before it is added to an install.
+pub fn curseforge_details(m: &Mod) -> String {
+    let authors: Vec<&str> = m.authors.iter().map(|a| a.name.as_str()).collect();
+    let versions: Vec<String> = m
+        .latest_files_indexes
+        .iter()
+        .map(|f| f.game_version.clone())
+        .collect();
+    let mut lines = vec![
+        format!("{} by {}", m.name, authors.join(", ")),
+        m.summary.clone(),
+        String::new(),
+        format!(
+            "Downloads: {}",
+            format_count(m.download_count.max(0) as usize)
+        ),
+        format!("Minecraft: {}", game_version_span(&versions)),
+        format!("Updated: {}", m.date_modified.format("%Y-%m-%d")),
+    ];
+    if !m.categories.is_empty() {
+        let names: Vec<&str> = m.categories.iter().map(|c| c.name.as_str()).collect();
+        lines.push(format!("Categories: {}", names.join(", ")));
+    }
+    lines.join("\n")
+}
+
+/// Newest file built for `game_version` (and `loader`, when files are tagged with one),
+/// preferring releases over betas and alphas. `None` rather than a build for another
+/// version.
+pub fn pick_curseforge_file<'a>(
+    files: &'a [File],
+    game_version: &str,
+    loader: Option<ModLoaderType>,
+) -> Option<&'a File> {
+    let loader = loader.map(|l| format!("{:?}", l));
+    files
+        .iter()
+        .filter(|f| f.is_available)
+        .filter(|f| f.game_versions.iter().any(|gv| gv == game_version))
+        .filter(|f| curseforge_file_fits_loader(f, loader.as_deref()))
+        .max_by_key(|f| (f.release_type == FileReleaseType::Release, f.file_date))
+}
+
+/// Whether a CurseForge file is built for `loader`. Mod files list their loaders among the
+/// game versions; plugins and packs carry none and fit any loader.
+pub fn curseforge_file_fits_loader(file: &File, loader: Option<&str>) -> bool {
+    const LOADERS: [&str; 4] = ["forge", "neoforge", "fabric", "quilt"];
+    let tagged: Vec<&String> = file
+        .game_versions
+        .iter()
+        .filter(|gv| LOADERS.contains(&gv.to_lowercase().as_str()))
+        .collect();
+    match loader {
+        Some(l) if !tagged.is_empty() => tagged.iter().any(|gv| gv.eq_ignore_ascii_case(l)),
+        _ => true,
+    }
+}
+
+/// Install the newest CurseForge file for `game_version` and record it in `oxidemc.lock`.
 pub async fn install_curseforge(
+    dir: &PathBuf,
     mod_id: i32,
     game_version: &str,
+    loader: Option<ModLoaderType>,
     install_dir: &PathBuf,
     api_key: &str,
 ) -> Result<String, Box<dyn Error>> {
     let client = furse::Furse::new(api_key);
     let files = client.get_mod_files(mod_id).await?;
 
-    // Find latest compatible file (matching game_version first, then any)
-    let file = files
-        .iter()
-        .find(|f| f.game_versions.iter().any(|gv| gv == game_version))
-        .or_else(|| files.first())
-        .ok_or("No files found for this CurseForge project")?;
+    let file = pick_curseforge_file(&files, game_version, loader).ok_or_else(|| {
+        format!(
+            "No file for Minecraft {}{} on this CurseForge project",
+            game_version,
+            loader.map(|l| format!(" ({:?})", l)).unwrap_or_default()
+        )
+    })?;
 
     let file_url = if let Some(url) = &file.download_url {
         url.to_string()
@@ -87,10 +733,209 @@ pub async fn install_curseforge(
 
     create_dir_all(install_dir)?;
     download_url(&file_url, install_dir, &file.file_name).await?;
+    record_install(
+        dir,
+        install_dir,
+        &file.file_name,
+        FileOrigin {
+            source: "curseforge".to_string(),
+            project_id: Some(mod_id.to_string()),
+            version_id: Some(file.id.to_string()),
+            version: Some(file.display_name.clone()),
+            url: Some(file_url.clone()),
+        },
+    )?;
     Ok(file.file_name.clone())
 }
 
-pub fn get_curseforge_key(dir: &PathBuf) -> String {
+/// Check that a file is a readable jar/zip of the right kind for `content_type`
+/// (`mod`, `plugin`, `datapack` or `resourcepack`).
+pub fn validate_content_file(
+    path: &PathBuf,
+    file_name: &str,
+    content_type: &str,
+) -> Result<(), String> {
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    let expected: &[&str] = match content_type {
+        "mod" | "plugin" => &["jar"],
+        _ => &["zip"],
+    };
+    if !expected.contains(&extension.as_str()) {
+        return Err(format!(
+            "{} is not a .{} file",
+            file_name,
+            expected.join(" / .")
+        ));
+    }
+    let file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|_| format!("{} is not a valid {} archive", file_name, extension))?;
+    if matches!(content_type, "datapack" | "resourcepack")
+        && archive.by_name("pack.mcmeta").is_err()
+    {
+        return Err(format!("{} has no pack.mcmeta", file_name));
+    }
+    Ok(())
+}
+
+/// Download a mod/plugin/pack from a direct link. The file name comes from
+/// `Content-Disposition` (or the URL), and the file is validated before it is installed.
+pub async fn install_from_url(
+    dir: &PathBuf,
+    url: &str,
+    install_dir: &PathBuf,
+    content_type: &str,
+) -> Result<String, Box<dyn Error>> {
+    let origin = FileOrigin {
+        source: "url".to_string(),
+        url: Some(url.to_string()),
+        ..Default::default()
+    };
+    install_from_url_as(dir, url, install_dir, content_type, origin).await
+}
+
+/// `install_from_url`, recording `origin` in `oxidemc.lock`.
+pub async fn install_from_url_as(
+    dir: &PathBuf,
+    url: &str,
+    install_dir: &PathBuf,
+    content_type: &str,
+    origin: FileOrigin,
+) -> Result<String, Box<dyn Error>> {
+    let temp_name = ".oxidemc-download.part";
+    let temp = install_dir.join(temp_name);
+    let result = async {
+        let file_name = download_url_suggested(url, install_dir, temp_name).await?;
+        validate_content_file(&temp, &file_name, content_type)?;
+        if install_dir.join(&file_name).exists() {
+            return Err(format!("{} already exists", file_name).into());
+        }
+        fs::rename(&temp, install_dir.join(&file_name))?;
+        record_install(dir, install_dir, &file_name, origin)?;
+        Ok(file_name)
+    }
+    .await;
+    if temp.exists() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Copy a mod/plugin/pack from the local filesystem into the server after validating it.
+pub fn install_from_file(
+    dir: &PathBuf,
+    path: &PathBuf,
+    install_dir: &PathBuf,
+    content_type: &str,
+) -> Result<String, Box<dyn Error>> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Not a file path")?
+        .to_string();
+    validate_content_file(path, &file_name, content_type)?;
+    if install_dir.join(&file_name).exists() {
+        return Err(format!("{} already exists", file_name).into());
+    }
+    create_dir_all(install_dir)?;
+    fs::copy(path, install_dir.join(&file_name))?;
+    let source_path = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+    record_install(
+        dir,
+        install_dir,
+        &file_name,
+        FileOrigin {
+            source: "file".to_string(),
+            url: Some(format!("file://{}", source_path.display())),
+            ..Default::default()
+        },
+    )?;
+    Ok(file_name)
+}
+
+/// Suffix that keeps a mod/plugin on disk without the server loading it.
+pub const DISABLED_SUFFIX: &str = ".disabled";
+
+/// Turn a mod or plugin off by renaming it to `<name>.disabled`. Returns the new name.
+pub fn disable_content(
+    dir: &PathBuf,
+    content_dir: &Path,
+    file_name: &str,
+) -> Result<String, Box<dyn Error>> {
+    if file_name.ends_with(DISABLED_SUFFIX) {
+        return Ok(file_name.to_string());
+    }
+    let disabled = format!("{}{}", file_name, DISABLED_SUFFIX);
+    fs::rename(content_dir.join(file_name), content_dir.join(&disabled))?;
+    rename_install(dir, content_dir, file_name, &disabled)?;
+    Ok(disabled)
+}
+
+/// Turn a disabled mod or plugin back on. Returns the new name.
+pub fn enable_content(
+    dir: &PathBuf,
+    content_dir: &Path,
+    file_name: &str,
+) -> Result<String, Box<dyn Error>> {
+    let Some(enabled) = file_name.strip_suffix(DISABLED_SUFFIX) else {
+        return Ok(file_name.to_string());
+    };
+    if content_dir.join(enabled).exists() {
+        return Err(format!("{} already exists", enabled).into());
+    }
+    fs::rename(content_dir.join(file_name), content_dir.join(enabled))?;
+    rename_install(dir, content_dir, file_name, enabled)?;
+    Ok(enabled.to_string())
+}
+
+/// A jar in a content folder and whether the server will load it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContentState {
+    /// File name without the `.disabled` suffix.
+    pub name: String,
+    pub enabled: bool,
+}
+
+impl ContentState {
+    /// Current file name on disk.
+    pub fn file_name(&self) -> String {
+        if self.enabled {
+            self.name.clone()
+        } else {
+            format!("{}{}", self.name, DISABLED_SUFFIX)
+        }
+    }
+}
+
+/// Every enabled and disabled jar in `content_dir`, sorted by name.
+pub fn list_content(content_dir: &PathBuf) -> Result<Vec<ContentState>, Box<dyn Error>> {
+    let mut states: Vec<ContentState> = list_entries(content_dir)?
+        .into_iter()
+        .filter_map(|f| match f.strip_suffix(DISABLED_SUFFIX) {
+            Some(name) if name.ends_with(".jar") => Some(ContentState {
+                name: name.to_string(),
+                enabled: false,
+            }),
+            None if f.ends_with(".jar") => Some(ContentState {
+                name: f,
+                enabled: true,
+            }),
+            _ => None,
+        })
+        .collect();
+    states.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(states)
+}
+
+/// Page for a specific file on a CurseForge project, for files that must be downloaded by hand.
+pub fn curseforge_file_url(website_url: &str, file_id: i32) -> String {
+    format!("{}/files/{}", website_url.trim_end_matches('/'), file_id)
+}
+
+pub fn get_curseforge_key(dir: &Path) -> String {
     if let Ok(key) = read_oxide_config(dir, "curseforge_api_key") {
         return key;
     }
@@ -103,3 +948,188 @@ pub fn get_curseforge_key(dir: &PathBuf) -> String {
     let _ = write_oxide_config(dir, "curseforge_api_key", &key);
     key
 }
+
+/// Hash every jar in `content_dir` and look for newer builds for `game_version`/`loader`.
+/// Files Modrinth doesn't know are looked up by CurseForge fingerprint when an API key is given.
+pub async fn check_updates(
+    content_dir: &PathBuf,
+    loader: Option<&str>,
+    game_version: &str,
+    curseforge_key: Option<&str>,
+) -> Result<Vec<ModUpdate>, Box<dyn Error>> {
+    let jars: Vec<String> = list_entries(content_dir)?
+        .into_iter()
+        .filter(|f| f.ends_with(".jar"))
+        .collect();
+    if jars.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut hashes: Vec<(String, String)> = Vec::new();
+    for jar in &jars {
+        hashes.push((jar.clone(), sha1_file(&content_dir.join(jar))?));
+    }
+    let hash_list: Vec<String> = hashes.iter().map(|(_, h)| h.clone()).collect();
+
+    let client = ferinth::Ferinth::<()>::new("OxideMC", Some(env!("CARGO_PKG_VERSION")), None);
+    let current = client
+        .version_get_from_multiple_hashes(hash_list.clone())
+        .await?;
+    let latest = client
+        .version_get_latest_from_multiple_hashes(
+            hash_list,
+            LatestVersionBody {
+                loaders: loader.map(|l| vec![l.to_string()]).unwrap_or_default(),
+                game_versions: vec![game_version.to_string()],
+            },
+        )
+        .await?;
+
+    let mut updates = Vec::new();
+    let mut unknown: Vec<String> = Vec::new();
+    for (file_name, hash) in &hashes {
+        let Some(installed) = current.get(hash) else {
+            unknown.push(file_name.clone());
+            continue;
+        };
+        let Some(newest) = latest.get(hash) else {
+            continue;
+        };
+        if newest.id == installed.id {
+            continue;
+        }
+        let Some(file) = newest
+            .files
+            .iter()
+            .find(|f| f.primary)
+            .or_else(|| newest.files.first())
+        else {
+            continue;
+        };
+        updates.push(ModUpdate {
+            dir: content_dir.clone(),
+            file_name: file_name.clone(),
+            source: UpdateSource::Modrinth,
+            project_id: installed.project_id.clone(),
+            version_id: newest.id.clone(),
+            current_version: installed.version_number.clone(),
+            new_version: newest.version_number.clone(),
+            new_file_name: file.filename.clone(),
+            url: file.url.to_string(),
+        });
+    }
+
+    if let (Some(api_key), false) = (curseforge_key, unknown.is_empty()) {
+        updates.extend(
+            check_curseforge_updates(content_dir, &unknown, loader, game_version, api_key).await?,
+        );
+    }
+
+    Ok(updates)
+}
+
+async fn check_curseforge_updates(
+    content_dir: &Path,
+    files: &[String],
+    loader: Option<&str>,
+    game_version: &str,
+    api_key: &str,
+) -> Result<Vec<ModUpdate>, Box<dyn Error>> {
+    let mut fingerprints: Vec<(String, usize)> = Vec::new();
+    for name in files {
+        let bytes = fs::read(content_dir.join(name))?;
+        fingerprints.push((name.clone(), furse::cf_fingerprint(&bytes)));
+    }
+
+    let client = furse::Furse::new(api_key);
+    let matches = client
+        .get_fingerprint_matches(fingerprints.iter().map(|(_, f)| *f).collect())
+        .await?;
+
+    let mut updates = Vec::new();
+    for m in matches.exact_matches {
+        let Some((file_name, _)) = fingerprints
+            .iter()
+            .find(|(_, f)| *f as i64 == m.file.file_fingerprint)
+        else {
+            continue;
+        };
+        let newest = m
+            .latest_files
+            .iter()
+            .filter(|f| f.game_versions.iter().any(|gv| gv == game_version))
+            .filter(|f| curseforge_file_fits_loader(f, loader))
+            .max_by_key(|f| f.file_date);
+        let Some(newest) = newest else { continue };
+        if newest.id == m.file.id || newest.file_date <= m.file.file_date {
+            continue;
+        }
+        let url = match &newest.download_url {
+            Some(url) => url.to_string(),
+            None => client
+                .file_download_url(newest.mod_id, newest.id)
+                .await?
+                .to_string(),
+        };
+        updates.push(ModUpdate {
+            dir: content_dir.to_path_buf(),
+            file_name: file_name.clone(),
+            source: UpdateSource::CurseForge,
+            project_id: m.file.mod_id.to_string(),
+            version_id: newest.id.to_string(),
+            current_version: m.file.display_name.clone(),
+            new_version: newest.display_name.clone(),
+            new_file_name: newest.file_name.clone(),
+            url,
+        });
+    }
+    Ok(updates)
+}
+
+/// Download an update next to the old file and swap it in, so a failed download never
+/// leaves the server without the mod. The lockfile follows the swap.
+pub async fn apply_update(dir: &PathBuf, update: &ModUpdate) -> Result<String, Box<dyn Error>> {
+    let part_name = format!(".{}.part", update.new_file_name);
+    if let Err(e) = download_url(&update.url, &update.dir, &part_name).await {
+        let _ = fs::remove_file(update.dir.join(&part_name));
+        return Err(e);
+    }
+    swap_in_update(
+        &update.dir,
+        &update.file_name,
+        &part_name,
+        &update.new_file_name,
+    )?;
+    forget_install(dir, &update.dir, &update.file_name)?;
+    record_install(
+        dir,
+        &update.dir,
+        &update.new_file_name,
+        FileOrigin {
+            source: match update.source {
+                UpdateSource::Modrinth => "modrinth".to_string(),
+                UpdateSource::CurseForge => "curseforge".to_string(),
+            },
+            project_id: Some(update.project_id.clone()),
+            version_id: Some(update.version_id.clone()),
+            version: Some(update.new_version.clone()),
+            url: Some(update.url.clone()),
+        },
+    )?;
+    Ok(update.new_file_name.clone())
+}
+
+/// Rename the downloaded `part_name` to `new_name` (atomic on the same filesystem) and
+/// remove `old_name` if the new build uses a different filename.
+pub fn swap_in_update(
+    dir: &Path,
+    old_name: &str,
+    part_name: &str,
+    new_name: &str,
+) -> Result<(), Box<dyn Error>> {
+    fs::rename(dir.join(part_name), dir.join(new_name))?;
+    if old_name != new_name {
+        fs::remove_file(dir.join(old_name))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,391 @@
+use crate::utils::write_atomic;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::error::Error;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+/// Nesting limit, matching the game's own reader.
+const MAX_DEPTH: usize = 512;
+
+/// One NBT value. Compounds keep their keys in file order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(Vec<(String, Tag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// Child of a compound by key.
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Tag> {
+        match self {
+            Tag::Compound(entries) => entries.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Set a compound's `key`, keeping its position if it exists. No-op on other tags.
+    pub fn set(&mut self, key: &str, value: Tag) {
+        if let Tag::Compound(entries) = self {
+            match entries.iter_mut().find(|(k, _)| k == key) {
+                Some((_, v)) => *v = value,
+                None => entries.push((key.to_string(), value)),
+            }
+        }
+    }
+
+    /// Descendant by dotted path, e.g. `Data.WorldGenSettings.seed`.
+    pub fn path(&self, path: &str) -> Option<&Tag> {
+        path.split('.').try_fold(self, |tag, key| tag.get(key))
+    }
+
+    /// Any integer tag widened to `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Tag::Byte(v) => Some(v as i64),
+            Tag::Short(v) => Some(v as i64),
+            Tag::Int(v) => Some(v as i64),
+            Tag::Long(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Any numeric tag as `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Tag::Float(v) => Some(v as f64),
+            Tag::Double(v) => Some(v),
+            _ => self.as_i64().map(|v| v as f64),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&[(String, Tag)]> {
+        match self {
+            Tag::Compound(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// The type id written before this tag.
+    pub fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+}
+
+/// Parse an NBT file's bytes, gzip- or zlib-compressed or not, returning the root's name
+/// and compound.
+pub fn read_nbt(bytes: &[u8]) -> Result<(String, Tag), String> {
+    let data = decompress(bytes)?;
+    let mut reader = Reader {
+        data: &data,
+        pos: 0,
+    };
+    let id = reader.u8()?;
+    if id != 10 {
+        return Err(format!(
+            "NBT root must be a compound, found tag type {}",
+            id
+        ));
+    }
+    let name = reader.string()?;
+    let root = reader.payload(id, 0)?;
+    Ok((name, root))
+}
+
+/// `read_nbt` on a file (`level.dat`, `playerdata/<uuid>.dat`).
+pub fn read_nbt_file(path: &PathBuf) -> Result<Tag, Box<dyn Error>> {
+    let bytes = fs::read(path)?;
+    let (_, root) = read_nbt(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(root)
+}
+
+/// Uncompressed NBT for a root compound named `name`.
+pub fn write_nbt(name: &str, root: &Tag) -> Result<Vec<u8>, String> {
+    let mut out = vec![root.id()];
+    write_string(&mut out, name)?;
+    write_payload(&mut out, root)?;
+    Ok(out)
+}
+
+/// Gzip and write an NBT file through a temporary file, keeping the previous version as
+/// `<name>_old` the way the game does for `level.dat`.
+pub fn write_nbt_file(path: &PathBuf, name: &str, root: &Tag) -> Result<(), Box<dyn Error>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&write_nbt(name, root)?)?;
+    let bytes = encoder.finish()?;
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid NBT path")?;
+    if path.exists() {
+        fs::copy(path, path.with_file_name(format!("{}_old", file_name)))?;
+    }
+    write_atomic(path, bytes)
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) -> Result<(), String> {
+    let bytes = encode_modified_utf8(s);
+    let len = u16::try_from(bytes.len()).map_err(|_| "NBT string longer than 65535 bytes")?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(&bytes);
+    Ok(())
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) -> Result<(), String> {
+    let len = i32::try_from(len).map_err(|_| "NBT array too long")?;
+    out.extend_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+fn write_payload(out: &mut Vec<u8>, tag: &Tag) -> Result<(), String> {
+    match tag {
+        Tag::Byte(v) => out.push(*v as u8),
+        Tag::Short(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Int(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Long(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Float(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Double(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::ByteArray(v) => {
+            write_len(out, v.len())?;
+            out.extend(v.iter().map(|b| *b as u8));
+        }
+        Tag::String(s) => write_string(out, s)?,
+        Tag::List(items) => {
+            // Empty lists are written with the end tag as their type, as the game does
+            let item_id = items.first().map_or(0, Tag::id);
+            if items.iter().any(|item| item.id() != item_id) {
+                return Err("NBT list items must all have the same type".to_string());
+            }
+            out.push(item_id);
+            write_len(out, items.len())?;
+            for item in items {
+                write_payload(out, item)?;
+            }
+        }
+        Tag::Compound(entries) => {
+            for (key, value) in entries {
+                out.push(value.id());
+                write_string(out, key)?;
+                write_payload(out, value)?;
+            }
+            out.push(0);
+        }
+        Tag::IntArray(v) => {
+            write_len(out, v.len())?;
+            for i in v {
+                out.extend_from_slice(&i.to_be_bytes());
+            }
+        }
+        Tag::LongArray(v) => {
+            write_len(out, v.len())?;
+            for l in v {
+                out.extend_from_slice(&l.to_be_bytes());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let result = match bytes {
+        [0x1f, 0x8b, ..] => GzDecoder::new(bytes).read_to_end(&mut out),
+        [0x78, ..] => ZlibDecoder::new(bytes).read_to_end(&mut out),
+        _ => return Ok(bytes.to_vec()),
+    };
+    result.map_err(|e| format!("Failed to decompress NBT: {}", e))?;
+    Ok(out)
+}
+
+/// Big-endian cursor over uncompressed NBT.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], String> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or("Unexpected end of NBT data")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().expect("take returns N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        Ok(i16::from_be_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_be_bytes(self.array()?))
+    }
+
+    /// Array or list length; negative lengths count as empty, as in the game.
+    fn len(&mut self) -> Result<usize, String> {
+        let len = self.i32()?.max(0) as usize;
+        // Each element takes at least one byte, so longer lengths are corrupt
+        if len > self.data.len() - self.pos {
+            return Err("NBT length runs past the end of the data".to_string());
+        }
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        Ok(decode_modified_utf8(self.take(len)?))
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Result<Tag, String> {
+        if depth > MAX_DEPTH {
+            return Err("NBT nested too deeply".to_string());
+        }
+        Ok(match id {
+            1 => Tag::Byte(self.u8()? as i8),
+            2 => Tag::Short(self.i16()?),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(self.i64()?),
+            5 => Tag::Float(f32::from_be_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.array()?)),
+            7 => {
+                let len = self.len()?;
+                Tag::ByteArray(self.take(len)?.iter().map(|b| *b as i8).collect())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let item_id = self.u8()?;
+                let len = self.len()?;
+                if item_id == 0 && len > 0 {
+                    return Err("NBT list of end tags".to_string());
+                }
+                let items = (0..len)
+                    .map(|_| self.payload(item_id, depth + 1))
+                    .collect::<Result<_, _>>()?;
+                Tag::List(items)
+            }
+            10 => {
+                let mut entries = Vec::new();
+                loop {
+                    let child = self.u8()?;
+                    if child == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    entries.push((name, self.payload(child, depth + 1)?));
+                }
+                Tag::Compound(entries)
+            }
+            11 => {
+                let len = self.len()?;
+                Tag::IntArray((0..len).map(|_| self.i32()).collect::<Result<_, _>>()?)
+            }
+            12 => {
+                let len = self.len()?;
+                Tag::LongArray((0..len).map(|_| self.i64()).collect::<Result<_, _>>()?)
+            }
+            other => return Err(format!("Unknown NBT tag type {}", other)),
+        })
+    }
+}
+
+/// Java's "modified UTF-8": NUL is written as `C0 80` and characters outside the BMP as
+/// two 3-byte surrogates.
+pub fn decode_modified_utf8(bytes: &[u8]) -> String {
+    let mut units: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i] as u16;
+        let continuation = |n: usize| bytes.get(i + n).map_or(0, |c| (*c & 0x3f) as u16);
+        if b < 0x80 {
+            units.push(b);
+            i += 1;
+        } else if b & 0xe0 == 0xc0 {
+            units.push(((b & 0x1f) << 6) | continuation(1));
+            i += 2;
+        } else {
+            units.push(((b & 0x0f) << 12) | (continuation(1) << 6) | continuation(2));
+            i += 3;
+        }
+    }
+    String::from_utf16_lossy(&units)
+}
+
+/// Inverse of `decode_modified_utf8`.
+pub fn encode_modified_utf8(text: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len());
+    for unit in text.encode_utf16() {
+        match unit {
+            0x01..=0x7f => out.push(unit as u8),
+            0x00 | 0x80..=0x7ff => {
+                out.push(0xc0 | (unit >> 6) as u8);
+                out.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                out.push(0xe0 | (unit >> 12) as u8);
+                out.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                out.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,297 @@
+use crate::level::world_dir;
+use crate::nbt::{read_nbt_file, Tag};
+use crate::players::{dashed_uuid, java_date};
+use crate::utils::format_count;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An entry of `usercache.json`: every player who joined recently, by last-known name.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedUser {
+    pub name: String,
+    pub uuid: String,
+    #[serde(default)]
+    pub expires_on: String,
+}
+
+/// One item stack in a player's inventory or ender chest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemStack {
+    pub slot: i64,
+    pub id: String,
+    pub count: i64,
+}
+
+/// What the server knows about one player, combined from `usercache.json` and their
+/// `playerdata/<uuid>.dat`. Either source may be missing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerInfo {
+    pub uuid: String,
+    pub name: Option<String>,
+    /// Unix time the player data was last saved.
+    pub last_saved: Option<u64>,
+    pub position: Option<[f64; 3]>,
+    pub dimension: Option<String>,
+    pub gamemode: Option<i64>,
+    pub health: Option<f64>,
+    pub food: Option<i64>,
+    pub xp_level: Option<i64>,
+    pub inventory: Vec<ItemStack>,
+    pub ender_items: Vec<ItemStack>,
+}
+
+/// Read `usercache.json`; missing means no one has joined yet.
+pub fn read_usercache(dir: &Path) -> Result<Vec<CachedUser>, Box<dyn Error>> {
+    let path = dir.join("usercache.json");
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(&path)?)?)
+}
+
+/// `<level-name>/playerdata`, where the server saves each player.
+pub fn playerdata_dir(dir: &Path) -> PathBuf {
+    world_dir(dir).join("playerdata")
+}
+
+/// Every player in the usercache or with saved data, most recently saved first.
+pub fn list_players(dir: &Path) -> Result<Vec<PlayerInfo>, Box<dyn Error>> {
+    let mut players: HashMap<String, PlayerInfo> = HashMap::new();
+    let data_dir = playerdata_dir(dir);
+    if data_dir.exists() {
+        for entry in fs::read_dir(&data_dir)?.filter_map(|e| e.ok()) {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some(uuid) = file_name
+                .strip_suffix(".dat")
+                .and_then(|id| dashed_uuid(id).ok())
+            else {
+                continue;
+            };
+            let info = match read_nbt_file(&entry.path()) {
+                Ok(root) => player_info(&uuid, &root),
+                // Keep the player listed even if their file can't be read
+                Err(_) => PlayerInfo {
+                    uuid: uuid.clone(),
+                    ..Default::default()
+                },
+            };
+            let last_saved = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs());
+            players.insert(uuid, PlayerInfo { last_saved, ..info });
+        }
+    }
+    for user in read_usercache(dir)? {
+        let Ok(uuid) = dashed_uuid(&user.uuid) else {
+            continue;
+        };
+        players
+            .entry(uuid.clone())
+            .or_insert_with(|| PlayerInfo {
+                uuid,
+                ..Default::default()
+            })
+            .name = Some(user.name);
+    }
+    let mut players: Vec<PlayerInfo> = players.into_values().collect();
+    players.sort_by(|a, b| {
+        b.last_saved
+            .cmp(&a.last_saved)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(players)
+}
+
+/// Find a player by last-known name (case-insensitive) or UUID.
+pub fn find_player(dir: &Path, name_or_uuid: &str) -> Result<PlayerInfo, Box<dyn Error>> {
+    let uuid = dashed_uuid(name_or_uuid).ok();
+    list_players(dir)?
+        .into_iter()
+        .find(|p| {
+            Some(&p.uuid) == uuid.as_ref()
+                || p.name
+                    .as_deref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(name_or_uuid))
+        })
+        .ok_or_else(|| format!("No saved data for player '{}'", name_or_uuid).into())
+}
+
+/// Pull the displayed fields out of a player data compound.
+pub fn player_info(uuid: &str, root: &Tag) -> PlayerInfo {
+    let position = root.get("Pos").and_then(Tag::as_list).and_then(|pos| {
+        let coords: Vec<f64> = pos.iter().filter_map(Tag::as_f64).collect();
+        coords.try_into().ok()
+    });
+    // 1.16 made the dimension a resource location; older saves use -1/0/1
+    let dimension = root.get("Dimension").and_then(|d| match d {
+        Tag::String(s) => Some(s.clone()),
+        other => other.as_i64().map(|id| {
+            match id {
+                -1 => "minecraft:the_nether",
+                1 => "minecraft:the_end",
+                _ => "minecraft:overworld",
+            }
+            .to_string()
+        }),
+    });
+    let mut inventory = item_stacks(root.get("Inventory"));
+    // 1.21.5 moved armor and the offhand out of the inventory list
+    if let Some(equipment) = root.get("equipment").and_then(Tag::as_compound) {
+        for (slot, item) in equipment {
+            if let Some(mut stack) = item_stack(item) {
+                stack.slot = equipment_slot(slot);
+                inventory.push(stack);
+            }
+        }
+    }
+    PlayerInfo {
+        uuid: uuid.to_string(),
+        // Paper and Spigot remember the name in the player file too
+        name: root
+            .path("bukkit.lastKnownName")
+            .and_then(Tag::as_str)
+            .map(str::to_string),
+        last_saved: None,
+        position,
+        dimension,
+        gamemode: root.get("playerGameType").and_then(Tag::as_i64),
+        health: root.get("Health").and_then(Tag::as_f64),
+        food: root.get("foodLevel").and_then(Tag::as_i64),
+        xp_level: root.get("XpLevel").and_then(Tag::as_i64),
+        inventory,
+        ender_items: item_stacks(root.get("EnderItems")),
+    }
+}
+
+fn item_stacks(list: Option<&Tag>) -> Vec<ItemStack> {
+    list.and_then(Tag::as_list)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(item_stack)
+        .collect()
+}
+
+fn item_stack(item: &Tag) -> Option<ItemStack> {
+    Some(ItemStack {
+        slot: item.get("Slot").and_then(Tag::as_i64).unwrap_or(-1),
+        id: item.get("id")?.as_str()?.to_string(),
+        // `Count` (a byte) became `count` (an int) in 1.20.5
+        count: item
+            .get("count")
+            .or_else(|| item.get("Count"))
+            .and_then(Tag::as_i64)
+            .unwrap_or(1),
+    })
+}
+
+/// The inventory slot numbers older versions use for equipment.
+fn equipment_slot(name: &str) -> i64 {
+    match name {
+        "feet" => 100,
+        "legs" => 101,
+        "chest" => 102,
+        "head" => 103,
+        "offhand" => -106,
+        _ => -1,
+    }
+}
+
+fn gamemode_name(id: i64) -> &'static str {
+    match id {
+        0 => "Survival",
+        1 => "Creative",
+        2 => "Adventure",
+        3 => "Spectator",
+        _ => "Unknown",
+    }
+}
+
+/// "64× cobblestone, 12× torch, …" with stacks of the same item added together, largest
+/// first, `minecraft:` dropped.
+pub fn item_summary(items: &[ItemStack], max: usize) -> String {
+    if items.is_empty() {
+        return "empty".to_string();
+    }
+    let mut totals: Vec<(&str, i64)> = Vec::new();
+    for item in items {
+        let id = item.id.strip_prefix("minecraft:").unwrap_or(&item.id);
+        match totals.iter_mut().find(|(t, _)| *t == id) {
+            Some((_, count)) => *count += item.count,
+            None => totals.push((id, item.count)),
+        }
+    }
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    let mut parts: Vec<String> = totals
+        .iter()
+        .take(max)
+        .map(|(id, count)| format!("{}× {}", format_count(*count as usize), id))
+        .collect();
+    if totals.len() > max {
+        parts.push(format!("and {} more", totals.len() - max));
+    }
+    parts.join(", ")
+}
+
+/// Multi-line description of a player for `players show` and the players menu.
+pub fn player_details(player: &PlayerInfo) -> String {
+    let mut lines = vec![
+        format!(
+            "{} ({})",
+            player.name.as_deref().unwrap_or("Unknown name"),
+            player.uuid
+        ),
+        String::new(),
+    ];
+    if let Some(secs) = player.last_saved {
+        lines.push(format!("Last saved: {} UTC", &java_date(secs)[..16]));
+    }
+    if let Some([x, y, z]) = player.position {
+        let dimension = player.dimension.as_deref().unwrap_or("minecraft:overworld");
+        lines.push(format!(
+            "Position: {:.1}, {:.1}, {:.1} in {}",
+            x, y, z, dimension
+        ));
+    }
+    if let Some(mode) = player.gamemode {
+        lines.push(format!("Gamemode: {}", gamemode_name(mode)));
+    }
+    if let Some(health) = player.health {
+        let food = player
+            .food
+            .map_or(String::new(), |f| format!(", food {}/20", f));
+        lines.push(format!("Health: {:.1}/20{}", health, food));
+    }
+    if let Some(level) = player.xp_level {
+        lines.push(format!("XP level: {}", level));
+    }
+    if player.position.is_none() && player.health.is_none() {
+        lines.push("No saved player data".to_string());
+        return lines.join("\n");
+    }
+    let (armor, carried): (Vec<ItemStack>, Vec<ItemStack>) = player
+        .inventory
+        .iter()
+        .cloned()
+        .partition(|item| (100..=103).contains(&item.slot));
+    lines.push(format!(
+        "Inventory ({} stacks): {}",
+        carried.len(),
+        item_summary(&carried, 8)
+    ));
+    if !armor.is_empty() {
+        lines.push(format!("Armor: {}", item_summary(&armor, 4)));
+    }
+    lines.push(format!(
+        "Ender chest ({} stacks): {}",
+        player.ender_items.len(),
+        item_summary(&player.ender_items, 8)
+    ));
+    lines.join("\n")
+}
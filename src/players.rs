@@ -0,0 +1,522 @@
+use crate::config::{read_oxide_config, read_property};
+use crate::rcon::{run_server_commands, server_running};
+use crate::storage::amz_timestamp;
+use crate::utils::{to_hex, write_atomic};
+use md5::{Digest, Md5};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Mojang's name-to-UUID endpoint; `profile_api` in `oxidemc.json` overrides it.
+pub const DEFAULT_PROFILE_API: &str = "https://api.mojang.com/users/profiles/minecraft";
+
+/// The four player lists a server keeps next to `server.jar`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerList {
+    Ops,
+    Whitelist,
+    Bans,
+    IpBans,
+}
+
+impl PlayerList {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            PlayerList::Ops => "ops.json",
+            PlayerList::Whitelist => "whitelist.json",
+            PlayerList::Bans => "banned-players.json",
+            PlayerList::IpBans => "banned-ips.json",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PlayerList::Ops => "Operators",
+            PlayerList::Whitelist => "Whitelist",
+            PlayerList::Bans => "Banned players",
+            PlayerList::IpBans => "Banned IPs",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpEntry {
+    pub uuid: String,
+    pub name: String,
+    pub level: u8,
+    #[serde(default)]
+    pub bypasses_player_limit: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    pub uuid: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BanEntry {
+    pub uuid: String,
+    pub name: String,
+    pub created: String,
+    pub source: String,
+    pub expires: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IpBanEntry {
+    pub ip: String,
+    pub created: String,
+    pub source: String,
+    pub expires: String,
+    pub reason: String,
+}
+
+/// A player's current name and dashed UUID.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub uuid: String,
+    pub name: String,
+}
+
+/// One change to a player list.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerChange {
+    Op {
+        name: String,
+        level: u8,
+        bypasses_player_limit: bool,
+    },
+    Deop(String),
+    WhitelistAdd(String),
+    WhitelistRemove(String),
+    Ban {
+        name: String,
+        reason: Option<String>,
+    },
+    Pardon(String),
+    BanIp {
+        ip: String,
+        reason: Option<String>,
+    },
+    PardonIp(String),
+}
+
+impl PlayerChange {
+    /// The console command that makes this change on a running server.
+    pub fn command(&self) -> String {
+        let with_reason = |cmd: String, reason: &Option<String>| match reason {
+            Some(reason) => format!("{} {}", cmd, reason),
+            None => cmd,
+        };
+        match self {
+            PlayerChange::Op { name, .. } => format!("op {}", name),
+            PlayerChange::Deop(name) => format!("deop {}", name),
+            PlayerChange::WhitelistAdd(name) => format!("whitelist add {}", name),
+            PlayerChange::WhitelistRemove(name) => format!("whitelist remove {}", name),
+            PlayerChange::Ban { name, reason } => with_reason(format!("ban {}", name), reason),
+            PlayerChange::Pardon(name) => format!("pardon {}", name),
+            PlayerChange::BanIp { ip, reason } => with_reason(format!("ban-ip {}", ip), reason),
+            PlayerChange::PardonIp(ip) => format!("pardon-ip {}", ip),
+        }
+    }
+}
+
+/// A `players` command-line request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerCommand {
+    /// Everyone in the usercache or with saved player data.
+    ListPlayers,
+    ShowPlayer(String),
+    List(PlayerList),
+    Change(PlayerChange),
+}
+
+/// Parse the arguments after `players`, e.g. `list`, `show Steve`, `op Steve --level 2`,
+/// `whitelist add Alex`, `ban Steve --reason griefing` or `list bans`. `--dir` and its value are skipped.
+pub fn parse_player_command(args: &[String]) -> Result<PlayerCommand, String> {
+    let mut words: Vec<&str> = Vec::new();
+    let mut level: Option<u8> = None;
+    let mut reason: Option<String> = None;
+    let mut bypass = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--dir" => {
+                iter.next();
+            }
+            "--level" => {
+                let value = iter.next().ok_or("--level needs a value")?;
+                level = match value.parse::<u8>() {
+                    Ok(l @ 1..=4) => Some(l),
+                    _ => return Err(format!("--level must be 1-4, got '{}'", value)),
+                };
+            }
+            "--reason" => reason = Some(iter.next().ok_or("--reason needs a value")?.clone()),
+            "--bypass-limit" => bypass = true,
+            word => words.push(word),
+        }
+    }
+    let name = |i: usize| -> Result<String, String> {
+        words
+            .get(i)
+            .map(|s| s.to_string())
+            .ok_or_else(|| format!("Missing name after '{}'", words[..i].join(" ")))
+    };
+    let command = match words.as_slice() {
+        [] | ["list"] => PlayerCommand::ListPlayers,
+        ["show", ..] => PlayerCommand::ShowPlayer(name(1)?),
+        ["list", "ops"] => PlayerCommand::List(PlayerList::Ops),
+        ["list", "whitelist"] => PlayerCommand::List(PlayerList::Whitelist),
+        ["list", "bans"] => PlayerCommand::List(PlayerList::Bans),
+        ["list", "ip-bans"] => PlayerCommand::List(PlayerList::IpBans),
+        ["op", ..] => PlayerCommand::Change(PlayerChange::Op {
+            name: name(1)?,
+            level: level.unwrap_or(4),
+            bypasses_player_limit: bypass,
+        }),
+        ["deop", ..] => PlayerCommand::Change(PlayerChange::Deop(name(1)?)),
+        ["whitelist", "add", ..] => PlayerCommand::Change(PlayerChange::WhitelistAdd(name(2)?)),
+        ["whitelist", "remove", ..] => {
+            PlayerCommand::Change(PlayerChange::WhitelistRemove(name(2)?))
+        }
+        ["ban", ..] => PlayerCommand::Change(PlayerChange::Ban {
+            name: name(1)?,
+            reason,
+        }),
+        ["pardon", ..] => PlayerCommand::Change(PlayerChange::Pardon(name(1)?)),
+        ["ban-ip", ..] => PlayerCommand::Change(PlayerChange::BanIp {
+            ip: name(1)?,
+            reason,
+        }),
+        ["pardon-ip", ..] => PlayerCommand::Change(PlayerChange::PardonIp(name(1)?)),
+        other => return Err(format!("Unknown players command '{}'", other.join(" "))),
+    };
+    Ok(command)
+}
+
+/// Profile API base URL from `oxidemc.json`, or Mojang's.
+pub fn profile_api(dir: &Path) -> String {
+    read_oxide_config(dir, "profile_api").unwrap_or_else(|_| DEFAULT_PROFILE_API.to_string())
+}
+
+/// Look a player up by name: `GET {api}/{name}` answering `{"id": ..., "name": ...}`.
+pub async fn lookup_profile(api: &str, name: &str) -> Result<Profile, String> {
+    #[derive(Deserialize)]
+    struct ApiProfile {
+        id: String,
+        name: String,
+    }
+    let url = format!("{}/{}", api.trim_end_matches('/'), name);
+    let response = reqwest::get(&url)
+        .await
+        .map_err(|e| format!("Profile lookup failed: {}", e))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::NO_CONTENT {
+        return Err(format!("No Minecraft account named '{}'", name));
+    }
+    if !status.is_success() {
+        return Err(format!("Profile lookup failed: HTTP {}", status));
+    }
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Profile lookup failed: {}", e))?;
+    let profile: ApiProfile = serde_json::from_str(&text)
+        .map_err(|e| format!("Unexpected profile API response: {}", e))?;
+    Ok(Profile {
+        uuid: dashed_uuid(&profile.id)?,
+        name: profile.name,
+    })
+}
+
+/// Resolve a name to the UUID the server will use: the offline UUID when `online-mode` is
+/// off, the account's UUID from the profile API otherwise.
+pub async fn resolve_profile(dir: &Path, name: &str) -> Result<Profile, String> {
+    if !valid_player_name(name) {
+        return Err(format!("'{}' is not a valid player name", name));
+    }
+    let online = read_property(dir, "server.properties", "online-mode")
+        .map(|v| v != "false")
+        .unwrap_or(true);
+    if online {
+        lookup_profile(&profile_api(dir), name).await
+    } else {
+        Ok(Profile {
+            uuid: offline_uuid(name),
+            name: name.to_string(),
+        })
+    }
+}
+
+/// 3-16 letters, digits or underscores.
+pub fn valid_player_name(name: &str) -> bool {
+    (3..=16).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The UUID an offline-mode server gives `name`: a version 3 UUID of `OfflinePlayer:<name>`.
+pub fn offline_uuid(name: &str) -> String {
+    let mut hash: [u8; 16] = Md5::digest(format!("OfflinePlayer:{}", name)).into();
+    hash[6] = (hash[6] & 0x0f) | 0x30;
+    hash[8] = (hash[8] & 0x3f) | 0x80;
+    dash(&to_hex(&hash))
+}
+
+/// Insert dashes into a 32-digit UUID (profile APIs return them bare).
+pub fn dashed_uuid(id: &str) -> Result<String, String> {
+    let hex: String = id.chars().filter(|c| *c != '-').collect();
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("'{}' is not a UUID", id));
+    }
+    Ok(dash(&hex.to_lowercase()))
+}
+
+/// `8-4-4-4-12` grouping of 32 hex digits.
+fn dash(hex: &str) -> String {
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// Read one of the player list files; a missing file is an empty list.
+pub fn read_player_list<T: DeserializeOwned>(
+    dir: &Path,
+    list: PlayerList,
+) -> Result<Vec<T>, Box<dyn Error>> {
+    let path = dir.join(list.file_name());
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let text = fs::read_to_string(&path)?;
+    if text.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(serde_json::from_str(&text)?)
+}
+
+/// Write a player list through a temporary file, as the server does.
+pub fn write_player_list<T: Serialize>(
+    dir: &Path,
+    list: PlayerList,
+    entries: &[T],
+) -> Result<(), Box<dyn Error>> {
+    write_atomic(
+        &dir.join(list.file_name()),
+        serde_json::to_string_pretty(entries)?,
+    )
+}
+
+/// `(name or ip, details)` for each entry of `list`, for display.
+pub fn player_list_rows(
+    dir: &Path,
+    list: PlayerList,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    Ok(match list {
+        PlayerList::Ops => read_player_list::<OpEntry>(dir, list)?
+            .into_iter()
+            .map(|e| {
+                let bypass = if e.bypasses_player_limit {
+                    ", bypasses player limit"
+                } else {
+                    ""
+                };
+                (e.name, format!("level {}{}", e.level, bypass))
+            })
+            .collect(),
+        PlayerList::Whitelist => read_player_list::<WhitelistEntry>(dir, list)?
+            .into_iter()
+            .map(|e| (e.name, e.uuid))
+            .collect(),
+        PlayerList::Bans => read_player_list::<BanEntry>(dir, list)?
+            .into_iter()
+            .map(|e| (e.name, ban_details(&e.reason, &e.expires)))
+            .collect(),
+        PlayerList::IpBans => read_player_list::<IpBanEntry>(dir, list)?
+            .into_iter()
+            .map(|e| (e.ip, ban_details(&e.reason, &e.expires)))
+            .collect(),
+    })
+}
+
+fn ban_details(reason: &str, expires: &str) -> String {
+    if expires == "forever" {
+        reason.to_string()
+    } else {
+        format!("{} (until {})", reason, expires)
+    }
+}
+
+/// Make `change` while the server is stopped by editing the JSON files. Names are resolved
+/// to UUIDs first for additions; removals match names case-insensitively.
+pub async fn apply_player_change_offline(
+    dir: &Path,
+    change: &PlayerChange,
+) -> Result<String, Box<dyn Error>> {
+    let same_name = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+    match change {
+        PlayerChange::Op {
+            name,
+            level,
+            bypasses_player_limit,
+        } => {
+            let profile = resolve_profile(dir, name).await?;
+            let mut ops: Vec<OpEntry> = read_player_list(dir, PlayerList::Ops)?;
+            ops.retain(|e| e.uuid != profile.uuid && !same_name(&e.name, &profile.name));
+            ops.push(OpEntry {
+                uuid: profile.uuid,
+                name: profile.name.clone(),
+                level: *level,
+                bypasses_player_limit: *bypasses_player_limit,
+            });
+            write_player_list(dir, PlayerList::Ops, &ops)?;
+            Ok(format!(
+                "Made {} a server operator (level {})",
+                profile.name, level
+            ))
+        }
+        PlayerChange::WhitelistAdd(name) => {
+            let profile = resolve_profile(dir, name).await?;
+            let mut list: Vec<WhitelistEntry> = read_player_list(dir, PlayerList::Whitelist)?;
+            list.retain(|e| e.uuid != profile.uuid && !same_name(&e.name, &profile.name));
+            list.push(WhitelistEntry {
+                uuid: profile.uuid,
+                name: profile.name.clone(),
+            });
+            write_player_list(dir, PlayerList::Whitelist, &list)?;
+            Ok(format!("Added {} to the whitelist", profile.name))
+        }
+        PlayerChange::Ban { name, reason } => {
+            let profile = resolve_profile(dir, name).await?;
+            let mut bans: Vec<BanEntry> = read_player_list(dir, PlayerList::Bans)?;
+            bans.retain(|e| e.uuid != profile.uuid && !same_name(&e.name, &profile.name));
+            bans.push(BanEntry {
+                uuid: profile.uuid,
+                name: profile.name.clone(),
+                created: ban_timestamp()?,
+                source: "Server".to_string(),
+                expires: "forever".to_string(),
+                reason: ban_reason(reason),
+            });
+            write_player_list(dir, PlayerList::Bans, &bans)?;
+            Ok(format!("Banned {}", profile.name))
+        }
+        PlayerChange::BanIp { ip, reason } => {
+            if ip.parse::<std::net::IpAddr>().is_err() {
+                return Err(format!("'{}' is not an IP address", ip).into());
+            }
+            let mut bans: Vec<IpBanEntry> = read_player_list(dir, PlayerList::IpBans)?;
+            bans.retain(|e| e.ip != *ip);
+            bans.push(IpBanEntry {
+                ip: ip.clone(),
+                created: ban_timestamp()?,
+                source: "Server".to_string(),
+                expires: "forever".to_string(),
+                reason: ban_reason(reason),
+            });
+            write_player_list(dir, PlayerList::IpBans, &bans)?;
+            Ok(format!("Banned IP {}", ip))
+        }
+        PlayerChange::Deop(name) => {
+            remove_entry::<OpEntry>(dir, PlayerList::Ops, name, |e| &e.name)?;
+            Ok(format!("{} is no longer a server operator", name))
+        }
+        PlayerChange::WhitelistRemove(name) => {
+            remove_entry::<WhitelistEntry>(dir, PlayerList::Whitelist, name, |e| &e.name)?;
+            Ok(format!("Removed {} from the whitelist", name))
+        }
+        PlayerChange::Pardon(name) => {
+            remove_entry::<BanEntry>(dir, PlayerList::Bans, name, |e| &e.name)?;
+            Ok(format!("Unbanned {}", name))
+        }
+        PlayerChange::PardonIp(ip) => {
+            remove_entry::<IpBanEntry>(dir, PlayerList::IpBans, ip, |e| &e.ip)?;
+            Ok(format!("Unbanned IP {}", ip))
+        }
+    }
+}
+
+fn remove_entry<T: Serialize + DeserializeOwned>(
+    dir: &Path,
+    list: PlayerList,
+    name: &str,
+    key: impl Fn(&T) -> &String,
+) -> Result<(), Box<dyn Error>> {
+    let mut entries: Vec<T> = read_player_list(dir, list)?;
+    let before = entries.len();
+    entries.retain(|e| !key(e).eq_ignore_ascii_case(name));
+    if entries.len() == before {
+        return Err(format!("'{}' is not in {}", name, list.file_name()).into());
+    }
+    write_player_list(dir, list, &entries)
+}
+
+fn ban_reason(reason: &Option<String>) -> String {
+    reason
+        .clone()
+        .filter(|r| !r.trim().is_empty())
+        .unwrap_or_else(|| "Banned by an operator.".to_string())
+}
+
+/// Now in the `yyyy-MM-dd HH:mm:ss Z` format the server writes to ban lists.
+fn ban_timestamp() -> Result<String, Box<dyn Error>> {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    Ok(java_date(secs))
+}
+
+/// Format a unix timestamp as `yyyy-MM-dd HH:mm:ss +0000`.
+pub fn java_date(secs: u64) -> String {
+    let (date, stamp) = amz_timestamp(secs);
+    format!(
+        "{}-{}-{} {}:{}:{} +0000",
+        &date[0..4],
+        &date[4..6],
+        &date[6..8],
+        &stamp[9..11],
+        &stamp[11..13],
+        &stamp[13..15]
+    )
+}
+
+/// Make `change` on a running server over RCON, or in the JSON files when it's stopped
+/// (the server rewrites them from memory, so editing them under it would be lost).
+pub async fn apply_player_change(
+    dir: &Path,
+    change: &PlayerChange,
+) -> Result<String, Box<dyn Error>> {
+    if !server_running(dir) {
+        return apply_player_change_offline(dir, change).await;
+    }
+    if let PlayerChange::Op {
+        level,
+        bypasses_player_limit,
+        ..
+    } = change
+    {
+        // `/op` always uses op-permission-level and can't set the player-limit bypass
+        let default_level = read_property(dir, "server.properties", "op-permission-level")
+            .unwrap_or_else(|_| "4".to_string());
+        if level.to_string() != default_level {
+            return Err(format!(
+                "A running server ops players at level {} (op-permission-level); stop it to set level {}",
+                default_level, level
+            )
+            .into());
+        }
+        if *bypasses_player_limit {
+            return Err("Stop the server to let an operator bypass the player limit".into());
+        }
+    }
+    let output = run_server_commands(dir, &[change.command()])?;
+    Ok(output.join("\n"))
+}
@@ -0,0 +1,342 @@
+use crate::level::WorldChange;
+use crate::metadata::scan_jar_metadata;
+use crate::mods::{install_modrinth_version, modrinth_loader, plan_modrinth_install};
+use crate::rcon::{connect_rcon, server_running, Rcon};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, Command};
+
+/// Chunky's Modrinth project.
+pub const CHUNKY_PROJECT: &str = "fALzjamp";
+/// How often the server log is checked for progress.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where Chunky goes on `platform`, and the Modrinth loader to fetch it for.
+pub fn chunky_install_dir(dir: &Path, platform: &str) -> Result<(PathBuf, &'static str), String> {
+    let content_type = if platform == "Paper" { "plugin" } else { "mod" };
+    let loader = modrinth_loader(platform, content_type).ok_or_else(|| {
+        format!(
+            "Chunky needs a Paper, Fabric or Forge server, not {}",
+            platform
+        )
+    })?;
+    Ok((dir.join(format!("{}s", content_type)), loader))
+}
+
+/// Whether a Chunky jar is in `install_dir`.
+pub fn chunky_installed(install_dir: &PathBuf) -> bool {
+    scan_jar_metadata(install_dir)
+        .iter()
+        .any(|(_, metadata)| metadata.id.eq_ignore_ascii_case("chunky"))
+}
+
+/// Install Chunky and what it depends on (Fabric API on Fabric) unless it's there already.
+/// Returns the files added.
+pub async fn install_chunky(
+    dir: &PathBuf,
+    platform: &str,
+    version: &str,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let (install_dir, loader) = chunky_install_dir(dir, platform)?;
+    if chunky_installed(&install_dir) {
+        return Ok(vec![]);
+    }
+    let plan = plan_modrinth_install(
+        &install_dir,
+        &[CHUNKY_PROJECT.to_string()],
+        Some(loader),
+        version,
+    )
+    .await?;
+    if plan.installs.is_empty() {
+        return Err(format!("No Chunky build for {} {}", platform, version).into());
+    }
+    let mut installed = vec![];
+    for planned in &plan.installs {
+        installed.push(install_modrinth_version(dir, &planned.version, &install_dir).await?);
+    }
+    Ok(installed)
+}
+
+/// Console commands that set the world border to the area and have Chunky fill it.
+pub fn pregen_commands(center: (f64, f64), radius: f64) -> Vec<String> {
+    let (x, z) = center;
+    vec![
+        WorldChange::BorderCenter { x, z }.command(),
+        WorldChange::BorderSize(radius * 2.0).command(),
+        format!("chunky center {} {}", x, z),
+        format!("chunky radius {}", radius),
+        "chunky start".to_string(),
+    ]
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChunkyState {
+    Running,
+    Finished,
+    /// Cancelled or paused from the console.
+    Stopped,
+}
+
+/// A Chunky status line from the server log.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkyProgress {
+    pub state: ChunkyState,
+    pub world: String,
+    pub chunks: u64,
+    pub percent: f64,
+    /// "h:mm:ss" as Chunky prints it.
+    pub eta: Option<String>,
+    /// Chunks per second.
+    pub rate: Option<f64>,
+}
+
+/// Parse lines like `[Chunky] Task running for minecraft:overworld. Processed: 1024 chunks
+/// (2.05%), ETA: 0:04:10, Rate: 195.3 cps, Current: 12, -3`. The log prefix varies by
+/// platform and is ignored.
+pub fn parse_chunky_line(line: &str) -> Option<ChunkyProgress> {
+    let (state, rest) = [
+        ("Task running for ", ChunkyState::Running),
+        ("Task finished for ", ChunkyState::Finished),
+        ("Task stopped for ", ChunkyState::Stopped),
+        ("Task cancelled for ", ChunkyState::Stopped),
+        ("Task paused for ", ChunkyState::Stopped),
+    ]
+    .iter()
+    .find_map(|(marker, state)| Some((*state, line.split_once(marker)?.1)))?;
+    // "Processed: N chunks (P%)" is only missing from the stop messages
+    let (world, details) = rest.split_once(". Processed: ").unwrap_or((rest, ""));
+    let field = |name: &str| {
+        details
+            .split(", ")
+            .find_map(|part| part.strip_prefix(name))
+            .map(str::trim)
+    };
+    let (chunks, percent) = details
+        .split_once(" chunks (")
+        .and_then(|(chunks, rest)| {
+            let percent = rest.split_once("%)")?.0;
+            Some((chunks.trim().parse().ok()?, percent.parse().ok()?))
+        })
+        .unwrap_or((0, 0.0));
+    Some(ChunkyProgress {
+        state,
+        world: world.trim_end_matches('.').to_string(),
+        chunks,
+        percent,
+        eta: field("ETA: ").map(str::to_string),
+        rate: field("Rate: ")
+            .and_then(|rate| rate.strip_suffix(" cps"))
+            .and_then(|rate| rate.parse().ok()),
+    })
+}
+
+/// Follows `logs/latest.log` from where it ended when the tail was opened, starting over
+/// when the server rotates the file on startup.
+pub struct LogTail {
+    path: PathBuf,
+    offset: u64,
+    created: Option<SystemTime>,
+    partial: String,
+}
+
+impl LogTail {
+    pub fn new(dir: &Path) -> Self {
+        let path = dir.join("logs").join("latest.log");
+        let metadata = fs::metadata(&path).ok();
+        LogTail {
+            offset: metadata.as_ref().map_or(0, |m| m.len()),
+            created: metadata.and_then(|m| m.created().ok()),
+            path,
+            partial: String::new(),
+        }
+    }
+
+    /// Complete lines written since the last call.
+    pub fn read_lines(&mut self) -> Vec<String> {
+        let Ok(metadata) = fs::metadata(&self.path) else {
+            return vec![];
+        };
+        let created = metadata.created().ok();
+        if metadata.len() < self.offset || created != self.created {
+            self.offset = 0;
+            self.created = created;
+            self.partial.clear();
+        }
+        let mut bytes = vec![];
+        let read = File::open(&self.path).and_then(|mut file| {
+            file.seek(SeekFrom::Start(self.offset))?;
+            file.read_to_end(&mut bytes)
+        });
+        if read.is_err() {
+            return vec![];
+        }
+        self.offset += bytes.len() as u64;
+        self.partial.push_str(&String::from_utf8_lossy(&bytes));
+        let Some(end) = self.partial.rfind('\n') else {
+            return vec![];
+        };
+        let complete: String = self.partial.drain(..=end).collect();
+        complete.lines().map(|l| l.trim_end().to_string()).collect()
+    }
+}
+
+/// How commands reach the server: RCON if it was already up, or the console of the
+/// server started for the run.
+enum Console {
+    Rcon(Rcon),
+    Process(Child),
+}
+
+impl Console {
+    async fn send(&mut self, command: &str) -> Result<(), Box<dyn Error>> {
+        match self {
+            Console::Rcon(rcon) => {
+                rcon.command(command)?;
+            }
+            Console::Process(child) => {
+                let stdin = child.stdin.as_mut().ok_or("The server console is closed")?;
+                stdin.write_all(format!("{}\n", command).as_bytes()).await?;
+                stdin.flush().await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn alive(&mut self, dir: &Path) -> bool {
+        match self {
+            Console::Rcon(_) => server_running(dir),
+            Console::Process(child) => matches!(child.try_wait(), Ok(None)),
+        }
+    }
+
+    /// Stop the server, waiting for it to exit when OxideMC started it.
+    async fn stop(&mut self) -> Result<(), Box<dyn Error>> {
+        // The server may hang up on RCON before it answers `stop`
+        let _ = self.send("stop").await;
+        if let Console::Process(child) = self {
+            child.wait().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Start `server.jar` with a console OxideMC can type into, and wait until it's ready.
+async fn start_server(dir: &PathBuf, tail: &mut LogTail) -> Result<Console, Box<dyn Error>> {
+    let child = Command::new("java")
+        .arg("-jar")
+        .arg("server.jar")
+        .arg("nogui")
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| {
+            format!(
+                "Could not launch Java: {}. Is Java installed and in your PATH?",
+                e
+            )
+        })?;
+    let mut console = Console::Process(child);
+    loop {
+        if !console.alive(dir) {
+            return Err("The server exited while starting; see logs/latest.log".into());
+        }
+        // "Done (12.345s)! For help, type "help""
+        if tail
+            .read_lines()
+            .iter()
+            .any(|line| line.contains("]: Done ("))
+        {
+            return Ok(console);
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Pre-generate the square `radius` blocks around `center` with Chunky: install it if
+/// needed, set the world border to the same square, start the task over RCON (or start
+/// the server when it's down), follow the log and stop the server once Chunky is done.
+/// `on_progress` is called with each status line. Returns the final status.
+pub async fn run_pregen(
+    dir: &PathBuf,
+    platform: &str,
+    version: &str,
+    center: (f64, f64),
+    radius: f64,
+    on_progress: impl FnMut(&ChunkyProgress),
+) -> Result<ChunkyProgress, Box<dyn Error>> {
+    WorldChange::BorderCenter {
+        x: center.0,
+        z: center.1,
+    }
+    .validate()?;
+    WorldChange::BorderSize(radius * 2.0).validate()?;
+
+    let running = server_running(dir);
+    let (install_dir, _) = chunky_install_dir(dir, platform)?;
+    if !chunky_installed(&install_dir) {
+        if running {
+            return Err("Chunky isn't installed; stop the server so it can be added".into());
+        }
+        install_chunky(dir, platform, version).await?;
+    }
+
+    let mut tail = LogTail::new(dir);
+    let mut console = if running {
+        Console::Rcon(connect_rcon(dir).map_err(|e| {
+            format!(
+                "The server is running, so pre-generation must go through RCON: {}. Enable RCON or stop the server first.",
+                e
+            )
+        })?)
+    } else {
+        start_server(dir, &mut tail).await?
+    };
+    let result = follow_pregen(dir, &mut console, &mut tail, center, radius, on_progress).await;
+    // A server started for the run always goes down again; one that was already up only
+    // once the work is done
+    if result.is_ok() || matches!(console, Console::Process(_)) {
+        console.stop().await?;
+    }
+    result
+}
+
+/// Send the pregen commands and follow Chunky's progress in the log until it finishes.
+async fn follow_pregen(
+    dir: &Path,
+    console: &mut Console,
+    tail: &mut LogTail,
+    center: (f64, f64),
+    radius: f64,
+    mut on_progress: impl FnMut(&ChunkyProgress),
+) -> Result<ChunkyProgress, Box<dyn Error>> {
+    for command in pregen_commands(center, radius) {
+        console.send(&command).await?;
+    }
+    loop {
+        let update = tail
+            .read_lines()
+            .iter()
+            .rev()
+            .find_map(|line| parse_chunky_line(line));
+        if let Some(progress) = update {
+            on_progress(&progress);
+            match progress.state {
+                ChunkyState::Running => {}
+                ChunkyState::Finished => return Ok(progress),
+                ChunkyState::Stopped => return Err("Chunky was stopped before it finished".into()),
+            }
+        }
+        if !console.alive(dir) {
+            return Err("The server stopped before pre-generation finished".into());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
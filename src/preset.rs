@@ -1,17 +1,43 @@
-use crate::config::{configure_file, default_server_properties, read_property};
+use crate::config::{default_server_properties, read_property};
+use crate::level::{apply_world_changes, game_rule_changes, read_world_info, world_dir};
+use crate::lockfile::{forget_install, lock_path, read_lockfile, FileOrigin, LockFile};
+use crate::metadata::{read_jar_metadata, ContentMetadata};
+use crate::mods::{
+    install_from_url_as, install_modrinth_version, modrinth_loader, pick_modrinth_version,
+    pick_pinned_modrinth_version,
+};
+use crate::modpack::safe_relative_path;
+use crate::properties::{unescape, Properties};
+use crate::rcon::server_running;
+use crate::schema::{validate_properties, validate_property};
+use crate::utils::write_atomic;
+use crate::yaml::{is_yaml_file, read_yaml_value, yaml_set};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::env;
 use std::error::Error;
 use std::fs::{self, create_dir_all};
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+
+/// Current preset.json layout. Presets without a `format` (saved before it existed) hold
+/// `.properties` values as escaped in the file (`minecraft\:flat`); from 2 on they're
+/// stored as plain values.
+pub const PRESET_FORMAT: u32 = 2;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Preset {
+    /// See `PRESET_FORMAT`; 0 when missing.
+    #[serde(default)]
+    pub format: u32,
     pub info: PresetInfo,
     #[serde(default)]
     pub settings: HashMap<String, HashMap<String, String>>,
     #[serde(default)]
     pub mods: PresetMods,
+    /// Game rules for the world (`keepInventory`, `randomTickSpeed`, ...), written to
+    /// level.dat or sent over RCON.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub gamerules: HashMap<String, String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -46,6 +72,11 @@ pub struct ModEntry {
     pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha512: Option<String>,
+    /// What the jar itself declares (ID, version, dependencies, supported game versions).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ContentMetadata>,
 }
 
 /// Check if a server version matches a pattern like "1.21.*" or an exact version.
@@ -70,7 +101,9 @@ pub fn version_matches(pattern: &str, version: &str) -> bool {
 }
 
 /// Scan installed content directories and build the PresetMods struct.
-pub fn scan_mods(dir: &PathBuf) -> PresetMods {
+/// Files recorded in `oxidemc.lock` carry their source, version and hash.
+pub fn scan_mods(dir: &Path) -> PresetMods {
+    let lock = read_lockfile(dir).unwrap_or_default();
     let scan_dir = |subdir: &str| -> Vec<ModEntry> {
         let path = dir.join(subdir);
         if !path.exists() {
@@ -81,11 +114,19 @@ pub fn scan_mods(dir: &PathBuf) -> PresetMods {
             .filter_map(|e| e.ok())
             .filter_map(|e| {
                 let name = e.file_name().into_string().ok()?;
+                let locked = lock.find(&format!("{}/{}", subdir, name));
                 Some(ModEntry {
+                    modrinth_id: locked
+                        .filter(|l| l.origin.source == "modrinth")
+                        .and_then(|l| l.origin.project_id.clone()),
+                    url: locked.and_then(|l| l.origin.url.clone()),
+                    version: locked.and_then(|l| l.origin.version.clone()),
+                    sha512: locked.map(|l| l.sha512.clone()),
+                    metadata: name
+                        .ends_with(".jar")
+                        .then(|| read_jar_metadata(&path.join(&name)).ok().flatten())
+                        .flatten(),
                     name,
-                    modrinth_id: None,
-                    url: None,
-                    version: None,
                 })
             })
             .collect()
@@ -104,7 +145,7 @@ pub fn scan_mods(dir: &PathBuf) -> PresetMods {
 }
 
 /// Build a Preset from the current server state, only including settings that differ from defaults.
-pub fn build_preset(dir: &PathBuf, platform: &str, version: &str) -> Preset {
+pub fn build_preset(dir: &Path, platform: &str, version: &str) -> Preset {
     let defaults = default_server_properties();
     let mut changed: HashMap<String, String> = HashMap::new();
 
@@ -122,6 +163,7 @@ pub fn build_preset(dir: &PathBuf, platform: &str, version: &str) -> Preset {
     if !changed.is_empty() {
         settings.insert("server.properties".to_string(), changed);
     }
+    settings.extend(tracked_yaml_settings(dir));
 
     let name = dir
         .file_name()
@@ -130,6 +172,7 @@ pub fn build_preset(dir: &PathBuf, platform: &str, version: &str) -> Preset {
         .to_string();
 
     Preset {
+        format: PRESET_FORMAT,
         info: PresetInfo {
             name,
             description: String::new(),
@@ -139,11 +182,88 @@ pub fn build_preset(dir: &PathBuf, platform: &str, version: &str) -> Preset {
         },
         settings,
         mods: scan_mods(dir),
+        gamerules: tracked_gamerules(dir),
+    }
+}
+
+/// YAML settings have no defaults to diff against, so the keys a preset or tuning profile
+/// set are remembered in preset.json and re-read from the files on each save.
+fn tracked_yaml_settings(dir: &Path) -> HashMap<String, HashMap<String, String>> {
+    let Some(previous) = saved_preset(dir) else {
+        return HashMap::new();
+    };
+    let mut tracked = HashMap::new();
+    for (file, keys) in previous.settings {
+        let Some(rel) = safe_relative_path(&file).filter(|_| is_yaml_file(&file)) else {
+            continue;
+        };
+        let current: HashMap<String, String> = keys
+            .into_keys()
+            .filter_map(|key| {
+                let value = read_yaml_value(&dir.join(&rel), &key).ok().flatten()?;
+                Some((key, value))
+            })
+            .collect();
+        if !current.is_empty() {
+            tracked.insert(file, current);
+        }
     }
+    tracked
+}
+
+/// Game rules a loaded preset set, with their current values from level.dat. Until the
+/// world exists they keep the preset's values.
+fn tracked_gamerules(dir: &Path) -> HashMap<String, String> {
+    let Some(previous) = saved_preset(dir) else {
+        return HashMap::new();
+    };
+    let current = read_world_info(dir)
+        .map(|info| info.game_rules)
+        .unwrap_or_default();
+    previous
+        .gamerules
+        .into_iter()
+        .map(|(rule, value)| {
+            let value = current
+                .iter()
+                .find(|(r, _)| *r == rule)
+                .map_or(value, |(_, v)| v.clone());
+            (rule, value)
+        })
+        .collect()
+}
+
+fn saved_preset(dir: &Path) -> Option<Preset> {
+    let text = fs::read_to_string(dir.join("preset.json")).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Edit preset.json in place, if there is one.
+fn update_saved_preset(dir: &Path, edit: impl FnOnce(&mut Preset)) {
+    let Some(mut preset) = saved_preset(dir) else {
+        return;
+    };
+    edit(&mut preset);
+    if let Ok(json) = serde_json::to_string_pretty(&preset) {
+        let _ = write_atomic(&dir.join("preset.json"), json);
+    }
+}
+
+/// Add the YAML keys from applied settings to preset.json so later auto-saves keep them.
+fn track_yaml_settings(dir: &Path, settings: &HashMap<String, HashMap<String, String>>) {
+    update_saved_preset(dir, |preset| {
+        for (file, keys) in settings.iter().filter(|(f, _)| is_yaml_file(f)) {
+            preset
+                .settings
+                .entry(file.clone())
+                .or_default()
+                .extend(keys.clone());
+        }
+    });
 }
 
 /// Auto-save the current server state to preset.json in the server directory.
-pub fn auto_save_preset(dir: &PathBuf, platform: &str, version: &str) {
+pub fn auto_save_preset(dir: &Path, platform: &str, version: &str) {
     let preset = build_preset(dir, platform, version);
     let path = dir.join("preset.json");
     if let Ok(json) = serde_json::to_string_pretty(&preset) {
@@ -152,7 +272,7 @@ pub fn auto_save_preset(dir: &PathBuf, platform: &str, version: &str) {
 }
 
 /// Save (export) the current preset.json to a user-chosen path.
-pub fn save_preset(dir: &PathBuf, dest: &PathBuf) -> Result<PathBuf, Box<dyn Error>> {
+pub fn save_preset(dir: &Path, dest: &PathBuf) -> Result<PathBuf, Box<dyn Error>> {
     let src = dir.join("preset.json");
     if !src.exists() {
         return Err("No preset.json found — configure the server first".into());
@@ -162,16 +282,12 @@ pub fn save_preset(dir: &PathBuf, dest: &PathBuf) -> Result<PathBuf, Box<dyn Err
     Ok(dest.clone())
 }
 
-/// Load a preset file, check version/platform compatibility, and apply settings.
-pub fn load_preset(
-    dir: &PathBuf,
-    preset_path: &PathBuf,
+/// Refuse a preset meant for another platform or Minecraft version.
+fn check_compatibility(
+    preset: &Preset,
     current_platform: &str,
     current_version: &str,
 ) -> Result<(), Box<dyn Error>> {
-    let text = fs::read_to_string(preset_path)?;
-    let preset: Preset = serde_json::from_str(&text)?;
-
     // Check platform compatibility
     if !preset.info.compatible_platforms.is_empty()
         && !preset
@@ -202,16 +318,483 @@ pub fn load_preset(
         .into());
     }
 
-    // Apply settings
-    for (file, props) in &preset.settings {
-        for (key, val) in props {
-            configure_file(dir, file, key, val).unwrap();
+    Ok(())
+}
+
+/// Load a preset file, check version/platform compatibility, and apply settings.
+/// Returns the preset so its content can be installed with `install_preset_mods`.
+pub fn load_preset(
+    dir: &Path,
+    preset_path: &PathBuf,
+    current_platform: &str,
+    current_version: &str,
+) -> Result<Preset, Box<dyn Error>> {
+    let text = fs::read_to_string(preset_path)?;
+    let mut preset: Preset = serde_json::from_str(&text)?;
+    upgrade_preset(&mut preset);
+
+    check_compatibility(&preset, current_platform, current_version)?;
+    // Check every rule and setting before anything is written
+    let rules = game_rule_changes(&preset.gamerules);
+    for rule in &rules {
+        rule.validate()?;
+    }
+    let writes = prepare_settings(dir, &preset.settings, current_version)?;
+    // Then the game rules: level.dat or the server can still refuse them
+    if server_running(dir) || world_dir(dir).join("level.dat").exists() {
+        apply_world_changes(dir, &rules)?;
+    }
+    write_settings(dir, &preset.settings, writes)?;
+    // Remembered in preset.json; rules for a world that doesn't exist yet wait there
+    update_saved_preset(dir, |saved| {
+        saved.gamerules.extend(preset.gamerules.clone())
+    });
+    Ok(preset)
+}
+
+/// Bring a preset read from disk up to `PRESET_FORMAT`.
+pub fn upgrade_preset(preset: &mut Preset) {
+    if preset.format < 2 {
+        for (file, values) in preset.settings.iter_mut() {
+            if is_yaml_file(file) {
+                continue;
+            }
+            for value in values.values_mut() {
+                *value = unescape(value);
+            }
+        }
+    }
+    preset.format = PRESET_FORMAT;
+}
+
+/// Write a preset's settings: `server.properties`-style files by key, YAML files
+/// (`bukkit.yml`, `config/paper-global.yml`, ...) by dotted path. Every file is edited in
+/// memory and checked first, so a bad value leaves the server untouched.
+pub fn apply_settings(
+    dir: &Path,
+    settings: &HashMap<String, HashMap<String, String>>,
+    version: &str,
+) -> Result<(), Box<dyn Error>> {
+    let writes = prepare_settings(dir, settings, version)?;
+    write_settings(dir, settings, writes)
+}
+
+/// Each settings file with the preset's edits applied, checked but not yet written.
+fn prepare_settings(
+    dir: &Path,
+    settings: &HashMap<String, HashMap<String, String>>,
+    version: &str,
+) -> Result<Vec<(PathBuf, String)>, Box<dyn Error>> {
+    let mut files: Vec<&String> = settings.keys().collect();
+    files.sort();
+    let mut writes: Vec<(PathBuf, String)> = Vec::new();
+    for file in files {
+        let rel = safe_relative_path(file)
+            .ok_or_else(|| format!("Preset targets an unsafe path: {}", file))?;
+        let path = dir.join(rel);
+        let mut keys: Vec<&String> = settings[file].keys().collect();
+        keys.sort();
+
+        if is_yaml_file(file) {
+            let mut text = if path.exists() {
+                fs::read_to_string(&path)?
+            } else {
+                String::new()
+            };
+            for key in keys {
+                text = yaml_set(&text, key, &settings[file][key])
+                    .map_err(|e| format!("Preset has an invalid setting in {}: {}", file, e))?;
+            }
+            serde_yaml::from_str::<serde_yaml::Value>(&text)
+                .map_err(|e| format!("Preset produced invalid YAML in {}: {}", file, e))?;
+            writes.push((path, text));
+            continue;
+        }
+
+        let edits: Vec<(&str, &str)> = keys
+            .into_iter()
+            .map(|key| (key.as_str(), settings[file][key].as_str()))
+            .collect();
+        if file.as_str() == "server.properties" {
+            validate_properties(&edits, version)
+                .map_err(|e| format!("Preset has an invalid setting: {}", e))?;
+        }
+        let mut props = Properties::load(&path)?;
+        for (key, value) in &edits {
+            props.set(key, value);
         }
+        writes.push((path, props.to_string()));
     }
+    Ok(writes)
+}
 
+fn write_settings(
+    dir: &Path,
+    settings: &HashMap<String, HashMap<String, String>>,
+    writes: Vec<(PathBuf, String)>,
+) -> Result<(), Box<dyn Error>> {
+    for (path, text) in writes {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        write_atomic(&path, text)?;
+    }
+    track_yaml_settings(dir, settings);
     Ok(())
 }
 
+fn profile(
+    name: &str,
+    description: &str,
+    platforms: &[&str],
+    versions: &[&str],
+    settings: &[(&str, &[(&str, &str)])],
+) -> Preset {
+    Preset {
+        format: PRESET_FORMAT,
+        info: PresetInfo {
+            name: name.to_string(),
+            description: description.to_string(),
+            author: "OxideMC".to_string(),
+            compatible_versions: versions.iter().map(|v| v.to_string()).collect(),
+            compatible_platforms: platforms.iter().map(|p| p.to_string()).collect(),
+        },
+        settings: settings
+            .iter()
+            .map(|(file, values)| {
+                let values = values
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect();
+                (file.to_string(), values)
+            })
+            .collect(),
+        mods: PresetMods::default(),
+        gamerules: HashMap::new(),
+    }
+}
+
+/// Built-in tuning profiles. They are presets without content, so they go through the same
+/// compatibility checks and validation as a loaded preset.
+pub fn tuning_profiles() -> Vec<Preset> {
+    // Paper moved its settings into config/paper-*.yml in 1.19
+    let paper_versions = &["1.19.*", "1.20.*", "1.21.*"];
+    vec![
+        profile(
+            "performance",
+            "Fewer mobs, shorter entity ranges and cheaper redstone for busy servers",
+            &["Paper"],
+            paper_versions,
+            &[
+                (
+                    "server.properties",
+                    &[("simulation-distance", "6"), ("view-distance", "8")],
+                ),
+                (
+                    "bukkit.yml",
+                    &[
+                        ("spawn-limits.monsters", "50"),
+                        ("spawn-limits.animals", "8"),
+                        ("spawn-limits.water-animals", "3"),
+                        ("spawn-limits.water-ambient", "5"),
+                        ("spawn-limits.ambient", "1"),
+                        ("ticks-per.monster-spawns", "4"),
+                        ("chunk-gc.period-in-ticks", "400"),
+                    ],
+                ),
+                (
+                    "spigot.yml",
+                    &[
+                        (
+                            "world-settings.default.entity-activation-range.animals",
+                            "16",
+                        ),
+                        (
+                            "world-settings.default.entity-activation-range.monsters",
+                            "24",
+                        ),
+                        (
+                            "world-settings.default.entity-activation-range.raiders",
+                            "48",
+                        ),
+                        ("world-settings.default.entity-activation-range.misc", "8"),
+                        ("world-settings.default.entity-activation-range.water", "8"),
+                        (
+                            "world-settings.default.entity-activation-range.villagers",
+                            "16",
+                        ),
+                        ("world-settings.default.merge-radius.item", "3.5"),
+                        ("world-settings.default.merge-radius.exp", "4.0"),
+                        ("world-settings.default.mob-spawn-range", "6"),
+                        ("world-settings.default.tick-inactive-villagers", "false"),
+                    ],
+                ),
+                (
+                    "config/paper-world-defaults.yml",
+                    &[
+                        ("chunks.max-auto-save-chunks-per-tick", "8"),
+                        ("chunks.prevent-moving-into-unloaded-chunks", "true"),
+                        ("collisions.max-entity-collisions", "2"),
+                        ("entities.armor-stands.tick", "false"),
+                        ("environment.optimize-explosions", "true"),
+                        ("hopper.disable-move-event", "true"),
+                        ("misc.redstone-implementation", "ALTERNATE_CURRENT"),
+                        ("tick-rates.mob-spawner", "2"),
+                    ],
+                ),
+            ],
+        ),
+        profile(
+            "balanced",
+            "Light optimisations that players won't notice",
+            &["Paper"],
+            paper_versions,
+            &[
+                ("server.properties", &[("simulation-distance", "8")]),
+                ("bukkit.yml", &[("chunk-gc.period-in-ticks", "400")]),
+                (
+                    "spigot.yml",
+                    &[
+                        ("world-settings.default.merge-radius.item", "2.5"),
+                        ("world-settings.default.merge-radius.exp", "3.0"),
+                    ],
+                ),
+                (
+                    "config/paper-world-defaults.yml",
+                    &[
+                        ("chunks.max-auto-save-chunks-per-tick", "12"),
+                        ("environment.optimize-explosions", "true"),
+                    ],
+                ),
+            ],
+        ),
+        profile(
+            "vanilla-parity",
+            "Undo Paper's gameplay changes for technical players (redstone, pistons, TNT)",
+            &["Paper"],
+            paper_versions,
+            &[
+                (
+                    "config/paper-world-defaults.yml",
+                    &[
+                        ("collisions.max-entity-collisions", "8"),
+                        ("environment.optimize-explosions", "false"),
+                        ("misc.redstone-implementation", "VANILLA"),
+                    ],
+                ),
+                (
+                    "config/paper-global.yml",
+                    &[
+                        ("unsupported-settings.allow-headless-pistons", "true"),
+                        (
+                            "unsupported-settings.allow-permanent-block-break-exploits",
+                            "true",
+                        ),
+                        ("unsupported-settings.allow-piston-duplication", "true"),
+                    ],
+                ),
+            ],
+        ),
+        profile(
+            "low-memory",
+            "Smaller view and simulation distances for hosts with little RAM",
+            &[],
+            &[],
+            &[(
+                "server.properties",
+                &[
+                    ("view-distance", "6"),
+                    ("simulation-distance", "4"),
+                    ("entity-broadcast-range-percentage", "75"),
+                ],
+            )],
+        ),
+    ]
+}
+
+/// Apply a tuning profile after the same checks a loaded preset gets.
+pub fn apply_tuning_profile(
+    dir: &Path,
+    profile: &Preset,
+    platform: &str,
+    version: &str,
+) -> Result<(), Box<dyn Error>> {
+    check_compatibility(profile, platform, version)?;
+    apply_settings(dir, &profile.settings, version)
+}
+
+/// Warnings for a preset's `server.properties` settings on a server running `version`:
+/// unknown keys and keys that version doesn't read.
+pub fn preset_property_warnings(preset: &Preset, version: &str) -> Vec<String> {
+    let Some(props) = preset.settings.get("server.properties") else {
+        return vec![];
+    };
+    let mut keys: Vec<&String> = props.keys().collect();
+    keys.sort();
+    keys.into_iter()
+        .filter_map(|k| validate_property(k, &props[k], version).ok())
+        .flatten()
+        .collect()
+}
+
+/// Outcome of installing a batch of content (preset mods, modpacks).
+#[derive(Debug, Default)]
+pub struct InstallReport {
+    pub installed: Vec<String>,
+    pub skipped: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Download the resource packs, datapacks, mods and plugins listed in a preset into the
+/// matching server directories. Pinned `version`s and `sha512`s are honoured.
+pub async fn install_preset_mods(
+    dir: &PathBuf,
+    mods: &PresetMods,
+    platform: &str,
+    version: &str,
+) -> InstallReport {
+    let level_name = read_property(dir, "server.properties", "level-name")
+        .unwrap_or_else(|_| "world".to_string());
+    let supports_mods = matches!(platform, "Fabric" | "Forge");
+    let groups: [(&str, &Vec<ModEntry>, PathBuf, bool); 4] = [
+        (
+            "resourcepack",
+            &mods.resource_packs,
+            dir.join("resourcepacks"),
+            true,
+        ),
+        (
+            "datapack",
+            &mods.datapacks,
+            dir.join(&level_name).join("datapacks"),
+            true,
+        ),
+        ("mod", &mods.mods, dir.join("mods"), supports_mods),
+        (
+            "plugin",
+            &mods.plugins,
+            dir.join("plugins"),
+            platform == "Paper",
+        ),
+    ];
+
+    let lock = read_lockfile(dir).unwrap_or_default();
+    let mut report = InstallReport::default();
+    for (content_type, entries, install_dir, supported) in groups {
+        for entry in entries {
+            if !supported {
+                report.skipped.push(format!(
+                    "{} ({}s are not supported on {})",
+                    entry.name, content_type, platform
+                ));
+                continue;
+            }
+            if !is_plain_file_name(&entry.name) {
+                report
+                    .failed
+                    .push((entry.name.clone(), "not a plain file name".to_string()));
+                continue;
+            }
+            if is_preset_entry_installed(&lock, dir, &install_dir, entry) {
+                report
+                    .skipped
+                    .push(format!("{} (already installed)", entry.name));
+                continue;
+            }
+            let loader = modrinth_loader(platform, content_type);
+            match install_preset_entry(dir, entry, content_type, loader, version, &install_dir)
+                .await
+            {
+                Ok(Some(file)) => report.installed.push(file),
+                Ok(None) => report
+                    .skipped
+                    .push(format!("{} (no modrinth_id or url)", entry.name)),
+                Err(e) => report.failed.push((entry.name.clone(), e.to_string())),
+            }
+        }
+    }
+    report
+}
+
+/// Whether `name` is a single file name, so a shared preset can't reach outside the content
+/// folder.
+fn is_plain_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+fn is_preset_entry_installed(
+    lock: &LockFile,
+    dir: &PathBuf,
+    install_dir: &Path,
+    entry: &ModEntry,
+) -> bool {
+    if install_dir.join(&entry.name).exists() {
+        return true;
+    }
+    let prefix = lock_path(dir, install_dir, "");
+    lock.files.iter().any(|l| {
+        l.path.starts_with(&prefix)
+            && match (&entry.modrinth_id, &entry.url) {
+                (Some(id), _) => {
+                    l.origin.project_id.as_ref() == Some(id)
+                        && entry
+                            .version
+                            .as_ref()
+                            .is_none_or(|v| l.origin.version.as_ref() == Some(v))
+                }
+                (None, Some(url)) => l.origin.url.as_ref() == Some(url),
+                (None, None) => false,
+            }
+    })
+}
+
+/// Install one preset entry. Returns the installed file name, or `None` if it has no source.
+async fn install_preset_entry(
+    dir: &PathBuf,
+    entry: &ModEntry,
+    content_type: &str,
+    loader: Option<&str>,
+    game_version: &str,
+    install_dir: &PathBuf,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let file_name = if let Some(project_id) = &entry.modrinth_id {
+        let client = ferinth::Ferinth::<()>::new("OxideMC", Some(env!("CARGO_PKG_VERSION")), None);
+        let versions = client.version_list(project_id).await?;
+        let chosen = match &entry.version {
+            Some(pinned) => pick_pinned_modrinth_version(&versions, pinned, loader, game_version)
+                .ok_or(format!("No build of version {} for this server", pinned))?,
+            None => pick_modrinth_version(&versions, loader, game_version, false)
+                .ok_or("No compatible version found")?,
+        };
+        install_modrinth_version(dir, chosen, install_dir).await?
+    } else if let Some(url) = &entry.url {
+        let origin = FileOrigin {
+            source: "url".to_string(),
+            version: entry.version.clone(),
+            url: Some(url.clone()),
+            ..Default::default()
+        };
+        install_from_url_as(dir, url, install_dir, content_type, origin).await?
+    } else {
+        return Ok(None);
+    };
+
+    if let Some(expected) = &entry.sha512 {
+        let lock = read_lockfile(dir)?;
+        let recorded = lock.find(&lock_path(dir, install_dir, &file_name));
+        if recorded.map(|l| &l.sha512) != Some(expected) {
+            let _ = fs::remove_file(install_dir.join(&file_name));
+            forget_install(dir, install_dir, &file_name)?;
+            return Err(format!("{} does not match the pinned sha512", file_name).into());
+        }
+    }
+    Ok(Some(file_name))
+}
+
 /// List saved preset files from a directory.
 pub fn list_presets(presets_dir: &PathBuf) -> Result<Vec<String>, Box<dyn Error>> {
     if !presets_dir.exists() {
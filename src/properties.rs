@@ -0,0 +1,252 @@
+use crate::utils::write_atomic;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One logical line of a `.properties` file.
+#[derive(Clone, Debug, PartialEq)]
+enum Line {
+    /// Comment or blank line, kept verbatim.
+    Other(String),
+    /// `raw` holds the original text (continuations included) until the entry is edited, so
+    /// untouched entries are written back exactly as they were.
+    Entry {
+        key: String,
+        value: String,
+        raw: Option<String>,
+    },
+}
+
+/// A Java `.properties` file that keeps comments, blank lines and ordering, and reads and
+/// writes escapes the way `java.util.Properties` does.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Properties {
+    lines: Vec<Line>,
+}
+
+impl Properties {
+    pub fn parse(text: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut physical = text.lines();
+        while let Some(first) = physical.next() {
+            let trimmed = first.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+                lines.push(Line::Other(first.to_string()));
+                continue;
+            }
+            // A line ending in an odd number of backslashes continues on the next one
+            let mut raw = first.to_string();
+            let mut logical = trimmed.to_string();
+            while ends_with_continuation(&logical) {
+                logical.pop();
+                match physical.next() {
+                    Some(next) => {
+                        raw.push('\n');
+                        raw.push_str(next);
+                        logical.push_str(next.trim_start());
+                    }
+                    None => break,
+                }
+            }
+            let (key, value) = split_entry(&logical);
+            lines.push(Line::Entry {
+                key: unescape(key),
+                value: unescape(value),
+                raw: Some(raw),
+            });
+        }
+        Properties { lines }
+    }
+
+    /// Read a file; a missing file is an empty set of properties.
+    pub fn load(path: &PathBuf) -> Result<Self, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(Properties::default());
+        }
+        Ok(Properties::parse(&fs::read_to_string(path)?))
+    }
+
+    /// Write through a temporary file and rename it over `path`, so readers never see a
+    /// half-written file.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        write_atomic(path, self.to_string())
+    }
+
+    /// Value for `key`. As in Java, the last occurrence wins.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().rev().find_map(|line| match line {
+            Line::Entry { key: k, value, .. } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Set `key`, keeping its position. Duplicate occurrences are dropped; new keys are
+    /// appended at the end.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let mut found = false;
+        self.lines.retain_mut(|line| match line {
+            Line::Entry {
+                key: k,
+                value: v,
+                raw,
+            } if k == key => {
+                if found {
+                    return false;
+                }
+                found = true;
+                if v != value {
+                    *v = value.to_string();
+                    *raw = None;
+                }
+                true
+            }
+            _ => true,
+        });
+        if !found {
+            self.lines.push(Line::Entry {
+                key: key.to_string(),
+                value: value.to_string(),
+                raw: None,
+            });
+        }
+    }
+
+    /// Remove every occurrence of `key`, returning the value that was in effect.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let previous = self.get(key).map(str::to_string);
+        self.lines
+            .retain(|line| !matches!(line, Line::Entry { key: k, .. } if k == key));
+        previous
+    }
+
+    /// Keys and values in file order, each key once at its effective value.
+    pub fn entries(&self) -> Vec<(&str, &str)> {
+        let mut seen: Vec<&str> = Vec::new();
+        for line in &self.lines {
+            if let Line::Entry { key, .. } = line {
+                if !seen.contains(&key.as_str()) {
+                    seen.push(key);
+                }
+            }
+        }
+        seen.into_iter()
+            .map(|k| (k, self.get(k).unwrap_or_default()))
+            .collect()
+    }
+}
+
+impl fmt::Display for Properties {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            match line {
+                Line::Other(text) => writeln!(f, "{}", text)?,
+                Line::Entry { raw: Some(raw), .. } => writeln!(f, "{}", raw)?,
+                Line::Entry { key, value, .. } => {
+                    writeln!(f, "{}={}", escape(key, true), escape(value, false))?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Apply several edits to a properties file in one atomic write.
+pub fn update_properties(path: &PathBuf, edits: &[(&str, &str)]) -> Result<(), Box<dyn Error>> {
+    let mut props = Properties::load(path)?;
+    for (key, value) in edits {
+        props.set(key, value);
+    }
+    props.save(path)
+}
+
+fn ends_with_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/// Split a logical line at the first unescaped `=`, `:` or whitespace.
+fn split_entry(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    let mut key_end = line.len();
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '=' || c == ':' || c.is_whitespace() {
+            key_end = i;
+            break;
+        }
+    }
+    let rest = line[key_end..].trim_start_matches([' ', '\t', '\x0c']);
+    let rest = rest
+        .strip_prefix(['=', ':'])
+        .unwrap_or(rest)
+        .trim_start_matches([' ', '\t', '\x0c']);
+    (&line[..key_end], rest)
+}
+
+/// Resolve Java escapes: `\t`, `\n`, `\r`, `\f`, `\uXXXX` (surrogate pairs included) and
+/// `\x` for any other `x`.
+pub fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    // `\uXXXX` escapes are UTF-16 code units; collect runs of them before decoding
+    let mut units: Vec<u16> = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek() == Some(&'u') {
+            let hex: String = chars.clone().skip(1).take(4).collect();
+            if let (4, Ok(unit)) = (hex.len(), u16::from_str_radix(&hex, 16)) {
+                units.push(unit);
+                chars.nth(4);
+                continue;
+            }
+        }
+        if !units.is_empty() {
+            out.push_str(&String::from_utf16_lossy(&units));
+            units.clear();
+        }
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('f') => out.push('\x0c'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out.push_str(&String::from_utf16_lossy(&units));
+    out
+}
+
+/// Escape text the way `Properties.store` does. Non-ASCII becomes `\uXXXX` so the file
+/// reads the same whether the server loads it as Latin-1 or UTF-8.
+pub fn escape(text: &str, is_key: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\x0c' => out.push_str("\\f"),
+            '=' | ':' | '#' | '!' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' ' if is_key || i == 0 => out.push_str("\\ "),
+            c if (c as u32) < 0x20 || (c as u32) > 0x7e => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    out.push_str(&format!("\\u{:04X}", unit));
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
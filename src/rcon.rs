@@ -0,0 +1,142 @@
+use crate::config::read_property;
+use std::error::Error;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::time::Duration;
+
+const PACKET_AUTH: i32 = 3;
+const PACKET_COMMAND: i32 = 2;
+const PACKET_RESPONSE: i32 = 0;
+/// Largest packet the server sends; longer command output is split across several.
+const MAX_PACKET: usize = 4110;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A logged-in connection to a server's remote console (Source RCON protocol).
+pub struct Rcon {
+    stream: TcpStream,
+    next_id: i32,
+}
+
+impl Rcon {
+    pub fn connect(addr: &str, password: &str) -> Result<Self, Box<dyn Error>> {
+        let addr: SocketAddr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| format!("Could not resolve {}", addr))?;
+        let stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut rcon = Rcon { stream, next_id: 1 };
+        let id = rcon.send(PACKET_AUTH, password)?;
+        // The server answers a login with id -1 when the password is wrong
+        let (reply_id, _, _) = rcon.receive()?;
+        if reply_id == -1 || reply_id != id {
+            return Err("RCON login failed: wrong rcon.password".into());
+        }
+        Ok(rcon)
+    }
+
+    /// Run a console command and return its output.
+    pub fn command(&mut self, command: &str) -> Result<String, Box<dyn Error>> {
+        let id = self.send(PACKET_COMMAND, command)?;
+        loop {
+            let (reply_id, kind, body) = self.receive()?;
+            if reply_id == id && kind == PACKET_RESPONSE {
+                return Ok(body);
+            }
+        }
+    }
+
+    fn send(&mut self, kind: i32, body: &str) -> Result<i32, Box<dyn Error>> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.stream.write_all(&encode_packet(id, kind, body))?;
+        Ok(id)
+    }
+
+    fn receive(&mut self) -> Result<(i32, i32, String), Box<dyn Error>> {
+        let mut len = [0u8; 4];
+        self.stream.read_exact(&mut len)?;
+        let len = i32::from_le_bytes(len);
+        if !(10..=MAX_PACKET as i32).contains(&len) {
+            return Err(format!("Bad RCON packet length {}", len).into());
+        }
+        let mut packet = vec![0u8; len as usize];
+        self.stream.read_exact(&mut packet)?;
+        decode_packet(&packet).ok_or_else(|| "Malformed RCON packet".into())
+    }
+}
+
+/// Length-prefixed packet: id, type, body, and two NUL terminators.
+pub fn encode_packet(id: i32, kind: i32, body: &str) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(body.len() + 14);
+    packet.extend_from_slice(&(body.len() as i32 + 10).to_le_bytes());
+    packet.extend_from_slice(&id.to_le_bytes());
+    packet.extend_from_slice(&kind.to_le_bytes());
+    packet.extend_from_slice(body.as_bytes());
+    packet.extend_from_slice(&[0, 0]);
+    packet
+}
+
+/// Id, type and body of a packet with its length prefix already stripped.
+pub fn decode_packet(packet: &[u8]) -> Option<(i32, i32, String)> {
+    if packet.len() < 10 {
+        return None;
+    }
+    let id = i32::from_le_bytes(packet[0..4].try_into().ok()?);
+    let kind = i32::from_le_bytes(packet[4..8].try_into().ok()?);
+    let body = &packet[8..packet.len() - 2];
+    Some((id, kind, String::from_utf8_lossy(body).to_string()))
+}
+
+/// Address the server listens on for `port_key`, defaulting to localhost.
+fn local_addr(dir: &Path, port_key: &str, default_port: &str) -> String {
+    let host = read_property(dir, "server.properties", "server-ip")
+        .ok()
+        .filter(|ip| !ip.is_empty() && ip != "0.0.0.0")
+        .unwrap_or_else(|| "127.0.0.1".to_string());
+    let port = read_property(dir, "server.properties", port_key)
+        .ok()
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| default_port.to_string());
+    format!("{}:{}", host, port)
+}
+
+/// Whether something is accepting connections on the server's game port, which is the
+/// case while the server is up.
+pub fn server_running(dir: &Path) -> bool {
+    let addr = local_addr(dir, "server-port", "25565");
+    addr.to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .is_some_and(|addr| TcpStream::connect_timeout(&addr, Duration::from_millis(300)).is_ok())
+}
+
+/// Log in to the server's RCON using the port and password from `server.properties`.
+pub fn connect_rcon(dir: &Path) -> Result<Rcon, Box<dyn Error>> {
+    let enabled = read_property(dir, "server.properties", "enable-rcon").unwrap_or_default();
+    if enabled != "true" {
+        return Err("RCON is disabled (enable-rcon=false)".into());
+    }
+    let password = read_property(dir, "server.properties", "rcon.password").unwrap_or_default();
+    if password.is_empty() {
+        return Err("RCON has no password set (rcon.password)".into());
+    }
+    Rcon::connect(&local_addr(dir, "rcon.port", "25575"), &password)
+}
+
+/// Run console commands on the running server over RCON, returning each one's output.
+/// The error explains how to proceed when RCON isn't set up.
+pub fn run_server_commands(dir: &Path, commands: &[String]) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut rcon = connect_rcon(dir).map_err(|e| {
+        format!(
+            "The server is running, so changes must go through RCON: {}. Enable RCON or stop the server first.",
+            e
+        )
+    })?;
+    commands
+        .iter()
+        .map(|command| Ok(rcon.command(command)?.trim().to_string()))
+        .collect()
+}
@@ -0,0 +1,381 @@
+use crate::nbt::{read_nbt, Tag};
+use crate::utils::{format_size, write_atomic};
+use crate::worlds::{current_world, refuse_while_running};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Region files are laid out in 4 KiB sectors; the first two hold the header.
+const SECTOR: usize = 4096;
+const HEADER: usize = 2 * SECTOR;
+/// Set on the compression byte when the chunk lives in a `c.<x>.<z>.mcc` file.
+const EXTERNAL_FLAG: u8 = 128;
+
+/// One stored chunk of an Anvil region file (`r.<x>.<z>.mca`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegionChunk {
+    /// Chunk coordinates.
+    pub x: i32,
+    pub z: i32,
+    /// Unix time of the last save.
+    pub timestamp: u32,
+    /// The chunk as stored: big-endian length, compression byte, compressed NBT.
+    pub data: Vec<u8>,
+}
+
+impl RegionChunk {
+    /// Slot in the region header.
+    pub fn index(&self) -> usize {
+        (self.x.rem_euclid(32) + self.z.rem_euclid(32) * 32) as usize
+    }
+
+    fn compression(&self) -> u8 {
+        self.data.get(4).copied().unwrap_or(0)
+    }
+
+    /// Path of the oversized-chunk file next to the region, if the chunk uses one.
+    pub fn external_file(&self, region_dir: &Path) -> Option<PathBuf> {
+        (self.compression() & EXTERNAL_FLAG != 0)
+            .then(|| region_dir.join(format!("c.{}.{}.mcc", self.x, self.z)))
+    }
+
+    /// The chunk's NBT. LZ4 and custom compression aren't supported.
+    pub fn nbt(&self, region_dir: &Path) -> Result<Tag, Box<dyn Error>> {
+        let bytes = match self.external_file(region_dir) {
+            Some(path) => fs::read(path)?,
+            None => self.data[5..].to_vec(),
+        };
+        match self.compression() & !EXTERNAL_FLAG {
+            1..=3 => Ok(read_nbt(&bytes)?.1),
+            other => Err(format!("Unsupported chunk compression {}", other).into()),
+        }
+    }
+}
+
+/// Region coordinates from a file name like `r.-1.2.mca`.
+pub fn region_coords(path: &Path) -> Option<(i32, i32)> {
+    let name = path.file_name()?.to_str()?;
+    let mut parts = name.strip_prefix("r.")?.strip_suffix(".mca")?.split('.');
+    let coords = (parts.next()?.parse().ok()?, parts.next()?.parse().ok()?);
+    parts.next().is_none().then_some(coords)
+}
+
+/// Every chunk stored in a region file, in header order.
+pub fn read_region(path: &PathBuf) -> Result<Vec<RegionChunk>, Box<dyn Error>> {
+    let (rx, rz) =
+        region_coords(path).ok_or_else(|| format!("{} is not a region file", path.display()))?;
+    let bytes = fs::read(path)?;
+    // The game sometimes leaves empty region files behind
+    if bytes.is_empty() {
+        return Ok(vec![]);
+    }
+    if bytes.len() < HEADER {
+        return Err(format!("{} is truncated", path.display()).into());
+    }
+    let mut chunks = vec![];
+    for index in 0..1024 {
+        let entry = u32::from_be_bytes(bytes[index * 4..index * 4 + 4].try_into()?);
+        if entry == 0 {
+            continue;
+        }
+        let start = (entry >> 8) as usize * SECTOR;
+        let damaged = || format!("{} has a damaged chunk at slot {}", path.display(), index);
+        if start < HEADER || start + 5 > bytes.len() {
+            return Err(damaged().into());
+        }
+        let length = u32::from_be_bytes(bytes[start..start + 4].try_into()?) as usize;
+        if length == 0 {
+            continue;
+        }
+        let data = bytes.get(start..start + 4 + length).ok_or_else(damaged)?;
+        let stamp = SECTOR + index * 4;
+        chunks.push(RegionChunk {
+            x: rx * 32 + (index % 32) as i32,
+            z: rz * 32 + (index / 32) as i32,
+            timestamp: u32::from_be_bytes(bytes[stamp..stamp + 4].try_into()?),
+            data: data.to_vec(),
+        });
+    }
+    Ok(chunks)
+}
+
+/// Bytes a region file holding `chunks` takes once written compactly.
+pub fn region_size(chunks: &[RegionChunk]) -> u64 {
+    if chunks.is_empty() {
+        return 0;
+    }
+    let sectors: usize = chunks.iter().map(|c| c.data.len().div_ceil(SECTOR)).sum();
+    (HEADER + sectors * SECTOR) as u64
+}
+
+/// Write `chunks` as a region file, packed with no free sectors. With no chunks left the
+/// file is deleted, as the game does not need it.
+pub fn write_region(path: &PathBuf, chunks: &[RegionChunk]) -> Result<(), Box<dyn Error>> {
+    if chunks.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    let mut bytes = vec![0u8; HEADER];
+    for chunk in chunks {
+        let sector = bytes.len() / SECTOR;
+        let count = chunk.data.len().div_ceil(SECTOR).min(255);
+        let index = chunk.index();
+        let entry = ((sector as u32) << 8) | count as u32;
+        bytes[index * 4..index * 4 + 4].copy_from_slice(&entry.to_be_bytes());
+        let stamp = SECTOR + index * 4;
+        bytes[stamp..stamp + 4].copy_from_slice(&chunk.timestamp.to_be_bytes());
+        bytes.extend_from_slice(&chunk.data);
+        bytes.resize(bytes.len().div_ceil(SECTOR) * SECTOR, 0);
+    }
+    write_atomic(path, &bytes)
+}
+
+/// Ticks players have spent in a chunk, from its 1.18+ or older `Level` layout.
+pub fn inhabited_time(chunk: &Tag) -> Option<i64> {
+    chunk
+        .get("InhabitedTime")
+        .or_else(|| chunk.path("Level.InhabitedTime"))
+        .and_then(Tag::as_i64)
+}
+
+/// Which chunks to drop. A chunk goes if either test says so.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PruneRule {
+    /// Drop chunks players spent fewer ticks than this in (20 ticks per second).
+    pub min_inhabited_ticks: Option<i64>,
+    /// Drop chunks whose center is more than this many blocks from `center`, measured in
+    /// overworld blocks (scaled down by 8 in the Nether, like the world border).
+    pub radius: Option<f64>,
+    /// Block x and z the radius is measured from.
+    pub center: (f64, f64),
+}
+
+impl PruneRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_inhabited_ticks.is_none() && self.radius.is_none() {
+            return Err("Choose a minimum inhabited time, a radius, or both".to_string());
+        }
+        if self.min_inhabited_ticks.is_some_and(|t| t <= 0) {
+            return Err("The minimum inhabited time must be above 0".to_string());
+        }
+        if self.radius.is_some_and(|r| r.is_nan() || r <= 0.0) {
+            return Err("The radius must be above 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether a chunk of a dimension with `scale` goes. Chunks whose inhabited time
+    /// can't be read are kept.
+    pub fn drops(&self, x: i32, z: i32, inhabited: Option<i64>, scale: f64) -> bool {
+        let unvisited = self
+            .min_inhabited_ticks
+            .is_some_and(|min| inhabited.is_some_and(|t| t < min));
+        let outside = self.radius.is_some_and(|radius| {
+            let dx = (x * 16 + 8) as f64 - self.center.0 * scale;
+            let dz = (z * 16 + 8) as f64 - self.center.1 * scale;
+            (dx * dx + dz * dz).sqrt() > radius * scale
+        });
+        unvisited || outside
+    }
+}
+
+/// A dimension's folder (the one holding `region/`) in the current world.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dimension {
+    pub name: &'static str,
+    pub root: PathBuf,
+    /// Blocks per overworld block: 1/8 in the Nether.
+    pub scale: f64,
+}
+
+/// Dimensions of the current world that have region files: vanilla keeps the Nether and
+/// End in `DIM-1` and `DIM1` inside the world, Paper and Spigot in `_nether` / `_the_end`.
+pub fn world_dimensions(dir: &Path) -> Vec<Dimension> {
+    let world = current_world(dir);
+    let candidates = [
+        ("Overworld", dir.join(&world), 1.0),
+        ("Nether", dir.join(&world).join("DIM-1"), 0.125),
+        (
+            "Nether",
+            dir.join(format!("{}_nether", world)).join("DIM-1"),
+            0.125,
+        ),
+        ("The End", dir.join(&world).join("DIM1"), 1.0),
+        (
+            "The End",
+            dir.join(format!("{}_the_end", world)).join("DIM1"),
+            1.0,
+        ),
+    ];
+    candidates
+        .into_iter()
+        .filter(|(_, root, _)| root.join("region").is_dir())
+        .map(|(name, root, scale)| Dimension { name, root, scale })
+        .collect()
+}
+
+/// What pruning did, or would do, to one dimension.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PruneReport {
+    pub dimension: String,
+    pub chunks: usize,
+    pub removed: usize,
+    /// Region, entity and POI files, plus external chunk files.
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    /// Region files that couldn't be read and were left alone.
+    pub skipped: Vec<String>,
+}
+
+fn region_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|path| region_coords(path).is_some())
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Drop the chunks at `drop` from one region file, returning (bytes before, bytes after).
+fn prune_file(
+    path: &PathBuf,
+    chunks: Vec<RegionChunk>,
+    drop: &HashSet<(i32, i32)>,
+    dry_run: bool,
+) -> Result<(u64, u64), Box<dyn Error>> {
+    let region_dir = path.parent().unwrap_or(Path::new("."));
+    let mut before = fs::metadata(path)?.len();
+    let (dropped, kept): (Vec<RegionChunk>, Vec<RegionChunk>) = chunks
+        .into_iter()
+        .partition(|chunk| drop.contains(&(chunk.x, chunk.z)));
+    if dropped.is_empty() {
+        return Ok((before, before));
+    }
+    let external: Vec<PathBuf> = dropped
+        .iter()
+        .filter_map(|chunk| chunk.external_file(region_dir))
+        .filter(|file| file.exists())
+        .collect();
+    before += external
+        .iter()
+        .filter_map(|f| fs::metadata(f).ok())
+        .map(|m| m.len())
+        .sum::<u64>();
+    if !dry_run {
+        write_region(path, &kept)?;
+        for file in external {
+            fs::remove_file(file)?;
+        }
+    }
+    Ok((before, region_size(&kept)))
+}
+
+/// Drop chunks matching `rule` in every dimension of the current world. Entity and POI
+/// data for those chunks goes with them. With `dry_run`, nothing is written and the report
+/// says what would be saved. Refused while the server runs, as it would write the chunks
+/// back.
+pub fn prune_regions(
+    dir: &Path,
+    rule: &PruneRule,
+    dry_run: bool,
+) -> Result<Vec<PruneReport>, Box<dyn Error>> {
+    rule.validate()?;
+    refuse_while_running(dir, "prune chunks")?;
+    let dimensions = world_dimensions(dir);
+    if dimensions.is_empty() {
+        return Err("The world has no region files yet".into());
+    }
+    let mut reports = vec![];
+    for dimension in dimensions {
+        let mut report = PruneReport {
+            dimension: dimension.name.to_string(),
+            ..Default::default()
+        };
+        let region_dir = dimension.root.join("region");
+        for path in region_files(&region_dir) {
+            let chunks = match read_region(&path) {
+                Ok(chunks) => chunks,
+                Err(e) => {
+                    report.skipped.push(e.to_string());
+                    continue;
+                }
+            };
+            let drop: HashSet<(i32, i32)> = chunks
+                .iter()
+                .filter(|chunk| {
+                    let inhabited = chunk
+                        .nbt(&region_dir)
+                        .ok()
+                        .and_then(|nbt| inhabited_time(&nbt));
+                    rule.drops(chunk.x, chunk.z, inhabited, dimension.scale)
+                })
+                .map(|chunk| (chunk.x, chunk.z))
+                .collect();
+            report.chunks += chunks.len();
+            report.removed += drop.len();
+
+            let file_name = path.file_name().unwrap_or_default();
+            let companions = ["entities", "poi"]
+                .iter()
+                .map(|folder| dimension.root.join(folder).join(file_name))
+                .filter(|companion| companion.is_file());
+            for file in std::iter::once(path.clone()).chain(companions) {
+                let chunks = if file == path {
+                    chunks.clone()
+                } else {
+                    match read_region(&file) {
+                        Ok(chunks) => chunks,
+                        Err(e) => {
+                            report.skipped.push(e.to_string());
+                            continue;
+                        }
+                    }
+                };
+                let (before, after) = prune_file(&file, chunks, &drop, dry_run)?;
+                report.bytes_before += before;
+                report.bytes_after += after;
+            }
+        }
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+/// Summary of a prune for the world menu.
+pub fn prune_summary(reports: &[PruneReport], dry_run: bool) -> String {
+    let verb = if dry_run { "would remove" } else { "removed" };
+    let mut lines = vec![];
+    for report in reports {
+        lines.push(format!(
+            "{}: {} {} of {} chunks, {} -> {}",
+            report.dimension,
+            verb,
+            report.removed,
+            report.chunks,
+            format_size(report.bytes_before),
+            format_size(report.bytes_after)
+        ));
+        for skipped in &report.skipped {
+            lines.push(format!("  skipped: {}", skipped));
+        }
+    }
+    let saved: u64 = reports
+        .iter()
+        .map(|r| r.bytes_before.saturating_sub(r.bytes_after))
+        .sum();
+    let total = if dry_run {
+        "Space to be freed"
+    } else {
+        "Space freed"
+    };
+    lines.push(format!("{}: {}", total, format_size(saved)));
+    lines.join("\n")
+}
@@ -0,0 +1,316 @@
+use crate::metadata::compare_versions;
+use crate::properties::Properties;
+use crate::utils::fuzzy_score;
+use std::cmp::Ordering;
+
+/// Value type of a `server.properties` key.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PropertyType {
+    Bool,
+    Int { min: i64, max: i64 },
+    Enum(&'static [&'static str]),
+    /// A namespaced id such as `minecraft:flat`; datapacks and mods add their own, so the
+    /// listed ones are only suggestions.
+    ResourceLocation(&'static [&'static str]),
+    String,
+}
+
+/// Schema entry for one `server.properties` key.
+#[derive(Clone, Copy, Debug)]
+pub struct PropertySpec {
+    pub key: &'static str,
+    pub kind: PropertyType,
+    pub default: &'static str,
+    pub description: &'static str,
+    /// First Minecraft version that reads the key (`None` if it predates 1.7).
+    pub added: Option<&'static str>,
+    /// First version that no longer reads it.
+    pub removed: Option<&'static str>,
+}
+
+const fn spec(
+    key: &'static str,
+    kind: PropertyType,
+    default: &'static str,
+    description: &'static str,
+) -> PropertySpec {
+    PropertySpec {
+        key,
+        kind,
+        default,
+        description,
+        added: None,
+        removed: None,
+    }
+}
+
+impl PropertySpec {
+    const fn added(mut self, version: &'static str) -> Self {
+        self.added = Some(version);
+        self
+    }
+
+    const fn removed(mut self, version: &'static str) -> Self {
+        self.removed = Some(version);
+        self
+    }
+
+    /// Whether a server running `version` reads this key.
+    pub fn supported_in(&self, version: &str) -> bool {
+        self.added
+            .is_none_or(|a| compare_versions(version, a) != Ordering::Less)
+            && self
+                .removed
+                .is_none_or(|r| compare_versions(version, r) == Ordering::Less)
+    }
+
+    /// Check `value` against the key's type, range or allowed values.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        match self.kind {
+            PropertyType::Bool => match value {
+                "true" | "false" => Ok(()),
+                _ => Err(format!("{} must be true or false", self.key)),
+            },
+            PropertyType::Int { min, max } => match value.trim().parse::<i64>() {
+                Ok(n) if (min..=max).contains(&n) => Ok(()),
+                _ => Err(format!(
+                    "{} must be a whole number from {} to {}",
+                    self.key, min, max
+                )),
+            },
+            PropertyType::Enum(values) => {
+                if values.iter().any(|v| v.eq_ignore_ascii_case(value)) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{} must be one of: {}",
+                        self.key,
+                        values.join(", ")
+                    ))
+                }
+            }
+            PropertyType::ResourceLocation(_) => {
+                if is_resource_location(&value.to_ascii_lowercase()) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{} must be an id like minecraft:flat or flat",
+                        self.key
+                    ))
+                }
+            }
+            PropertyType::String => Ok(()),
+        }
+    }
+}
+
+/// `namespace:path` or a bare path (read as `minecraft:path`), in the characters Minecraft
+/// allows.
+fn is_resource_location(value: &str) -> bool {
+    let (namespace, path) = value.split_once(':').unwrap_or(("minecraft", value));
+    let allowed = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-.".contains(c);
+    !namespace.is_empty()
+        && !path.is_empty()
+        && namespace.chars().all(allowed)
+        && path.chars().all(|c| allowed(c) || c == '/')
+}
+
+const PORT: PropertyType = PropertyType::Int { min: 1, max: 65535 };
+const I32_MAX: i64 = i32::MAX as i64;
+
+/// Every key vanilla servers have read from `server.properties`, in the order Minecraft
+/// writes them.
+#[rustfmt::skip]
+pub const SERVER_PROPERTIES: &[PropertySpec] = &[
+    spec("accepts-transfers", PropertyType::Bool, "false", "Accept players transferred from another server").added("1.20.5"),
+    spec("allow-flight", PropertyType::Bool, "false", "Don't kick players for flying in survival"),
+    spec("allow-nether", PropertyType::Bool, "true", "Allow travel to the Nether"),
+    spec("announce-player-achievements", PropertyType::Bool, "true", "Announce achievements in chat").removed("1.12"),
+    spec("broadcast-console-to-ops", PropertyType::Bool, "true", "Send console command output to online ops"),
+    spec("broadcast-rcon-to-ops", PropertyType::Bool, "true", "Send RCON command output to online ops"),
+    spec("bug-report-link", PropertyType::String, "", "Link shown on the disconnect screen for reporting bugs").added("1.21"),
+    spec("debug", PropertyType::Bool, "false", "Extra debug logging"),
+    spec("difficulty", PropertyType::Enum(&["peaceful", "easy", "normal", "hard"]), "easy", "World difficulty"),
+    spec("enable-command-block", PropertyType::Bool, "false", "Allow command blocks to run"),
+    spec("enable-jmx-monitoring", PropertyType::Bool, "false", "Expose tick time metrics over JMX").added("1.16"),
+    spec("enable-query", PropertyType::Bool, "false", "Answer GameSpy4 query requests"),
+    spec("enable-rcon", PropertyType::Bool, "false", "Allow remote console access"),
+    spec("enable-status", PropertyType::Bool, "true", "Show the server as online in the server list").added("1.16"),
+    spec("enforce-secure-profile", PropertyType::Bool, "true", "Require Mojang-signed chat keys").added("1.19"),
+    spec("enforce-whitelist", PropertyType::Bool, "false", "Kick players not on a reloaded whitelist"),
+    spec("entity-broadcast-range-percentage", PropertyType::Int { min: 10, max: 1000 }, "100", "How far away entities are sent to clients, in percent").added("1.16"),
+    spec("force-gamemode", PropertyType::Bool, "false", "Put players in the default gamemode on join"),
+    spec("function-permission-level", PropertyType::Int { min: 1, max: 4 }, "2", "Permission level of functions").added("1.14.4"),
+    spec("gamemode", PropertyType::Enum(&["survival", "creative", "adventure", "spectator"]), "survival", "Default gamemode for new players"),
+    spec("generate-structures", PropertyType::Bool, "true", "Generate villages, temples and other structures"),
+    spec("generator-settings", PropertyType::String, "{}", "JSON settings for customised world types"),
+    spec("hardcore", PropertyType::Bool, "false", "Players are banned on death"),
+    spec("hide-online-players", PropertyType::Bool, "false", "Hide the player list in the server status").added("1.18"),
+    spec("initial-disabled-packs", PropertyType::String, "", "Datapacks not enabled when the world is created").added("1.19.3"),
+    spec("initial-enabled-packs", PropertyType::String, "vanilla", "Datapacks enabled when the world is created").added("1.19.3"),
+    spec("level-name", PropertyType::String, "world", "World folder name"),
+    spec("level-seed", PropertyType::String, "", "World generation seed, random if empty"),
+    spec(
+        "level-type",
+        PropertyType::ResourceLocation(&[
+            "minecraft:normal",
+            "minecraft:flat",
+            "minecraft:large_biomes",
+            "minecraft:amplified",
+            "minecraft:single_biome_surface",
+            "default",
+            "flat",
+            "largebiomes",
+            "amplified",
+            "buffet",
+            "customized",
+        ]),
+        "minecraft:normal",
+        "World generation preset",
+    ),
+    spec("log-ips", PropertyType::Bool, "true", "Log player IP addresses").added("1.20.2"),
+    spec("max-build-height", PropertyType::Int { min: 64, max: 256 }, "256", "Highest block players can build at").removed("1.17"),
+    spec("max-chained-neighbor-updates", PropertyType::Int { min: -I32_MAX, max: I32_MAX }, "1000000", "Limit on consecutive neighbour updates").added("1.19"),
+    spec("max-players", PropertyType::Int { min: 0, max: I32_MAX }, "20", "Most players online at once"),
+    spec("max-tick-time", PropertyType::Int { min: -1, max: i64::MAX }, "60000", "Milliseconds a tick may take before the watchdog stops the server"),
+    spec("max-world-size", PropertyType::Int { min: 1, max: 29999984 }, "29999984", "World border radius in blocks"),
+    spec("motd", PropertyType::String, "A Minecraft Server", "Message shown in the server list"),
+    spec("network-compression-threshold", PropertyType::Int { min: -1, max: I32_MAX }, "256", "Compress packets above this size in bytes"),
+    spec("online-mode", PropertyType::Bool, "true", "Verify accounts with Mojang"),
+    spec("op-permission-level", PropertyType::Int { min: 0, max: 4 }, "4", "Permission level given by /op"),
+    spec("pause-when-empty-seconds", PropertyType::Int { min: -1, max: I32_MAX }, "60", "Pause ticking after the server has been empty this long").added("1.21.2"),
+    spec("player-idle-timeout", PropertyType::Int { min: 0, max: I32_MAX }, "0", "Kick idle players after this many minutes"),
+    spec("prevent-proxy-connections", PropertyType::Bool, "false", "Kick players whose ISP differs from the one Mojang saw"),
+    spec("previews-chat", PropertyType::Bool, "false", "Preview chat messages while typing").added("1.19").removed("1.19.3"),
+    spec("pvp", PropertyType::Bool, "true", "Players can damage each other"),
+    spec("query.port", PORT, "25565", "Port for query requests"),
+    spec("rate-limit", PropertyType::Int { min: 0, max: I32_MAX }, "0", "Kick players sending more packets per second than this"),
+    spec("rcon.password", PropertyType::String, "", "Remote console password"),
+    spec("rcon.port", PORT, "25575", "Remote console port"),
+    spec("region-file-compression", PropertyType::Enum(&["deflate", "lz4", "none"]), "deflate", "Compression for region files").added("1.20.5"),
+    spec("require-resource-pack", PropertyType::Bool, "false", "Kick players who decline the resource pack").added("1.17"),
+    spec("resource-pack", PropertyType::String, "", "URL of the server resource pack"),
+    spec("resource-pack-id", PropertyType::String, "", "UUID of the server resource pack").added("1.20.3"),
+    spec("resource-pack-prompt", PropertyType::String, "", "Message shown when offering the resource pack").added("1.17"),
+    spec("resource-pack-sha1", PropertyType::String, "", "SHA-1 of the resource pack"),
+    spec("server-ip", PropertyType::String, "", "Address to bind to, all interfaces if empty"),
+    spec("server-port", PORT, "25565", "Port players connect to"),
+    spec("simulation-distance", PropertyType::Int { min: 3, max: 32 }, "10", "Chunks around players that are ticked").added("1.18"),
+    spec("snooper-enabled", PropertyType::Bool, "true", "Send usage data to Mojang").removed("1.18"),
+    spec("spawn-animals", PropertyType::Bool, "true", "Spawn animals").removed("1.21.2"),
+    spec("spawn-monsters", PropertyType::Bool, "true", "Spawn hostile mobs"),
+    spec("spawn-npcs", PropertyType::Bool, "true", "Spawn villagers").removed("1.21.2"),
+    spec("spawn-protection", PropertyType::Int { min: 0, max: I32_MAX }, "16", "Radius around spawn only ops can build in"),
+    spec("sync-chunk-writes", PropertyType::Bool, "true", "Write chunks synchronously").added("1.16"),
+    spec("text-filtering-config", PropertyType::String, "", "Chat filtering service config").added("1.17"),
+    spec("use-native-transport", PropertyType::Bool, "true", "Use Linux epoll networking"),
+    spec("view-distance", PropertyType::Int { min: 3, max: 32 }, "10", "Chunks sent to clients"),
+    spec("white-list", PropertyType::Bool, "false", "Only let whitelisted players join"),
+];
+
+pub fn property_spec(key: &str) -> Option<&'static PropertySpec> {
+    SERVER_PROPERTIES.iter().find(|s| s.key == key)
+}
+
+/// Check a value for the menus' input prompts; unknown keys accept anything.
+pub fn check_property(key: &str, value: &str) -> Result<(), String> {
+    property_spec(key).map_or(Ok(()), |s| s.check(value))
+}
+
+/// Validate one `server.properties` edit for a server running `version`. Bad values are
+/// errors; unknown keys and keys the version doesn't read come back as warnings.
+pub fn validate_property(key: &str, value: &str, version: &str) -> Result<Vec<String>, String> {
+    let Some(spec) = property_spec(key) else {
+        return Ok(vec![format!(
+            "{} is not a vanilla server.properties key; Minecraft will ignore it",
+            key
+        )]);
+    };
+    spec.check(value)?;
+    let mut warnings = Vec::new();
+    if !spec.supported_in(version) {
+        match (spec.added, spec.removed) {
+            (_, Some(removed)) if compare_versions(version, removed) != Ordering::Less => warnings
+                .push(format!(
+                    "{} was removed in Minecraft {}; this server is {}",
+                    key, removed, version
+                )),
+            (Some(added), _) => warnings.push(format!(
+                "{} was added in Minecraft {}; this server is {}",
+                key, added, version
+            )),
+            _ => {}
+        }
+    }
+    Ok(warnings)
+}
+
+/// Validate a batch of edits, failing on the first bad value.
+pub fn validate_properties(edits: &[(&str, &str)], version: &str) -> Result<Vec<String>, String> {
+    let mut warnings = Vec::new();
+    for (key, value) in edits {
+        warnings.extend(validate_property(key, value, version)?);
+    }
+    Ok(warnings)
+}
+
+/// Warnings for keys already in a `server.properties` that `version` doesn't read.
+pub fn stale_property_warnings(keys: &[&str], version: &str) -> Vec<String> {
+    keys.iter()
+        .filter_map(|k| property_spec(k))
+        .filter(|s| !s.supported_in(version))
+        .filter_map(|s| validate_property(s.key, s.default, version).ok())
+        .flatten()
+        .collect()
+}
+
+/// One key of a `server.properties` file as shown in the editor.
+#[derive(Clone, Debug)]
+pub struct PropertyRow {
+    pub key: String,
+    pub value: String,
+    pub spec: Option<&'static PropertySpec>,
+}
+
+impl PropertyRow {
+    pub fn default(&self) -> Option<&'static str> {
+        self.spec.map(|s| s.default)
+    }
+
+    /// Whether the value differs from Minecraft's default. Unknown keys never count.
+    pub fn is_changed(&self) -> bool {
+        self.default().is_some_and(|d| d != self.value)
+    }
+}
+
+/// Every key in the file, in file order, with its schema entry when there is one.
+pub fn property_rows(props: &Properties) -> Vec<PropertyRow> {
+    props
+        .entries()
+        .into_iter()
+        .map(|(key, value)| PropertyRow {
+            key: key.to_string(),
+            value: value.to_string(),
+            spec: property_spec(key),
+        })
+        .collect()
+}
+
+/// Rows fuzzily matching `query` on key or description, best match first. An empty query
+/// keeps every row in file order.
+pub fn search_property_rows<'a>(rows: &'a [PropertyRow], query: &str) -> Vec<&'a PropertyRow> {
+    let mut scored: Vec<(i64, &PropertyRow)> = rows
+        .iter()
+        .filter_map(|row| {
+            let key = fuzzy_score(query, &row.key);
+            // Description hits rank below key hits
+            let description = row
+                .spec
+                .and_then(|s| fuzzy_score(query, s.description))
+                .map(|score| score - 100);
+            key.max(description).map(|score| (score, row))
+        })
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, row)| row).collect()
+}
@@ -1,13 +1,61 @@
-use crate::config::{configure_file, read_oxide_config, read_property, write_oxide_config};
-use crate::download::{convert_to_items, download_url, get_jar_url, get_versions};
-use crate::mods::{get_curseforge_key, install_curseforge, install_modrinth, search_modrinth};
-use crate::preset::{auto_save_preset, list_presets, load_preset, save_preset};
-use crate::utils::{backup_world, expand_path, get_platform, list_entries};
-use cliclack::{confirm, input, intro, log, multiselect, outro, select, spinner};
-use ferinth::structures::project::ProjectType;
+use crate::bisect::{abort_bisect, bisect_step, read_bisect, start_bisect, BisectOutcome};
+use crate::config::{
+    configure_file, configure_properties, read_oxide_config, read_property, write_oxide_config,
+};
+use crate::doctor::{diagnose, disable_issue_files, run_doctor, scan_content};
+use crate::download::{
+    convert_to_items, download_url, fabric_server_jar_url, get_jar_url, get_versions,
+};
+use crate::level::{apply_world_changes, read_world_info, world_details, WorldChange};
+use crate::lockfile::forget_install;
+use crate::metadata::{read_jar_metadata, server_compatibility_warnings};
+use crate::modpack::{
+    apply_curseforge_pack, apply_mrpack, curseforge_platform, detect_server_pack_platform,
+    export_mrpack, extract_zip_dir, loader_install_hint, mrpack_platform, read_curseforge_manifest,
+    read_mrpack_index, server_pack_game_version, server_pack_root,
+};
+use crate::mods::{
+    apply_update, check_updates, curseforge_class_id, curseforge_content_type, curseforge_details,
+    curseforge_loader, disable_content, enable_content, get_curseforge_key, install_curseforge,
+    install_from_file, install_from_url, install_modrinth_version, list_content,
+    modrinth_categories, modrinth_details, modrinth_loader, plan_modrinth_install,
+    search_curseforge, search_modrinth, CurseForgeSearch, ModrinthSearch, SideFilter,
+};
+use crate::playerdata::{list_players, player_details};
+use crate::players::{
+    apply_player_change, player_list_rows, valid_player_name, PlayerChange, PlayerList,
+};
+use crate::pregen::run_pregen;
+use crate::preset::{
+    apply_settings, apply_tuning_profile, auto_save_preset, install_preset_mods, list_presets,
+    load_preset, preset_property_warnings, save_preset, tuning_profiles, InstallReport,
+};
+use crate::properties::Properties;
+use crate::rcon::server_running;
+use crate::region::{prune_regions, prune_summary, PruneRule};
+use crate::schema::{
+    check_property, property_rows, search_property_rows, stale_property_warnings,
+    validate_properties, PropertyRow, PropertyType,
+};
+use crate::storage::{backup_backend, parse_backend};
+use crate::utils::{
+    backup_world, expand_home, expand_path, format_count, format_size, get_platform, list_entries,
+    truncate_chars,
+};
+use crate::worlds::{
+    create_world, current_world, import_name, import_world_zip, list_worlds, reset_world,
+    switch_world, valid_world_name, zip_world_root, WorldFolder,
+};
+use crate::yaml::read_yaml_value;
+use cliclack::{
+    confirm, input, intro, log, multiselect, outro, progress_bar, select, spinner,
+};
+use ferinth::structures::{project::ProjectType, search::Sort};
+use furse::structures::common_structs::ModLoaderType;
 //use serde_json::error;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, create_dir_all};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::process::Command;
 use std::process::Stdio;
 
@@ -84,6 +132,191 @@ impl OxideMC {
         Ok(oxide)
     }
 
+    /// Create a new server from a Modrinth `.mrpack`: server jar for the pack's loader,
+    /// server-side files, and the pack's overrides.
+    pub async fn setup_from_mrpack(pack: &PathBuf) -> Result<Self, String> {
+        let index = read_mrpack_index(pack).map_err(|e| e.to_string())?;
+        let (platform, version, loader_version) = mrpack_platform(&index)?;
+        let _ = intro(format!("Installing {} {}", index.name, index.version_id));
+        log::info(format!("{} {} modpack", platform, version)).unwrap();
+
+        let dir = prompt_server_dir(&index.name)?;
+        let jar_supported = pack_jar_supported(&platform);
+        if jar_supported {
+            download_server_jar(&dir, &platform, &version, loader_version.as_deref()).await?;
+        }
+
+        let report = apply_mrpack(pack, &dir).await.map_err(|e| e.to_string())?;
+        log_install_report(&report);
+        if !jar_supported {
+            return Ok(Self::loader_left_to_install(
+                dir,
+                platform,
+                version,
+                loader_version,
+            ));
+        }
+
+        Ok(OxideMC {
+            dir,
+            platform,
+            version,
+        }
+        .finish_setup()
+        .await)
+    }
+
+    /// Create a new server from a CurseForge modpack zip (with `manifest.json`) or a
+    /// ready-made CurseForge server pack.
+    pub async fn setup_from_curseforge(pack: &PathBuf) -> Result<Self, String> {
+        let Some(manifest) = read_curseforge_manifest(pack).map_err(|e| e.to_string())? else {
+            return Self::setup_from_server_pack(pack).await;
+        };
+        let (platform, version, loader_version) = curseforge_platform(&manifest)?;
+        let _ = intro(format!("Installing {} {}", manifest.name, manifest.version));
+        log::info(format!("{} {} modpack", platform, version)).unwrap();
+
+        let dir = prompt_server_dir(&manifest.name)?;
+        let jar_supported = pack_jar_supported(&platform);
+        if jar_supported {
+            download_server_jar(&dir, &platform, &version, loader_version.as_deref()).await?;
+        }
+
+        let api_key = get_curseforge_key(&dir);
+        let spin = spinner();
+        spin.start(format!("Downloading {} file(s)...", manifest.files.len()));
+        let (report, manual) = apply_curseforge_pack(pack, &manifest, &dir, &api_key)
+            .await
+            .map_err(|e| e.to_string())?;
+        spin.stop("Modpack downloaded");
+        log_install_report(&report);
+        if !manual.is_empty() {
+            log::warning(format!(
+                "{} file(s) must be downloaded manually into {}:",
+                manual.len(),
+                dir.join("mods").display()
+            ))
+            .unwrap();
+            for m in &manual {
+                log::warning(format!("{} ({}): {}", m.name, m.file_name, m.url)).unwrap();
+            }
+        }
+        if !jar_supported {
+            return Ok(Self::loader_left_to_install(
+                dir,
+                platform,
+                version,
+                loader_version,
+            ));
+        }
+
+        Ok(OxideMC {
+            dir,
+            platform,
+            version,
+        }
+        .finish_setup()
+        .await)
+    }
+
+    /// Finish a modpack whose loader OxideMC can't install by telling the user how to.
+    fn loader_left_to_install(
+        dir: PathBuf,
+        platform: String,
+        version: String,
+        loader_version: Option<String>,
+    ) -> Self {
+        log::warning(format!(
+            "{} in {}",
+            loader_install_hint(&platform, &version, &loader_version.unwrap_or_default()),
+            dir.display()
+        ))
+        .unwrap();
+        let _ = outro("Modpack downloaded");
+        OxideMC {
+            dir,
+            platform,
+            version,
+        }
+    }
+
+    /// Unpack a CurseForge server pack as-is; it already contains its mods and configs.
+    async fn setup_from_server_pack(pack: &PathBuf) -> Result<Self, String> {
+        let _ = intro("Installing CurseForge server pack");
+        let default_name = pack
+            .file_stem()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "minecraft-server".to_string());
+        let dir = prompt_server_dir(&default_name)?;
+
+        let root = server_pack_root(pack).map_err(|e| e.to_string())?;
+        let extracted = extract_zip_dir(pack, &root, &dir).map_err(|e| e.to_string())?;
+        log::success(format!("Extracted {} file(s)", extracted.len())).unwrap();
+
+        let platform = match detect_server_pack_platform(&dir) {
+            Some(p) => p.to_string(),
+            None => select("Which loader does this server pack use?")
+                .item("Fabric", "Fabric", "")
+                .item("Forge", "Forge", "")
+                .item("NeoForge", "NeoForge", "")
+                .interact()
+                .unwrap()
+                .to_string(),
+        };
+        let version = match server_pack_game_version(&dir) {
+            Some(v) => v,
+            None => input("Which Minecraft version is this pack for?")
+                .required(true)
+                .interact()
+                .unwrap(),
+        };
+        log::info(format!("{} {} server pack", platform, version)).unwrap();
+
+        if platform == "Fabric" && !dir.join("server.jar").exists() {
+            download_server_jar(&dir, &platform, &version, None).await?;
+        } else if platform != "Fabric" {
+            log::warning(format!(
+                "{} servers are started with the pack's own start script (e.g. run.sh or startserver.sh) in {}",
+                platform,
+                dir.display()
+            ))
+            .unwrap();
+            let _ = outro("Server pack extracted");
+            return Ok(OxideMC {
+                dir,
+                platform,
+                version,
+            });
+        }
+
+        Ok(OxideMC {
+            dir,
+            platform,
+            version,
+        }
+        .finish_setup()
+        .await)
+    }
+
+    /// First start, EULA and initial preset for a freshly created server.
+    async fn finish_setup(self) -> Self {
+        if self.start().await.is_err() {
+            log::error("Failed to start server. This is likely because Java is not installed or not in your PATH. Please install Java and try again.").unwrap();
+        }
+
+        if confirm("Do you accept EULA?").interact().unwrap() {
+            let _ = configure_file(&self.dir, "eula.txt", "eula", "true");
+        } else {
+            log::warning("You must accept the EULA to run the server.").unwrap();
+        }
+
+        auto_save_preset(&self.dir, &self.platform, &self.version);
+
+        let _ = outro("You're all set!");
+
+        self
+    }
+
     pub async fn new(dir: PathBuf, platform: String, version: String) -> Self {
         let jar_url = get_jar_url(&platform, &version).await.unwrap();
         let _ = download_url(&jar_url, &dir, "server.jar").await;
@@ -97,7 +330,7 @@ impl OxideMC {
         }
     }
 
-    pub fn open(dir: &PathBuf) -> Result<Self, String> {
+    pub fn open(dir: &Path) -> Result<Self, String> {
         let dir = expand_path(dir.to_str().unwrap()).unwrap();
         let jar_path = dir.join("server.jar");
         if !jar_path.exists() {
@@ -108,7 +341,7 @@ impl OxideMC {
                 .find(|p| p.extension().and_then(|e| e.to_str()) == Some("jar"));
 
             match jar {
-                Some(path) => fs::rename(&path, &dir.join("server.jar")).map_err(|e| {
+                Some(path) => fs::rename(&path, dir.join("server.jar")).map_err(|e| {
                     format!("Failed to rename {} to server.jar: {}", path.display(), e)
                 })?,
                 None => return Err("No .jar file found in directory".to_string()),
@@ -117,24 +350,19 @@ impl OxideMC {
 
         let versions_dir = dir.join("versions");
         let mut versions: Vec<String> = fs::read_dir(&versions_dir)
-            .unwrap()
-            .filter_map(|entry| {
-                let entry = entry.ok()?;
-                let path = entry.path();
-                if path.is_dir() {
-                    Some(
-                        path.to_str()
-                            .unwrap()
-                            .split("/")
-                            .last()
-                            .unwrap()
-                            .to_string(),
-                    )
-                } else {
-                    None
-                }
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| {
+                        let path = entry.ok()?.path();
+                        if path.is_dir() {
+                            Some(path.file_name()?.to_str()?.to_string())
+                        } else {
+                            None
+                        }
+                    })
+                    .collect()
             })
-            .collect();
+            .unwrap_or_default();
 
         versions.sort_by(|a, b| {
             let parse =
@@ -142,16 +370,44 @@ impl OxideMC {
             parse(b).cmp(&parse(a))
         });
 
+        // The server unpacks versions/ on first start; before that, or if it was deleted,
+        // the world's level.dat still says which version last ran it
+        let version = match versions.first() {
+            Some(version) => version.clone(),
+            None => read_world_info(&dir)
+                .ok()
+                .and_then(|info| info.game_version)
+                .ok_or("Could not tell the Minecraft version: no versions/ folder or level.dat")?,
+        };
+
         Ok(OxideMC {
             dir: dir.clone(),
             platform: get_platform(&dir).unwrap(),
-            version: versions.first().unwrap().to_string(),
+            version,
         })
     }
 
     pub async fn configure(&self) {
         let mut page: &str = "main";
 
+        if let Ok(props) = Properties::load(&self.dir.join("server.properties")) {
+            let keys: Vec<&str> = props.entries().into_iter().map(|(k, _)| k).collect();
+            for warning in stale_property_warnings(&keys, &self.version) {
+                log::warning(warning).unwrap();
+            }
+        }
+        if let Some(saved) = read_world_info(&self.dir)
+            .ok()
+            .and_then(|info| info.game_version)
+            .filter(|saved| *saved != self.version)
+        {
+            log::warning(format!(
+                "The world was last saved by Minecraft {}; this server runs {}",
+                saved, self.version
+            ))
+            .unwrap();
+        }
+
         // Auto-save preset.json on every loop iteration (after each setting change)
         loop {
             auto_save_preset(&self.dir, &self.platform, &self.version);
@@ -172,7 +428,13 @@ impl OxideMC {
                         .item("game", "Game", "Players, difficulty, gamemode")
                         .item("world", "World", "Seed, type, backup, border")
                         .item("mods", mods_label, mods_hint)
+                        .item("players", "Players", "Ops, whitelist, bans")
                         .item("advanced", "Advanced", "Port, MOTD, online mode")
+                        .item(
+                            "properties",
+                            "Properties",
+                            "Search and edit server.properties",
+                        )
                         .item("quit", "Quit", "")
                         .interact()
                         .unwrap();
@@ -181,6 +443,8 @@ impl OxideMC {
                     let action = select("Presets")
                         .item("save", "Export Preset", "Copy preset.json to a file")
                         .item("load", "Load Preset", "Apply a saved preset")
+                        .item("mrpack", "Export Modpack", "Share as a Modrinth .mrpack")
+                        .item("profile", "Tuning Profile", "Built-in performance tweaks")
                         .item("back", "Back", "")
                         .interact()
                         .unwrap();
@@ -283,13 +547,44 @@ impl OxideMC {
                                     &self.platform,
                                     &self.version,
                                 ) {
-                                    Ok(()) => {
+                                    Ok(preset) => {
+                                        for warning in
+                                            preset_property_warnings(&preset, &self.version)
+                                        {
+                                            log::warning(warning).unwrap();
+                                        }
+                                        log::success("Preset settings applied!").unwrap();
+                                        if !preset.gamerules.is_empty()
+                                            && read_world_info(&self.dir).is_err()
+                                            && !server_running(&self.dir)
+                                        {
+                                            log::warning("The world doesn't exist yet, so its game rules weren't applied; load the preset again after the first start").unwrap();
+                                        }
+                                        let report = install_preset_mods(
+                                            &self.dir,
+                                            &preset.mods,
+                                            &self.platform,
+                                            &self.version,
+                                        )
+                                        .await;
+                                        for name in &report.installed {
+                                            log::success(format!("Installed {}", name)).unwrap();
+                                        }
+                                        for name in &report.skipped {
+                                            log::info(format!("Skipped {}", name)).unwrap();
+                                        }
+                                        for (name, e) in &report.failed {
+                                            log::error(format!(
+                                                "Failed to install {}: {}",
+                                                name, e
+                                            ))
+                                            .unwrap();
+                                        }
                                         auto_save_preset(
                                             &self.dir,
                                             &self.platform,
                                             &self.version,
                                         );
-                                        log::success("Preset applied!").unwrap();
                                     }
                                     Err(e) => {
                                         log::error(format!("Failed to load: {}", e)).unwrap()
@@ -297,6 +592,53 @@ impl OxideMC {
                                 }
                             }
                         }
+                        "mrpack" => {
+                            let default_name = self
+                                .dir
+                                .file_name()
+                                .map(|n| n.to_string_lossy().to_string())
+                                .unwrap_or_else(|| "server".to_string());
+                            let name: String = input("Modpack name:")
+                                .default_input(&default_name)
+                                .required(true)
+                                .interact()
+                                .unwrap();
+                            let dest: String = input("Save modpack to (file path):")
+                                .default_input(&format!("~/{}.mrpack", name.replace(' ', "-")))
+                                .required(true)
+                                .interact()
+                                .unwrap();
+                            let dest_path = expand_home(dest.trim());
+                            let spin = spinner();
+                            spin.start("Resolving mods on Modrinth...");
+                            match export_mrpack(
+                                &self.dir,
+                                &self.platform,
+                                &self.version,
+                                &name,
+                                &dest_path,
+                            )
+                            .await
+                            {
+                                Ok(report) => {
+                                    spin.stop(format!(
+                                        "Modpack exported to {}",
+                                        dest_path.display()
+                                    ));
+                                    log::info(format!(
+                                        "{} linked from Modrinth, {} bundled",
+                                        report.linked.len(),
+                                        report.bundled.len()
+                                    ))
+                                    .unwrap();
+                                    for warning in &report.warnings {
+                                        log::warning(warning).unwrap();
+                                    }
+                                }
+                                Err(e) => spin.error(format!("Failed to export modpack: {}", e)),
+                            }
+                        }
+                        "profile" => self.tuning_profile(),
                         _ => {
                             page = "main";
                         }
@@ -315,17 +657,10 @@ impl OxideMC {
                         "max-players" => {
                             let max: String = input("Max players:")
                                 .default_input("20")
-                                .validate(|input: &String| {
-                                    if input.parse::<u32>().map(|n| n >= 1).unwrap_or(false) {
-                                        Ok(())
-                                    } else {
-                                        Err("Please enter a positive integer".to_string())
-                                    }
-                                })
+                                .validate(|input: &String| check_property("max-players", input))
                                 .interact()
                                 .unwrap();
-                            configure_file(&self.dir, "server.properties", "max-players", &max)
-                                .unwrap();
+                            self.set_property("max-players", &max);
                         }
                         "difficulty" => {
                             let difficulty = select("Select the difficulty level:")
@@ -335,13 +670,7 @@ impl OxideMC {
                                 .item("hard", "Hard", "")
                                 .interact()
                                 .unwrap();
-                            configure_file(
-                                &self.dir,
-                                "server.properties",
-                                "difficulty",
-                                difficulty,
-                            )
-                            .unwrap();
+                            self.set_property("difficulty", difficulty);
                         }
                         "gamemode" => {
                             let gamemode = select("Select the default gamemode:")
@@ -351,8 +680,7 @@ impl OxideMC {
                                 .item("spectator", "Spectator", "")
                                 .interact()
                                 .unwrap();
-                            configure_file(&self.dir, "server.properties", "gamemode", gamemode)
-                                .unwrap();
+                            self.set_property("gamemode", gamemode);
                         }
                         "pvp" => {
                             let pvp = select("Enable PVP?")
@@ -360,7 +688,7 @@ impl OxideMC {
                                 .item("false", "No", "")
                                 .interact()
                                 .unwrap();
-                            configure_file(&self.dir, "server.properties", "pvp", pvp).unwrap();
+                            self.set_property("pvp", pvp);
                         }
                         _ => {
                             page = "main";
@@ -369,27 +697,45 @@ impl OxideMC {
                 }
                 "world" => {
                     let subpage = select("World")
-                        .item("seed", "Seed", "World generation seed")
+                        .item("worlds", "Worlds", "Switch, create, reset or import worlds")
+                        .item(
+                            "info",
+                            "World Info",
+                            "Seed, version, spawn and rules from level.dat",
+                        )
+                        .item("seed", "Seed", "Seed for new worlds")
                         .item("worldtype", "Type", "Terrain generation type")
                         .item("backup", "Backup", "Create or configure backups")
-                        .item("worldborder", "World Border", "Radius and center")
+                        .item("worldborder", "World Border", "Radius, center and size")
+                        .item("gamerules", "Game Rules", "keepInventory, mobGriefing, ...")
+                        .item(
+                            "pregen",
+                            "Pre-generate",
+                            "Generate chunks ahead with Chunky",
+                        )
+                        .item(
+                            "prune",
+                            "Prune Chunks",
+                            "Delete unvisited or far-out chunks",
+                        )
                         .item("back", "Back", "")
                         .interact()
                         .unwrap();
                     match subpage {
+                        "worlds" => self.worlds_menu().await,
+                        "pregen" => self.pregen_menu().await,
+                        "prune" => self.prune_menu().await,
+                        "info" => match read_world_info(&self.dir) {
+                            Ok(info) => log::info(world_details(&info)).unwrap(),
+                            Err(e) => log::warning(format!("Can't read the world: {}", e)).unwrap(),
+                        },
                         "seed" => {
                             let seed: String = input("Seed (leave blank for random):")
                                 .default_input("")
                                 .required(false)
                                 .interact()
                                 .unwrap();
-                            configure_file(
-                                &self.dir,
-                                "server.properties",
-                                "level-seed",
-                                &seed,
-                            )
-                            .unwrap();
+                            self.set_property("level-seed", &seed);
                         }
                         "worldtype" => {
                             let worldtype = select("Select the world type:")
@@ -407,28 +753,39 @@ impl OxideMC {
                                 )
                                 .interact()
                                 .unwrap();
-                            configure_file(
-                                &self.dir,
-                                "server.properties",
-                                "level-type",
-                                worldtype,
-                            )
-                            .unwrap();
+                            self.set_property("level-type", worldtype);
                         }
                         "backup" => {
                             let backup_action = select("Backup")
                                 .item("now", "Backup Now", "Timestamped backup now")
-                                .item("folder", "Backup Folder", "Set backup directory")
+                                .item("list", "List Backups", "Show stored backups")
+                                .item("folder", "Backup Target", "Local folder, ssh:// or s3://")
                                 .item("back", "Back", "")
                                 .interact()
                                 .unwrap();
                             match backup_action {
-                                "now" => match backup_world(&self.dir) {
-                                    Ok(()) => {}
+                                "now" => match backup_world(&self.dir).await {
+                                    Ok(_) => {}
                                     Err(e) => {
                                         log::error(format!("Backup failed: {}", e)).unwrap()
                                     }
                                 },
+                                "list" => match backup_backend(&self.dir) {
+                                    Ok(backend) => match backend.list().await {
+                                        Ok(names) if !names.is_empty() => log::info(format!(
+                                            "Backups in {}:\n{}",
+                                            backend.describe(),
+                                            names.join("\n")
+                                        ))
+                                        .unwrap(),
+                                        Ok(_) => log::warning("No backups found.").unwrap(),
+                                        Err(e) => {
+                                            log::error(format!("Failed to list: {}", e)).unwrap()
+                                        }
+                                    },
+                                    Err(e) => log::error(format!("Invalid backup target: {}", e))
+                                        .unwrap(),
+                                },
                                 "folder" => {
                                     let current = read_oxide_config(&self.dir, "backup_dir")
                                         .unwrap_or_else(|_| {
@@ -437,13 +794,20 @@ impl OxideMC {
                                                 .to_string_lossy()
                                                 .to_string()
                                         });
-                                    let folder: String = input("Backup folder path:")
-                                        .default_input(&current)
-                                        .interact()
-                                        .unwrap();
+                                    let dir = self.dir.clone();
+                                    let folder: String =
+                                        input("Backup target (path, ssh://user@host/path, s3://bucket/prefix):")
+                                            .default_input(&current)
+                                            .validate(move |s: &String| {
+                                                parse_backend(&dir, s)
+                                                    .map(|_| ())
+                                                    .map_err(|e| e.to_string())
+                                            })
+                                            .interact()
+                                            .unwrap();
                                     match write_oxide_config(&self.dir, "backup_dir", &folder) {
                                         Ok(()) => log::success(format!(
-                                            "Backup folder set to {}",
+                                            "Backup target set to {}",
                                             folder
                                         ))
                                         .unwrap(),
@@ -458,7 +822,8 @@ impl OxideMC {
                         "worldborder" => {
                             let border_action = select("World Border")
                                 .item("radius", "Radius", "Max world radius in blocks")
-                                .item("center", "Center", "Get /worldborder center command")
+                                .item("center", "Center", "Move the border's center")
+                                .item("size", "Size", "Current border width")
                                 .item("back", "Back", "")
                                 .interact()
                                 .unwrap();
@@ -468,27 +833,21 @@ impl OxideMC {
                                         input("Max world radius in blocks (1-29999984):")
                                             .default_input("29999984")
                                             .validate(|input: &String| {
-                                                match input.parse::<u32>() {
-                                                    Ok(n) if n >= 1 => Ok(()),
-                                                    _ => Err(
-                                                        "Please enter a positive integer up to 29999984"
-                                                            .to_string(),
-                                                    ),
-                                                }
+                                                check_property("max-world-size", input)
                                             })
                                             .interact()
                                             .unwrap();
-                                    configure_file(
-                                        &self.dir,
-                                        "server.properties",
-                                        "max-world-size",
-                                        &size,
-                                    )
-                                    .unwrap();
+                                    self.set_property("max-world-size", &size);
                                 }
                                 "center" => {
+                                    let current = read_world_info(&self.dir)
+                                        .ok()
+                                        .and_then(|info| info.border)
+                                        .map_or("0,0".to_string(), |b| {
+                                            format!("{},{}", b.center_x, b.center_z)
+                                        });
                                     let center: String = input("World border center (x,z):")
-                                        .default_input("0,0")
+                                        .default_input(&current)
                                         .validate(|input: &String| {
                                             let parts: Vec<&str> = input.split(',').collect();
                                             if parts.len() == 2
@@ -503,17 +862,39 @@ impl OxideMC {
                                         })
                                         .interact()
                                         .unwrap();
-                                    let parts: Vec<&str> = center.split(',').collect();
-                                    log::info(format!(
-                                        "Run in-game or via RCON: /worldborder center {} {}",
-                                        parts[0].trim(),
-                                        parts[1].trim()
-                                    ))
-                                    .unwrap();
+                                    let parts: Vec<f64> = center
+                                        .split(',')
+                                        .map(|p| p.trim().parse().unwrap())
+                                        .collect();
+                                    self.change_world(&[WorldChange::BorderCenter {
+                                        x: parts[0],
+                                        z: parts[1],
+                                    }]);
+                                }
+                                "size" => {
+                                    let current = read_world_info(&self.dir)
+                                        .ok()
+                                        .and_then(|info| info.border)
+                                        .map_or("59999968".to_string(), |b| b.size.to_string());
+                                    let size: String = input("Border width in blocks:")
+                                        .default_input(&current)
+                                        .validate(|input: &String| {
+                                            let size =
+                                                input.trim().parse::<f64>().map_err(|_| {
+                                                    "Enter a number of blocks".to_string()
+                                                })?;
+                                            WorldChange::BorderSize(size).validate()
+                                        })
+                                        .interact()
+                                        .unwrap();
+                                    self.change_world(&[WorldChange::BorderSize(
+                                        size.trim().parse().unwrap(),
+                                    )]);
                                 }
                                 _ => {}
                             }
                         }
+                        "gamerules" => self.game_rules_editor(),
                         _ => {
                             page = "main";
                         }
@@ -521,9 +902,16 @@ impl OxideMC {
                 }
                 "mods" => {
                     let action = select("Mods & Content")
-                        .item("install", "Install", "Modrinth or CurseForge")
+                        .item("install", "Install", "Modrinth, CurseForge, URL or file")
                         .item("remove", "Remove", "Uninstall content")
-                        .item("update", "Update", "Check for updates (coming soon)")
+                        .item("update", "Update", "Check for and apply updates")
+                        .item(
+                            "toggle",
+                            "Enable / Disable",
+                            "Turn content off without deleting it",
+                        )
+                        .item("bisect", "Find Crashing Mod", "Disable half at a time")
+                        .item("doctor", "Doctor", "Find mods that would crash the server")
                         .item("back", "Back", "")
                         .interact()
                         .unwrap();
@@ -559,24 +947,13 @@ impl OxideMC {
                                 continue;
                             }
 
-                            let install_dir = match content_type {
-                                "plugin" => self.dir.join("plugins"),
-                                "mod" => self.dir.join("mods"),
-                                "datapack" => {
-                                    let level_name = read_property(
-                                        &self.dir,
-                                        "server.properties",
-                                        "level-name",
-                                    )
-                                    .unwrap_or_else(|_| "world".to_string());
-                                    self.dir.join(level_name).join("datapacks")
-                                }
-                                _ => self.dir.join("resourcepacks"),
-                            };
+                            let install_dir = self.content_dir(content_type);
 
                             let source = select("Install from:")
                                 .item("modrinth", "Modrinth", "Search Modrinth library")
-                                .item("curseforge", "CurseForge", "Install by project ID")
+                                .item("curseforge", "CurseForge", "Search CurseForge")
+                                .item("url", "URL", "Direct download link")
+                                .item("file", "Local File", "A jar or zip on this machine")
                                 .item("back", "Back", "")
                                 .interact()
                                 .unwrap();
@@ -589,93 +966,129 @@ impl OxideMC {
                                         "resourcepack" => ProjectType::ResourcePack,
                                         _ => ProjectType::Mod,
                                     };
-                                    let loader: Option<&str> =
-                                        match (content_type, self.platform.as_str()) {
-                                            ("mod", "Fabric") => Some("fabric"),
-                                            ("mod", "Forge") => Some("forge"),
-                                            ("plugin", "Paper") => Some("paper"),
-                                            _ => None,
-                                        };
-
-                                    let query: String = input("Search Modrinth:")
-                                        .required(true)
-                                        .interact()
-                                        .unwrap();
+                                    let loader = modrinth_loader(&self.platform, content_type);
+
+                                    let chosen_ids = self.pick_modrinth(project_type, loader).await;
+                                    if chosen_ids.is_empty() {
+                                        continue;
+                                    }
 
-                                    match search_modrinth(
-                                        &query,
-                                        project_type,
+                                    let sp = spinner();
+                                    sp.start("Resolving dependencies...");
+                                    let plan = match plan_modrinth_install(
+                                        &install_dir,
+                                        &chosen_ids,
                                         loader,
-                                        Some(&self.version),
+                                        &self.version,
                                     )
                                     .await
                                     {
-                                        Ok(results) if !results.is_empty() => {
-                                            let items: Vec<(String, String, String)> = results
-                                                .iter()
-                                                .map(|h| {
-                                                    let hint = if h.description.len() > 45 {
-                                                        format!("{}…", &h.description[..45])
-                                                    } else {
-                                                        h.description.clone()
-                                                    };
-                                                    (h.project_id.clone(), h.title.clone(), hint)
-                                                })
-                                                .collect();
-                                            let chosen_ids = multiselect(
-                                                "Select content to install (space to toggle, enter to confirm):",
-                                            )
-                                            .items(&items)
-                                            .interact()
-                                            .unwrap();
-
-                                            for chosen_id in chosen_ids {
-                                                match install_modrinth(
-                                                    &chosen_id,
-                                                    loader,
-                                                    &self.version,
-                                                    &install_dir,
-                                                )
-                                                .await
-                                                {
-                                                    Ok(name) => log::success(format!(
-                                                        "Installed {}",
-                                                        name
-                                                    ))
-                                                    .unwrap(),
-                                                    Err(e) => log::error(format!(
-                                                        "Failed to install {}: {}",
-                                                        chosen_id, e
-                                                    ))
-                                                    .unwrap(),
-                                                }
-                                            }
+                                        Ok(plan) => {
+                                            sp.stop(format!(
+                                                "{} file(s) to install",
+                                                plan.installs.len()
+                                            ));
+                                            plan
                                         }
-                                        Ok(_) => log::warning("No results found.").unwrap(),
                                         Err(e) => {
-                                            log::error(format!("Search failed: {}", e)).unwrap()
+                                            sp.stop("Dependency resolution failed");
+                                            log::error(format!(
+                                                "Failed to resolve dependencies: {}",
+                                                e
+                                            ))
+                                            .unwrap();
+                                            continue;
+                                        }
+                                    };
+                                    for warning in &plan.warnings {
+                                        log::warning(warning).unwrap();
+                                    }
+                                    if plan.installs.is_empty() {
+                                        continue;
+                                    }
+                                    let summary: Vec<String> = plan
+                                        .installs
+                                        .iter()
+                                        .map(|p| match &p.required_by {
+                                            Some(parent) => format!(
+                                                "{} {} (required by {})",
+                                                p.title, p.version.version_number, parent
+                                            ),
+                                            None => {
+                                                format!("{} {}", p.title, p.version.version_number)
+                                            }
+                                        })
+                                        .collect();
+                                    log::info(format!("Install plan:\n{}", summary.join("\n")))
+                                        .unwrap();
+                                    if !confirm("Install these?").interact().unwrap() {
+                                        continue;
+                                    }
+
+                                    for planned in &plan.installs {
+                                        match install_modrinth_version(
+                                            &self.dir,
+                                            &planned.version,
+                                            &install_dir,
+                                        )
+                                        .await
+                                        {
+                                            Ok(name) => log::success(format!(
+                                                "Installed {}",
+                                                name
+                                            ))
+                                            .unwrap(),
+                                            Err(e) => log::error(format!(
+                                                "Failed to install {}: {}",
+                                                planned.title, e
+                                            ))
+                                            .unwrap(),
                                         }
                                     }
                                 }
                                 "curseforge" => {
                                     let api_key = get_curseforge_key(&self.dir);
-                                    let id_str: String = input("Enter CurseForge project ID:")
+                                    let picked = self.pick_curseforge(content_type, &api_key).await;
+                                    for (mod_id, name, picked_type) in picked {
+                                        match install_curseforge(
+                                            &self.dir,
+                                            mod_id,
+                                            &self.version,
+                                            curseforge_loader(&self.platform, picked_type),
+                                            &self.content_dir(picked_type),
+                                            &api_key,
+                                        )
+                                        .await
+                                        {
+                                            Ok(file) => {
+                                                log::success(format!("Installed {}", file)).unwrap()
+                                            }
+                                            Err(e) => log::error(format!(
+                                                "Failed to install {}: {}",
+                                                name, e
+                                            ))
+                                            .unwrap(),
+                                        }
+                                    }
+                                }
+                                "url" => {
+                                    let url: String = input("Download URL:")
+                                        .required(true)
                                         .validate(|s: &String| {
-                                            if s.parse::<i32>().is_ok() {
+                                            if s.starts_with("http://") || s.starts_with("https://")
+                                            {
                                                 Ok(())
                                             } else {
-                                                Err("Please enter a numeric project ID"
-                                                    .to_string())
+                                                Err("Please enter an http(s) URL".to_string())
                                             }
                                         })
                                         .interact()
                                         .unwrap();
-                                    let mod_id: i32 = id_str.parse().unwrap();
-                                    match install_curseforge(
-                                        mod_id,
-                                        &self.version,
+                                    match install_from_url(
+                                        &self.dir,
+                                        url.trim(),
                                         &install_dir,
-                                        &api_key,
+                                        content_type,
                                     )
                                     .await
                                     {
@@ -687,8 +1100,42 @@ impl OxideMC {
                                         }
                                     }
                                 }
+                                "file" => {
+                                    let path: String = input("Path to file:")
+                                        .required(true)
+                                        .validate(|s: &String| {
+                                            if expand_home(s.trim()).is_file() {
+                                                Ok(())
+                                            } else {
+                                                Err("File not found".to_string())
+                                            }
+                                        })
+                                        .interact()
+                                        .unwrap();
+                                    let path = expand_home(path.trim());
+                                    match install_from_file(
+                                        &self.dir,
+                                        &path,
+                                        &install_dir,
+                                        content_type,
+                                    ) {
+                                        Ok(name) => {
+                                            log::success(format!("Installed {}", name)).unwrap()
+                                        }
+                                        Err(e) => {
+                                            log::error(format!("Install failed: {}", e)).unwrap()
+                                        }
+                                    }
+                                }
                                 _ => {}
                             }
+                            for warning in server_compatibility_warnings(
+                                &self.dir,
+                                &self.platform,
+                                &self.version,
+                            ) {
+                                log::warning(warning).unwrap();
+                            }
                         }
                         "remove" => {
                             let dir_options: &[(&str, &str, &str)] =
@@ -738,7 +1185,24 @@ impl OxideMC {
                             } else {
                                 let items: Vec<(String, String, String)> = entries
                                     .iter()
-                                    .map(|f| (f.clone(), f.clone(), String::new()))
+                                    .map(|f| {
+                                        let metadata = f
+                                            .ends_with(".jar")
+                                            .then(|| {
+                                                read_jar_metadata(&remove_dir.join(f))
+                                                    .ok()
+                                                    .flatten()
+                                            })
+                                            .flatten();
+                                        match metadata {
+                                            Some(m) => (
+                                                f.clone(),
+                                                format!("{} {}", m.name, m.version),
+                                                f.clone(),
+                                            ),
+                                            None => (f.clone(), f.clone(), String::new()),
+                                        }
+                                    })
                                     .collect();
                                 let chosen = select("Select to remove:")
                                     .items(&items)
@@ -754,6 +1218,7 @@ impl OxideMC {
                                     };
                                     match result {
                                         Ok(()) => {
+                                            let _ = forget_install(&self.dir, &remove_dir, &chosen);
                                             log::success(format!("Removed {}", chosen)).unwrap()
                                         }
                                         Err(e) => {
@@ -763,56 +1228,244 @@ impl OxideMC {
                                 }
                             }
                         }
-                        "update" => {
-                            log::warning("Update checking is not yet implemented.").unwrap();
-                        }
-                        _ => {
-                            page = "main";
-                        }
-                    }
-                }
-                "advanced" => {
-                    let setting = select("Advanced Settings")
-                        .item("port", "Server Port", "Default: 25565")
-                        .item("motd", "MOTD", "Server list message")
-                        .item("online-mode", "Online Mode", "Require Mojang auth")
-                        .item("view-distance", "View Distance", "Chunks sent to clients")
-                        .item(
-                            "simulation-distance",
-                            "Simulation Distance",
-                            "Entity update range",
-                        )
-                        .item("spawn-protection", "Spawn Protection", "Protected spawn radius")
-                        .item("back", "Back", "")
-                        .interact()
-                        .unwrap();
-                    match setting {
-                        "port" => {
-                            let port: String = input("Server port:")
-                                .default_input("25565")
-                                .validate(|input: &String| {
-                                    if input.parse::<u16>().is_ok() {
-                                        Ok(())
-                                    } else {
-                                        Err("Please enter a valid port (1-65535)".to_string())
-                                    }
-                                })
+                        "doctor" => self.doctor().await,
+                        "toggle" => {
+                            let Some(content_dir) = self.toggleable_dir() else {
+                                log::warning("Vanilla servers have no mods or plugins.").unwrap();
+                                continue;
+                            };
+                            let states = match list_content(&content_dir) {
+                                Ok(states) if !states.is_empty() => states,
+                                _ => {
+                                    log::warning("Nothing installed yet.").unwrap();
+                                    continue;
+                                }
+                            };
+                            let items: Vec<(String, String, String)> = states
+                                .iter()
+                                .map(|c| (c.name.clone(), c.name.clone(), String::new()))
+                                .collect();
+                            let enabled: Vec<String> = states
+                                .iter()
+                                .filter(|c| c.enabled)
+                                .map(|c| c.name.clone())
+                                .collect();
+                            let chosen = multiselect("Enabled content (space to toggle):")
+                                .items(&items)
+                                .initial_values(enabled)
+                                .required(false)
                                 .interact()
                                 .unwrap();
-                            configure_file(
-                                &self.dir,
-                                "server.properties",
-                                "server-port",
-                                &port,
-                            )
-                            .unwrap();
+                            for state in &states {
+                                let want = chosen.contains(&state.name);
+                                if want == state.enabled {
+                                    continue;
+                                }
+                                let result = if want {
+                                    enable_content(&self.dir, &content_dir, &state.file_name())
+                                } else {
+                                    disable_content(&self.dir, &content_dir, &state.file_name())
+                                };
+                                match result {
+                                    Ok(_) if want => {
+                                        log::success(format!("Enabled {}", state.name)).unwrap()
+                                    }
+                                    Ok(_) => log::info(format!("Disabled {}", state.name)).unwrap(),
+                                    Err(e) => log::error(format!(
+                                        "Failed to toggle {}: {}",
+                                        state.name, e
+                                    ))
+                                    .unwrap(),
+                                }
+                            }
+                        }
+                        "bisect" => {
+                            let outcome = if read_bisect(&self.dir).is_some() {
+                                let answer = select("How did the server do?")
+                                    .item("crashed", "Crashed", "The problem is still there")
+                                    .item("works", "Started fine", "The problem went away")
+                                    .item("abort", "Stop bisecting", "Re-enable everything")
+                                    .interact()
+                                    .unwrap();
+                                match answer {
+                                    "abort" => {
+                                        match abort_bisect(&self.dir) {
+                                            Ok(()) => log::success(
+                                                "Bisect stopped; everything re-enabled",
+                                            )
+                                            .unwrap(),
+                                            Err(e) => {
+                                                log::error(format!("Failed to stop bisect: {}", e))
+                                                    .unwrap()
+                                            }
+                                        }
+                                        continue;
+                                    }
+                                    answer => bisect_step(&self.dir, answer == "crashed"),
+                                }
+                            } else {
+                                let Some(content_dir) = self.toggleable_dir() else {
+                                    log::warning("Vanilla servers have no mods or plugins.")
+                                        .unwrap();
+                                    continue;
+                                };
+                                let subdir = content_dir
+                                    .file_name()
+                                    .unwrap_or_default()
+                                    .to_string_lossy()
+                                    .to_string();
+                                start_bisect(&self.dir, &subdir)
+                            };
+                            match outcome {
+                                Ok(BisectOutcome::Continue(state)) if state.suspects.len() == 1 => {
+                                    log::info(format!(
+                                        "Only {} is left, so it has been disabled to confirm. Start the server, then come back here and report whether it crashed.",
+                                        state.suspects[0]
+                                    ))
+                                    .unwrap();
+                                }
+                                Ok(BisectOutcome::Continue(state)) => {
+                                    log::info(format!(
+                                        "Round {}: disabled {} of {} suspect(s). Start the server, then come back here and report whether it crashed.",
+                                        state.round,
+                                        state.disabled.len(),
+                                        state.suspects.len()
+                                    ))
+                                    .unwrap();
+                                }
+                                Ok(BisectOutcome::Found(culprit)) => {
+                                    log::success(format!(
+                                        "Found it: {} (everything has been re-enabled)",
+                                        culprit
+                                    ))
+                                    .unwrap();
+                                }
+                                Err(e) => log::error(format!("Bisect failed: {}", e)).unwrap(),
+                            }
+                        }
+                        "update" => {
+                            let (content_type, content_dir) = match self.platform.as_str() {
+                                "Paper" => ("plugin", self.dir.join("plugins")),
+                                "Fabric" | "Forge" => ("mod", self.dir.join("mods")),
+                                _ => {
+                                    log::warning("Vanilla servers have no mods or plugins to update.")
+                                        .unwrap();
+                                    continue;
+                                }
+                            };
+                            let loader = modrinth_loader(&self.platform, content_type);
+                            let cf_key = read_oxide_config(&self.dir, "curseforge_api_key").ok();
+
+                            let sp = spinner();
+                            sp.start("Checking for updates...");
+                            let updates = match check_updates(
+                                &content_dir,
+                                loader,
+                                &self.version,
+                                cf_key.as_deref(),
+                            )
+                            .await
+                            {
+                                Ok(updates) => {
+                                    sp.stop(format!("{} update(s) available", updates.len()));
+                                    updates
+                                }
+                                Err(e) => {
+                                    sp.stop("Update check failed");
+                                    log::error(format!("Failed to check updates: {}", e)).unwrap();
+                                    continue;
+                                }
+                            };
+                            if updates.is_empty() {
+                                log::success("Everything is up to date.").unwrap();
+                                continue;
+                            }
+
+                            let items: Vec<(usize, String, String)> = updates
+                                .iter()
+                                .enumerate()
+                                .map(|(i, u)| {
+                                    (
+                                        i,
+                                        u.file_name.clone(),
+                                        format!("{} → {}", u.current_version, u.new_version),
+                                    )
+                                })
+                                .collect();
+                            let mode = select("Apply updates")
+                                .item("all", "Update All", "")
+                                .item("pick", "Choose Updates", "Select one or more")
+                                .item("back", "Back", "")
+                                .interact()
+                                .unwrap();
+                            let chosen: Vec<usize> = match mode {
+                                "all" => (0..updates.len()).collect(),
+                                "pick" => multiselect("Select updates to apply:")
+                                    .items(&items)
+                                    .interact()
+                                    .unwrap(),
+                                _ => continue,
+                            };
+                            for i in chosen {
+                                let update = &updates[i];
+                                match apply_update(&self.dir, update).await {
+                                    Ok(name) => log::success(format!(
+                                        "Updated {} to {}",
+                                        update.file_name, name
+                                    ))
+                                    .unwrap(),
+                                    Err(e) => log::error(format!(
+                                        "Failed to update {}: {}",
+                                        update.file_name, e
+                                    ))
+                                    .unwrap(),
+                                }
+                            }
+                        }
+                        _ => {
+                            page = "main";
+                        }
+                    }
+                }
+                "advanced" => {
+                    let setting = select("Advanced Settings")
+                        .item("port", "Server Port", "Default: 25565")
+                        .item("motd", "MOTD", "Server list message")
+                        .item("online-mode", "Online Mode", "Require Mojang auth")
+                        .item("view-distance", "View Distance", "Chunks sent to clients")
+                        .item(
+                            "simulation-distance",
+                            "Simulation Distance",
+                            "Entity update range",
+                        )
+                        .item(
+                            "spawn-protection",
+                            "Spawn Protection",
+                            "Protected spawn radius",
+                        )
+                        .item(
+                            "yaml",
+                            "Bukkit / Paper Config",
+                            "Edit YAML settings by path",
+                        )
+                        .item("back", "Back", "")
+                        .interact()
+                        .unwrap();
+                    match setting {
+                        "port" => {
+                            let port: String = input("Server port:")
+                                .default_input("25565")
+                                .validate(|input: &String| check_property("server-port", input))
+                                .interact()
+                                .unwrap();
+                            self.set_property("server-port", &port);
                         }
                         "motd" => {
                             let motd: String = input("MOTD (supports \u{00a7}color codes):")
                                 .default_input("A Minecraft Server")
                                 .interact()
                                 .unwrap();
-                            configure_file(&self.dir, "server.properties", "motd", &motd).unwrap();
+                            self.set_property("motd", &motd);
                         }
                         "online-mode" => {
                             let online = select("Require valid Mojang accounts?")
@@ -820,78 +1473,51 @@ impl OxideMC {
                                 .item("false", "No (Cracked)", "Allows non-premium accounts")
                                 .interact()
                                 .unwrap();
-                            configure_file(
-                                &self.dir,
-                                "server.properties",
-                                "online-mode",
-                                online,
-                            )
-                            .unwrap();
+                            self.set_property("online-mode", online);
                         }
                         "view-distance" => {
                             let dist: String = input("View distance (3-32 chunks):")
                                 .default_input("10")
-                                .validate(|input: &String| match input.parse::<u32>() {
-                                    Ok(n) if (3..=32).contains(&n) => Ok(()),
-                                    _ => {
-                                        Err("Please enter a number between 3 and 32".to_string())
-                                    }
-                                })
+                                .validate(|input: &String| check_property("view-distance", input))
                                 .interact()
                                 .unwrap();
-                            configure_file(
-                                &self.dir,
-                                "server.properties",
-                                "view-distance",
-                                &dist,
-                            )
-                            .unwrap();
+                            self.set_property("view-distance", &dist);
                         }
                         "simulation-distance" => {
                             let dist: String = input("Simulation distance (3-32 chunks):")
                                 .default_input("10")
-                                .validate(|input: &String| match input.parse::<u32>() {
-                                    Ok(n) if (3..=32).contains(&n) => Ok(()),
-                                    _ => {
-                                        Err("Please enter a number between 3 and 32".to_string())
-                                    }
+                                .validate(|input: &String| {
+                                    check_property("simulation-distance", input)
                                 })
                                 .interact()
                                 .unwrap();
-                            configure_file(
-                                &self.dir,
-                                "server.properties",
-                                "simulation-distance",
-                                &dist,
-                            )
-                            .unwrap();
+                            self.set_property("simulation-distance", &dist);
                         }
                         "spawn-protection" => {
                             let radius: String =
                                 input("Spawn protection radius in blocks (0 to disable):")
                                     .default_input("16")
                                     .validate(|input: &String| {
-                                        if input.parse::<u32>().is_ok() {
-                                            Ok(())
-                                        } else {
-                                            Err("Please enter a non-negative integer".to_string())
-                                        }
+                                        check_property("spawn-protection", input)
                                     })
                                     .interact()
                                     .unwrap();
-                            configure_file(
-                                &self.dir,
-                                "server.properties",
-                                "spawn-protection",
-                                &radius,
-                            )
-                            .unwrap();
+                            self.set_property("spawn-protection", &radius);
                         }
+                        "yaml" => self.yaml_editor(),
                         _ => {
                             page = "main";
                         }
                     }
                 }
+                "players" => {
+                    self.players_menu().await;
+                    page = "main";
+                }
+                "properties" => {
+                    self.properties_editor();
+                    page = "main";
+                }
                 "quit" => {
                     break;
                 }
@@ -902,7 +1528,1133 @@ impl OxideMC {
         }
     }
 
+    /// Validate a `server.properties` edit against this server's version and write it.
+    /// Bad values are refused; unknown or out-of-version keys are written with a warning.
+    pub fn set_property(&self, key: &str, value: &str) -> bool {
+        self.set_properties(&[(key, value)])
+    }
+
+    pub fn set_properties(&self, edits: &[(&str, &str)]) -> bool {
+        let warnings = match validate_properties(edits, &self.version) {
+            Ok(warnings) => warnings,
+            Err(e) => {
+                log::error(e).unwrap();
+                return false;
+            }
+        };
+        for warning in &warnings {
+            log::warning(warning).unwrap();
+        }
+        match configure_properties(&self.dir, "server.properties", edits) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error(format!("Failed to write server.properties: {}", e)).unwrap();
+                false
+            }
+        }
+    }
+
+    /// List world folders and switch, create, reset or import them.
+    async fn worlds_menu(&self) {
+        let worlds = match list_worlds(&self.dir) {
+            Ok(worlds) => worlds,
+            Err(e) => {
+                log::error(format!("Failed to list worlds: {}", e)).unwrap();
+                return;
+            }
+        };
+        let lines: Vec<String> = worlds
+            .iter()
+            .map(|w| {
+                let marker = if w.current { "●" } else { " " };
+                let details = match &w.info {
+                    Some(info) => format!(
+                        "Minecraft {}, seed {}, {}",
+                        info.game_version.as_deref().unwrap_or("?"),
+                        info.seed.map_or("?".to_string(), |s| s.to_string()),
+                        format_size(w.size)
+                    ),
+                    None => "not generated yet".to_string(),
+                };
+                format!("{} {}  {}", marker, w.name, details)
+            })
+            .collect();
+        log::info(format!("Worlds (● = current):\n{}", lines.join("\n"))).unwrap();
+
+        let action = select("Worlds")
+            .item("switch", "Switch World", "Change level-name")
+            .item("create", "New World", "Fresh world with a seed and type")
+            .item(
+                "reset",
+                "Reset Current World",
+                "Back it up, then regenerate",
+            )
+            .item("import", "Import World", "From a .zip containing level.dat")
+            .item("back", "Back", "")
+            .interact()
+            .unwrap();
+        let result: Result<String, Box<dyn std::error::Error>> = match action {
+            "switch" => {
+                let others: Vec<&WorldFolder> = worlds
+                    .iter()
+                    .filter(|w| !w.current && w.info.is_some())
+                    .collect();
+                if others.is_empty() {
+                    log::info("There are no other worlds to switch to").unwrap();
+                    return;
+                }
+                let mut pick = select("Switch to:");
+                for w in &others {
+                    pick = pick.item(w.name.clone(), &w.name, format_size(w.size));
+                }
+                let name: String = pick.interact().unwrap();
+                switch_world(&self.dir, &name)
+                    .map(|_| format!("Switched to '{}'; it loads on the next start", name))
+            }
+            "create" => {
+                let name: String = input("World name:")
+                    .validate(|n: &String| valid_world_name(n))
+                    .interact()
+                    .unwrap();
+                let seed: String = input("Seed (leave blank for random):")
+                    .default_input("")
+                    .required(false)
+                    .interact()
+                    .unwrap();
+                let mut level_type = select("World type:")
+                    .item("minecraft:normal".to_string(), "Normal", "")
+                    .item("minecraft:flat".to_string(), "Flat", "")
+                    .item("minecraft:large_biomes".to_string(), "Large Biomes", "")
+                    .item("minecraft:amplified".to_string(), "Amplified", "")
+                    .item(String::new(), "Other…", "A datapack or mod world preset")
+                    .interact()
+                    .unwrap();
+                if level_type.is_empty() {
+                    level_type = input("World preset id:")
+                        .placeholder("namespace:id")
+                        .validate(|v: &String| check_property("level-type", v))
+                        .interact()
+                        .unwrap();
+                }
+                create_world(&self.dir, &name, &seed, &level_type)
+                    .map(|_| format!("'{}' will be generated on the next start", name))
+            }
+            "reset" => {
+                let current = current_world(&self.dir);
+                let confirmed = confirm(format!(
+                    "Back up and delete '{}'? The server generates a new world on next start.",
+                    current
+                ))
+                .initial_value(false)
+                .interact()
+                .unwrap();
+                if !confirmed {
+                    return;
+                }
+                let keep_seed = confirm("Keep the same seed?")
+                    .initial_value(true)
+                    .interact()
+                    .unwrap();
+                reset_world(&self.dir, keep_seed)
+                    .await
+                    .map(|archive| format!("'{}' reset; the old world is in {}", current, archive))
+            }
+            "import" => {
+                let path: String = input("Path to the world .zip:")
+                    .required(true)
+                    .interact()
+                    .unwrap();
+                let path = expand_home(path.trim());
+                match zip_world_root(&path) {
+                    Ok(root) => {
+                        let name: String = input("Name for the imported world:")
+                            .default_input(&import_name(&path, &root))
+                            .validate(|n: &String| valid_world_name(n))
+                            .interact()
+                            .unwrap();
+                        import_world_zip(&self.dir, &path, &name)
+                            .map(|_| format!("Imported '{}'; use Switch World to play it", name))
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            _ => return,
+        };
+        match result {
+            Ok(message) => log::success(message).unwrap(),
+            Err(e) => log::error(format!("{}", e)).unwrap(),
+        }
+    }
+
+    /// Block x,z to measure a radius from, defaulting to the world spawn.
+    fn prompt_center(&self) -> (f64, f64) {
+        let spawn = read_world_info(&self.dir)
+            .ok()
+            .and_then(|info| info.spawn)
+            .map_or("0,0".to_string(), |[x, _, z]| format!("{},{}", x, z));
+        let center: String = input("Center (x,z):")
+            .default_input(&spawn)
+            .validate(|input: &String| {
+                let parts: Vec<&str> = input.split(',').collect();
+                if parts.len() == 2 && parts.iter().all(|p| p.trim().parse::<f64>().is_ok()) {
+                    Ok(())
+                } else {
+                    Err("Enter coordinates as x,z (e.g. 0,0)")
+                }
+            })
+            .interact()
+            .unwrap();
+        let parts: Vec<f64> = center
+            .split(',')
+            .map(|p| p.trim().parse().unwrap())
+            .collect();
+        (parts[0], parts[1])
+    }
+
+    /// Ask for the area to pre-generate, then run Chunky on it.
+    async fn pregen_menu(&self) {
+        let center = self.prompt_center();
+        let radius: String = input("Radius in blocks (the world border is set to match):")
+            .default_input("5000")
+            .validate(|input: &String| match input.trim().parse::<f64>() {
+                Ok(r) if r >= 1.0 => Ok(()),
+                _ => Err("Enter a radius of at least 1"),
+            })
+            .interact()
+            .unwrap();
+        let _ = self.pregen(center, radius.trim().parse().unwrap()).await;
+    }
+
+    /// Pick what to prune, show a dry run, then prune after a confirmation and backup.
+    async fn prune_menu(&self) {
+        if server_running(&self.dir) {
+            log::error("Stop the server before pruning chunks").unwrap();
+            return;
+        }
+        let criteria: Vec<&str> = multiselect("Prune chunks that are:")
+            .item("unvisited", "Barely visited", "Low InhabitedTime")
+            .item("outside", "Far out", "Outside a radius")
+            .required(true)
+            .interact()
+            .unwrap();
+        let mut rule = PruneRule::default();
+        if criteria.contains(&"unvisited") {
+            let minutes: String = input("Keep chunks players spent at least this many minutes in:")
+                .default_input("1")
+                .validate(|input: &String| match input.trim().parse::<f64>() {
+                    Ok(m) if m > 0.0 => Ok(()),
+                    _ => Err("Enter a number of minutes above 0"),
+                })
+                .interact()
+                .unwrap();
+            // 20 ticks a second
+            let minutes: f64 = minutes.trim().parse().unwrap();
+            rule.min_inhabited_ticks = Some((minutes * 1200.0).ceil() as i64);
+        }
+        if criteria.contains(&"outside") {
+            rule.center = self.prompt_center();
+            let radius: String = input("Keep chunks within this many blocks of it:")
+                .validate(|input: &String| match input.trim().parse::<f64>() {
+                    Ok(r) if r > 0.0 => Ok(()),
+                    _ => Err("Enter a radius above 0"),
+                })
+                .interact()
+                .unwrap();
+            rule.radius = Some(radius.trim().parse().unwrap());
+        }
+
+        let sp = spinner();
+        sp.start("Scanning region files...");
+        let reports = match prune_regions(&self.dir, &rule, true) {
+            Ok(reports) => reports,
+            Err(e) => {
+                sp.stop("Scan failed");
+                log::error(format!("{}", e)).unwrap();
+                return;
+            }
+        };
+        sp.stop("Scan complete");
+        log::info(prune_summary(&reports, true)).unwrap();
+        if reports.iter().all(|r| r.removed == 0) {
+            return;
+        }
+        let confirmed = confirm("Delete these chunks? They regenerate from the seed if visited.")
+            .initial_value(false)
+            .interact()
+            .unwrap();
+        if !confirmed {
+            return;
+        }
+        let backup = confirm("Back up the world first?")
+            .initial_value(true)
+            .interact()
+            .unwrap();
+        if backup {
+            if let Err(e) = backup_world(&self.dir).await {
+                log::error(format!("Backup failed, so nothing was pruned: {}", e)).unwrap();
+                return;
+            }
+        }
+        match prune_regions(&self.dir, &rule, false) {
+            Ok(reports) => log::success(prune_summary(&reports, false)).unwrap(),
+            Err(e) => log::error(format!("Pruning failed: {}", e)).unwrap(),
+        }
+    }
+
+    /// Apply border or game rule changes (over RCON if the server is up) and report back.
+    fn change_world(&self, changes: &[WorldChange]) {
+        match apply_world_changes(&self.dir, changes) {
+            Ok(lines) => {
+                for line in lines.iter().filter(|l| !l.is_empty()) {
+                    log::success(line).unwrap();
+                }
+            }
+            Err(e) => log::error(format!("Failed to change the world: {}", e)).unwrap(),
+        }
+    }
+
+    /// Pick a game rule from level.dat and set it. With no world yet but the server up,
+    /// any rule can be typed in and is sent over RCON.
+    fn game_rules_editor(&self) {
+        let rules = match read_world_info(&self.dir) {
+            Ok(info) if !info.game_rules.is_empty() => info.game_rules,
+            _ if server_running(&self.dir) => {
+                let rule: String = input("Game rule:").required(true).interact().unwrap();
+                let value: String = input("Value:").required(true).interact().unwrap();
+                self.change_world(&[WorldChange::GameRule { rule, value }]);
+                return;
+            }
+            Ok(_) => {
+                log::warning("level.dat has no game rules").unwrap();
+                return;
+            }
+            Err(e) => {
+                log::warning(format!("Can't read the world: {}", e)).unwrap();
+                return;
+            }
+        };
+        let mut pick = select("Which game rule?");
+        for (i, (rule, value)) in rules.iter().enumerate() {
+            pick = pick.item(Some(i), rule, value);
+        }
+        let Some(i) = pick.item(None, "Back", "").interact().unwrap() else {
+            return;
+        };
+        let (rule, current) = &rules[i];
+        let value = if current == "true" || current == "false" {
+            select(format!("{}:", rule))
+                .item("true".to_string(), "true", "")
+                .item("false".to_string(), "false", "")
+                .initial_value(current.clone())
+                .interact()
+                .unwrap()
+        } else {
+            input(format!("{}:", rule))
+                .default_input(current)
+                .validate(|v: &String| {
+                    v.trim()
+                        .parse::<i32>()
+                        .map(|_| ())
+                        .map_err(|_| "Enter a whole number")
+                })
+                .interact()
+                .unwrap()
+        };
+        self.change_world(&[WorldChange::GameRule {
+            rule: rule.clone(),
+            value: value.trim().to_string(),
+        }]);
+    }
+
+    /// Ops, whitelist and ban lists. Changes go over RCON while the server runs.
+    async fn players_menu(&self) {
+        if server_running(&self.dir) {
+            log::info("The server is running; changes will be sent over RCON").unwrap();
+        }
+        loop {
+            let choice = select("Players")
+                .item("known", "Known Players", "Position, health and inventory")
+                .item("ops", "Operators", "ops.json")
+                .item("whitelist", "Whitelist", "whitelist.json")
+                .item("bans", "Banned Players", "banned-players.json")
+                .item("ip-bans", "Banned IPs", "banned-ips.json")
+                .item("back", "Back", "")
+                .interact()
+                .unwrap();
+            let list = match choice {
+                "known" => {
+                    self.known_players();
+                    continue;
+                }
+                "ops" => PlayerList::Ops,
+                "whitelist" => PlayerList::Whitelist,
+                "bans" => PlayerList::Bans,
+                "ip-bans" => PlayerList::IpBans,
+                _ => return,
+            };
+            let rows = match player_list_rows(&self.dir, list) {
+                Ok(rows) => rows,
+                Err(e) => {
+                    log::error(format!("Failed to read {}: {}", list.file_name(), e)).unwrap();
+                    continue;
+                }
+            };
+            if rows.is_empty() {
+                log::info(format!("{}: none", list.label())).unwrap();
+            } else {
+                let lines: Vec<String> = rows
+                    .iter()
+                    .map(|(name, details)| format!("{}  {}", name, details))
+                    .collect();
+                log::info(format!("{}:\n{}", list.label(), lines.join("\n"))).unwrap();
+            }
+
+            let mut action = select(list.label()).item("add", "Add", "");
+            if !rows.is_empty() {
+                action = action.item("remove", "Remove", "");
+            }
+            let action = action.item("back", "Back", "").interact().unwrap();
+            let change = match action {
+                "add" => self.prompt_player_addition(list),
+                "remove" => {
+                    let mut remove = select("Remove which entry?");
+                    for (name, details) in &rows {
+                        remove = remove.item(name.clone(), name, details);
+                    }
+                    let name: String = remove.interact().unwrap();
+                    Some(match list {
+                        PlayerList::Ops => PlayerChange::Deop(name),
+                        PlayerList::Whitelist => PlayerChange::WhitelistRemove(name),
+                        PlayerList::Bans => PlayerChange::Pardon(name),
+                        PlayerList::IpBans => PlayerChange::PardonIp(name),
+                    })
+                }
+                _ => None,
+            };
+            let Some(change) = change else {
+                continue;
+            };
+            let sp = spinner();
+            sp.start("Applying...");
+            match apply_player_change(&self.dir, &change).await {
+                Ok(message) => sp.stop(message),
+                Err(e) => sp.error(format!("{}", e)),
+            }
+        }
+    }
+
+    /// Pick a player from the usercache and player data and show what's saved about them.
+    fn known_players(&self) {
+        let players = match list_players(&self.dir) {
+            Ok(players) => players,
+            Err(e) => {
+                log::error(format!("Failed to read player data: {}", e)).unwrap();
+                return;
+            }
+        };
+        if players.is_empty() {
+            log::info("No players have joined yet").unwrap();
+            return;
+        }
+        let mut pick = select("Which player?");
+        for (i, player) in players.iter().enumerate() {
+            let name = player.name.as_deref().unwrap_or(&player.uuid);
+            pick = pick.item(Some(i), name, &player.uuid);
+        }
+        if let Some(i) = pick.item(None, "Back", "").interact().unwrap() {
+            log::info(player_details(&players[i])).unwrap();
+        }
+    }
+
+    fn prompt_player_addition(&self, list: PlayerList) -> Option<PlayerChange> {
+        let validate_name = |name: &String| {
+            if valid_player_name(name) {
+                Ok(())
+            } else {
+                Err("3-16 letters, digits or underscores")
+            }
+        };
+        let reason = || {
+            let reason: String = input("Reason (optional):")
+                .required(false)
+                .interact()
+                .unwrap();
+            Some(reason).filter(|r| !r.trim().is_empty())
+        };
+        Some(match list {
+            PlayerList::IpBans => {
+                let ip: String = input("IP address to ban:")
+                    .validate(|ip: &String| {
+                        ip.parse::<std::net::IpAddr>()
+                            .map(|_| ())
+                            .map_err(|_| "Not an IP address")
+                    })
+                    .interact()
+                    .unwrap();
+                PlayerChange::BanIp {
+                    ip,
+                    reason: reason(),
+                }
+            }
+            _ => {
+                let name: String = input("Player name:")
+                    .validate(validate_name)
+                    .interact()
+                    .unwrap();
+                match list {
+                    PlayerList::Ops => {
+                        let level = select("Permission level:")
+                            .item(4u8, "4 - Owner", "All commands, including /stop")
+                            .item(3u8, "3 - Admin", "Player management: /ban, /op")
+                            .item(2u8, "2 - Game master", "Command blocks, /gamemode, /tp")
+                            .item(1u8, "1 - Moderator", "Bypass spawn protection")
+                            .interact()
+                            .unwrap();
+                        let bypasses_player_limit =
+                            confirm("Let them join when the server is full?")
+                                .initial_value(false)
+                                .interact()
+                                .unwrap();
+                        PlayerChange::Op {
+                            name,
+                            level,
+                            bypasses_player_limit,
+                        }
+                    }
+                    PlayerList::Whitelist => PlayerChange::WhitelistAdd(name),
+                    _ => PlayerChange::Ban {
+                        name,
+                        reason: reason(),
+                    },
+                }
+            }
+        })
+    }
+
+    /// Every key in `server.properties` with its value, default and description, filtered
+    /// by fuzzy search. Values that differ from the default are marked and can be reset.
+    fn properties_editor(&self) {
+        let path = self.dir.join("server.properties");
+        let mut query = String::new();
+        loop {
+            let props = match Properties::load(&path) {
+                Ok(props) => props,
+                Err(e) => {
+                    log::error(format!("Failed to read server.properties: {}", e)).unwrap();
+                    return;
+                }
+            };
+            let rows = property_rows(&props);
+            if rows.is_empty() {
+                log::warning("server.properties is empty; start the server once to create it")
+                    .unwrap();
+                return;
+            }
+            let matches = search_property_rows(&rows, &query);
+            let changed = rows.iter().filter(|r| r.is_changed()).count();
+
+            let title = if query.is_empty() {
+                format!("server.properties — {} changed from default", changed)
+            } else {
+                format!(
+                    "Matching \"{}\" — {} of {}",
+                    query,
+                    matches.len(),
+                    rows.len()
+                )
+            };
+            let mut menu = select(title);
+            for row in &matches {
+                let mark = if row.is_changed() { "● " } else { "  " };
+                let hint = match row.spec {
+                    Some(spec) => format!("default: {} · {}", spec.default, spec.description),
+                    None => "not a vanilla key".to_string(),
+                };
+                menu = menu.item(
+                    row.key.clone(),
+                    format!("{}{} = {}", mark, row.key, truncate_chars(&row.value, 40)),
+                    hint,
+                );
+            }
+            menu = menu.item(
+                "__search".to_string(),
+                "Search…",
+                "Fuzzy match keys and descriptions",
+            );
+            if !query.is_empty() {
+                menu = menu.item("__clear".to_string(), "Show all", "");
+            }
+            menu = menu.item("__back".to_string(), "Back", "");
+
+            let choice = menu.interact().unwrap();
+            match choice.as_str() {
+                "__search" => {
+                    query = input("Search properties:")
+                        .required(false)
+                        .default_input(&query)
+                        .interact()
+                        .unwrap();
+                }
+                "__clear" => query.clear(),
+                "__back" => return,
+                key => {
+                    let Some(row) = rows.iter().find(|r| r.key == key) else {
+                        continue;
+                    };
+                    self.edit_property(row);
+                }
+            }
+        }
+    }
+
+    fn edit_property(&self, row: &PropertyRow) {
+        if let Some(spec) = row.spec {
+            let mut about = format!(
+                "{}\n{}\nCurrent: {}\nDefault: {}",
+                row.key, spec.description, row.value, spec.default
+            );
+            if let Some(added) = spec.added {
+                about.push_str(&format!("\nAdded in {}", added));
+            }
+            if let Some(removed) = spec.removed {
+                about.push_str(&format!("\nRemoved in {}", removed));
+            }
+            log::info(about).unwrap();
+        }
+        let mut actions = select(format!("{} = {}", row.key, row.value)).item("edit", "Edit", "");
+        if row.is_changed() {
+            let default = row.default().unwrap_or_default();
+            actions = actions.item(
+                "reset",
+                "Reset to default",
+                format!("Set to \"{}\"", default),
+            );
+        }
+        let action = actions.item("back", "Back", "").interact().unwrap();
+
+        match action {
+            "edit" => {
+                let value = match row.spec.map(|s| s.kind) {
+                    Some(PropertyType::Bool) => select(format!("{}:", row.key))
+                        .item("true".to_string(), "true", "")
+                        .item("false".to_string(), "false", "")
+                        .initial_value(row.value.clone())
+                        .interact()
+                        .unwrap(),
+                    Some(PropertyType::Enum(values)) => {
+                        let mut choices = select(format!("{}:", row.key));
+                        for v in values {
+                            choices = choices.item(v.to_string(), *v, "");
+                        }
+                        choices.initial_value(row.value.clone()).interact().unwrap()
+                    }
+                    Some(PropertyType::ResourceLocation(values)) => {
+                        let custom = !values.contains(&row.value.as_str());
+                        let mut choices = select(format!("{}:", row.key));
+                        for v in values {
+                            choices = choices.item(v.to_string(), *v, "");
+                        }
+                        let choice = choices
+                            .item(String::new(), "Other…", "From a datapack or mod")
+                            .initial_value(if custom {
+                                String::new()
+                            } else {
+                                row.value.clone()
+                            })
+                            .interact()
+                            .unwrap();
+                        if choice.is_empty() {
+                            let key = row.key.clone();
+                            input(format!("{}:", row.key))
+                                .placeholder("namespace:id")
+                                .default_input(if custom { &row.value } else { "" })
+                                .validate(move |v: &String| check_property(&key, v))
+                                .interact()
+                                .unwrap()
+                        } else {
+                            choice
+                        }
+                    }
+                    _ => {
+                        let key = row.key.clone();
+                        input(format!("{}:", row.key))
+                            .default_input(&row.value)
+                            .required(false)
+                            .validate(move |v: &String| check_property(&key, v))
+                            .interact()
+                            .unwrap()
+                    }
+                };
+                if value != row.value && self.set_property(&row.key, &value) {
+                    log::success(format!("{} = {}", row.key, value)).unwrap();
+                }
+            }
+            "reset" => {
+                let default = row.default().unwrap_or_default();
+                if self.set_property(&row.key, default) {
+                    log::success(format!("{} reset to \"{}\"", row.key, default)).unwrap();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Pick a built-in tuning profile, review its changes and apply them.
+    fn tuning_profile(&self) {
+        let profiles = tuning_profiles();
+        let mut menu = select("Tuning profile");
+        for (i, p) in profiles.iter().enumerate() {
+            let hint = if p.info.compatible_platforms.is_empty() {
+                p.info.description.clone()
+            } else {
+                format!(
+                    "{} ({})",
+                    p.info.description,
+                    p.info.compatible_platforms.join(", ")
+                )
+            };
+            menu = menu.item(i, &p.info.name, hint);
+        }
+        let profile = &profiles[menu.interact().unwrap()];
+
+        let mut files: Vec<&String> = profile.settings.keys().collect();
+        files.sort();
+        let mut changes = Vec::new();
+        for file in files {
+            let mut keys: Vec<(&String, &String)> = profile.settings[file].iter().collect();
+            keys.sort();
+            for (key, value) in keys {
+                changes.push(format!("{}: {} = {}", file, key, value));
+            }
+        }
+        log::info(format!("{}\n{}", profile.info.name, changes.join("\n"))).unwrap();
+        if !confirm("Apply these settings?").interact().unwrap() {
+            return;
+        }
+        for warning in preset_property_warnings(profile, &self.version) {
+            log::warning(warning).unwrap();
+        }
+        match apply_tuning_profile(&self.dir, profile, &self.platform, &self.version) {
+            Ok(()) => log::success(format!(
+                "Applied the {} profile; restart the server to use it",
+                profile.info.name
+            ))
+            .unwrap(),
+            Err(e) => log::error(format!("Failed to apply profile: {}", e)).unwrap(),
+        }
+    }
+
+    /// Read and set Bukkit, Spigot and Paper YAML settings by dotted path.
+    fn yaml_editor(&self) {
+        let known = [
+            "bukkit.yml",
+            "spigot.yml",
+            "config/paper-global.yml",
+            "config/paper-world-defaults.yml",
+        ];
+        let present: Vec<&str> = known
+            .into_iter()
+            .filter(|f| self.dir.join(f).exists())
+            .collect();
+        if present.is_empty() {
+            log::warning("No Bukkit/Paper config files yet; start the server once to create them")
+                .unwrap();
+            return;
+        }
+        let mut menu = select("Config file");
+        for file in &present {
+            menu = menu.item(*file, *file, "");
+        }
+        let file = menu.interact().unwrap();
+        let path = self.dir.join(file);
+
+        let key: String = input("Setting path (e.g. world-settings.default.mob-spawn-range):")
+            .required(true)
+            .interact()
+            .unwrap();
+        let current = match read_yaml_value(&path, key.trim()) {
+            Ok(current) => current,
+            Err(e) => {
+                log::error(format!("Failed to read {}: {}", file, e)).unwrap();
+                return;
+            }
+        };
+        let mut prompt = input(format!("New value for {}:", key.trim())).required(false);
+        match &current {
+            Some(value) => {
+                log::info(format!("{} = {}", key.trim(), value)).unwrap();
+                prompt = prompt.default_input(value);
+            }
+            None => log::info(format!("{} is not set yet; it will be added", key.trim())).unwrap(),
+        }
+        let value: String = prompt.interact().unwrap();
+
+        let settings = HashMap::from([(
+            file.to_string(),
+            HashMap::from([(key.trim().to_string(), value.clone())]),
+        )]);
+        match apply_settings(&self.dir, &settings, &self.version) {
+            Ok(()) => log::success(format!("{} = {}", key.trim(), value)).unwrap(),
+            Err(e) => log::error(format!("Failed to update {}: {}", file, e)).unwrap(),
+        }
+    }
+
+    /// Search Modrinth page by page, with sorting and filters, showing a project's details
+    /// before it is picked. Returns the chosen project IDs; empty if the user cancelled.
+    async fn pick_modrinth(&self, project_type: ProjectType, loader: Option<&str>) -> Vec<String> {
+        let query: String = input("Search Modrinth:").required(true).interact().unwrap();
+        let mut search = ModrinthSearch::new(&query, project_type);
+        search.loader = loader.map(str::to_string);
+        search.game_version = Some(self.version.clone());
+        if project_type == ProjectType::Mod {
+            search.side = SideFilter::Server;
+        }
+        let mut chosen: Vec<(String, String)> = vec![];
+
+        loop {
+            let sp = spinner();
+            sp.start("Searching Modrinth...");
+            let page = match search_modrinth(&search).await {
+                Ok(page) => {
+                    sp.stop(format!(
+                        "{} result(s) — page {} of {}",
+                        page.total_hits,
+                        page.page + 1,
+                        page.page_count()
+                    ));
+                    page
+                }
+                Err(e) => {
+                    sp.stop("Search failed");
+                    log::error(format!("Search failed: {}", e)).unwrap();
+                    return vec![];
+                }
+            };
+
+            let mut menu = select(format!("Results ({} selected)", chosen.len()));
+            for h in &page.hits {
+                let mark = if chosen.iter().any(|(id, _)| *id == h.project_id) {
+                    "✓ "
+                } else {
+                    ""
+                };
+                menu = menu.item(
+                    h.project_id.clone(),
+                    format!("{}{}", mark, h.title),
+                    truncate_chars(&h.description, 45),
+                );
+            }
+            if page.has_next() {
+                menu = menu.item("__next".to_string(), "Next page →", "");
+            }
+            if page.page > 0 {
+                menu = menu.item("__prev".to_string(), "← Previous page", "");
+            }
+            menu = menu
+                .item("__sort".to_string(), "Sort", format!("{:?}", search.sort))
+                .item(
+                    "__filter".to_string(),
+                    "Filters",
+                    "Categories and client/server side",
+                )
+                .item("__query".to_string(), "New search", "")
+                .item(
+                    "__done".to_string(),
+                    format!("Install selected ({})", chosen.len()),
+                    "",
+                )
+                .item("__cancel".to_string(), "Cancel", "");
+
+            let choice = menu.interact().unwrap();
+            match choice.as_str() {
+                "__next" => search.page += 1,
+                "__prev" => search.page -= 1,
+                "__sort" => {
+                    search.sort = select("Sort by")
+                        .item(Sort::Relevance, "Relevance", "")
+                        .item(Sort::Downloads, "Downloads", "")
+                        .item(Sort::Follows, "Followers", "")
+                        .item(Sort::Updated, "Recently updated", "")
+                        .item(Sort::Newest, "Newest", "")
+                        .initial_value(search.sort)
+                        .interact()
+                        .unwrap();
+                    search.page = 0;
+                }
+                "__filter" => {
+                    if project_type == ProjectType::Mod {
+                        search.side = select("Runs on")
+                            .item(SideFilter::Server, "Server", "Works on a dedicated server")
+                            .item(
+                                SideFilter::ServerOnly,
+                                "Server only",
+                                "Players don't need to install it",
+                            )
+                            .item(
+                                SideFilter::ClientAndServer,
+                                "Client and server",
+                                "Players need it too",
+                            )
+                            .item(SideFilter::Any, "Any", "")
+                            .initial_value(search.side)
+                            .interact()
+                            .unwrap();
+                    }
+                    match modrinth_categories(project_type).await {
+                        Ok(categories) if !categories.is_empty() => {
+                            let items: Vec<(String, String, String)> = categories
+                                .into_iter()
+                                .map(|c| (c.clone(), c, String::new()))
+                                .collect();
+                            search.categories = multiselect("Categories (all must match):")
+                                .items(&items)
+                                .initial_values(search.categories.clone())
+                                .required(false)
+                                .interact()
+                                .unwrap();
+                        }
+                        Ok(_) => {}
+                        Err(e) => log::warning(format!("Couldn't load categories: {}", e)).unwrap(),
+                    }
+                    search.page = 0;
+                }
+                "__query" => {
+                    search.query = input("Search Modrinth:").required(true).interact().unwrap();
+                    search.page = 0;
+                }
+                "__done" => return chosen.into_iter().map(|(id, _)| id).collect(),
+                "__cancel" => return vec![],
+                id => {
+                    let Some(hit) = page.hits.iter().find(|h| h.project_id == id) else {
+                        continue;
+                    };
+                    log::info(modrinth_details(hit)).unwrap();
+                    if let Some(pos) = chosen.iter().position(|(cid, _)| cid == id) {
+                        if confirm(format!("Remove {} from the selection?", hit.title))
+                            .interact()
+                            .unwrap()
+                        {
+                            chosen.remove(pos);
+                        }
+                    } else if confirm(format!("Add {} to the install?", hit.title))
+                        .initial_value(true)
+                        .interact()
+                        .unwrap()
+                    {
+                        chosen.push((hit.project_id.clone(), hit.title.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Search CurseForge page by page, filtered by class, game version and loader. A numeric
+    /// query is taken as a project ID. Returns `(project ID, name, content type)` per pick.
+    async fn pick_curseforge(
+        &self,
+        content_type: &str,
+        api_key: &str,
+    ) -> Vec<(i32, String, &'static str)> {
+        let prompt = "Search CurseForge (or enter a project ID):";
+        let query: String = input(prompt).required(true).interact().unwrap();
+        if let Ok(id) = query.trim().parse::<i32>() {
+            let content_type = curseforge_content_type(curseforge_class_id(content_type));
+            return vec![(id, format!("project {}", id), content_type)];
+        }
+        let mut search = CurseForgeSearch {
+            query,
+            class_id: curseforge_class_id(content_type),
+            game_version: Some(self.version.clone()),
+            loader: curseforge_loader(&self.platform, content_type),
+            page: 0,
+        };
+        let mut chosen: Vec<(i32, String, &'static str)> = vec![];
+
+        loop {
+            let sp = spinner();
+            sp.start("Searching CurseForge...");
+            let page = match search_curseforge(&search, api_key).await {
+                Ok(page) => {
+                    sp.stop(format!(
+                        "{} result(s) — page {} of {}",
+                        page.total_hits,
+                        page.page + 1,
+                        page.page_count()
+                    ));
+                    page
+                }
+                Err(e) => {
+                    sp.stop("Search failed");
+                    log::error(format!("Search failed: {}", e)).unwrap();
+                    return vec![];
+                }
+            };
+
+            let mut menu = select(format!("Results ({} selected)", chosen.len()));
+            for m in &page.hits {
+                let mark = if chosen.iter().any(|(id, _, _)| *id == m.id) {
+                    "✓ "
+                } else {
+                    ""
+                };
+                menu = menu.item(
+                    m.id.to_string(),
+                    format!("{}{}", mark, m.name),
+                    truncate_chars(&m.summary, 45),
+                );
+            }
+            if page.has_next() {
+                menu = menu.item("__next".to_string(), "Next page →", "");
+            }
+            if page.page > 0 {
+                menu = menu.item("__prev".to_string(), "← Previous page", "");
+            }
+            menu = menu
+                .item(
+                    "__filter".to_string(),
+                    "Filters",
+                    "Class, game version and loader",
+                )
+                .item("__query".to_string(), "New search", "")
+                .item(
+                    "__done".to_string(),
+                    format!("Install selected ({})", chosen.len()),
+                    "",
+                )
+                .item("__cancel".to_string(), "Cancel", "");
+
+            let choice = menu.interact().unwrap();
+            match choice.as_str() {
+                "__next" => search.page += 1,
+                "__prev" => search.page -= 1,
+                "__filter" => {
+                    search.class_id = select("Class")
+                        .item(6, "Mods", "")
+                        .item(5, "Bukkit Plugins", "")
+                        .item(12, "Resource Packs", "")
+                        .item(6945, "Data Packs", "")
+                        .initial_value(search.class_id)
+                        .interact()
+                        .unwrap();
+                    search.game_version = if confirm(format!(
+                        "Only show projects for Minecraft {}?",
+                        self.version
+                    ))
+                    .initial_value(search.game_version.is_some())
+                    .interact()
+                    .unwrap()
+                    {
+                        Some(self.version.clone())
+                    } else {
+                        None
+                    };
+                    search.loader = if search.class_id == 6 {
+                        select("Mod loader")
+                            .item(None, "Any", "")
+                            .item(Some(ModLoaderType::Forge), "Forge", "")
+                            .item(Some(ModLoaderType::NeoForge), "NeoForge", "")
+                            .item(Some(ModLoaderType::Fabric), "Fabric", "")
+                            .item(Some(ModLoaderType::Quilt), "Quilt", "")
+                            .initial_value(search.loader)
+                            .interact()
+                            .unwrap()
+                    } else {
+                        None
+                    };
+                    search.page = 0;
+                }
+                "__query" => {
+                    search.query = input(prompt).required(true).interact().unwrap();
+                    search.page = 0;
+                }
+                "__done" => return chosen,
+                "__cancel" => return vec![],
+                id => {
+                    let Some(m) = page.hits.iter().find(|m| m.id.to_string() == id) else {
+                        continue;
+                    };
+                    log::info(curseforge_details(m)).unwrap();
+                    if let Some(pos) = chosen.iter().position(|(cid, _, _)| *cid == m.id) {
+                        if confirm(format!("Remove {} from the selection?", m.name))
+                            .interact()
+                            .unwrap()
+                        {
+                            chosen.remove(pos);
+                        }
+                    } else if confirm(format!("Add {} to the install?", m.name))
+                        .initial_value(true)
+                        .interact()
+                        .unwrap()
+                    {
+                        let class = m.class_id.unwrap_or(search.class_id);
+                        chosen.push((m.id, m.name.clone(), curseforge_content_type(class)));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Folder that content of `content_type` installs into.
+    fn content_dir(&self, content_type: &str) -> PathBuf {
+        match content_type {
+            "plugin" => self.dir.join("plugins"),
+            "mod" => self.dir.join("mods"),
+            "datapack" => {
+                let level_name = read_property(&self.dir, "server.properties", "level-name")
+                    .unwrap_or_else(|_| "world".to_string());
+                self.dir.join(level_name).join("datapacks")
+            }
+            _ => self.dir.join("resourcepacks"),
+        }
+    }
+
+    /// Check mods/plugins for problems that would crash the server and offer to
+    /// disable the offending files.
+    pub async fn doctor(&self) {
+        let sp = spinner();
+        sp.start("Checking mods and plugins...");
+        let issues = run_doctor(&self.dir).await;
+        if issues.is_empty() {
+            sp.stop("No problems found");
+            return;
+        }
+        sp.stop(format!("{} problem(s) found", issues.len()));
+        for issue in &issues {
+            log::warning(&issue.message).unwrap();
+        }
+        if issues.iter().all(|i| i.files.is_empty()) {
+            return;
+        }
+        if !confirm("Disable the offending files?").interact().unwrap() {
+            return;
+        }
+        let (disabled, failed) = disable_issue_files(&self.dir, &issues);
+        for file in &disabled {
+            log::success(format!("Disabled {}", file)).unwrap();
+        }
+        for (file, e) in &failed {
+            log::error(format!("Failed to disable {}: {}", file, e)).unwrap();
+        }
+    }
+
+    /// Folder whose jars can be toggled on this platform.
+    fn toggleable_dir(&self) -> Option<PathBuf> {
+        match self.platform.as_str() {
+            "Paper" => Some(self.dir.join("plugins")),
+            "Fabric" | "Forge" => Some(self.dir.join("mods")),
+            _ => None,
+        }
+    }
+
     pub async fn start(&self) -> Result<(), ()> {
+        // Starts also run unattended, so only the local checks, and no prompt
+        let issues = diagnose(&scan_content(&self.dir), &HashSet::new());
+        for issue in &issues {
+            log::warning(&issue.message).unwrap();
+        }
+        if issues.iter().any(|i| !i.files.is_empty()) {
+            log::info("Run Doctor from the menu to disable the offending files").unwrap();
+        }
         let spinner = spinner();
         spinner.start("Setting up server...");
         let mut cmd: tokio::process::Child = match Command::new("java")
@@ -929,4 +2681,114 @@ impl OxideMC {
         spinner.stop("");
         Ok(())
     }
+
+    /// Pre-generate the square `radius` blocks around `center` with Chunky, showing its
+    /// progress, and leave the server stopped once it's done.
+    pub async fn pregen(&self, center: (f64, f64), radius: f64) -> Result<(), ()> {
+        log::info(format!(
+            "Pre-generating {} blocks around {}, {}. The server stops when it's done.",
+            radius, center.0, center.1
+        ))
+        .unwrap();
+        let pb = progress_bar(10_000);
+        let mut started = false;
+        let result = run_pregen(
+            &self.dir,
+            &self.platform,
+            &self.version,
+            center,
+            radius,
+            |progress| {
+                if !started {
+                    pb.start(format!("Generating {}", progress.world));
+                    started = true;
+                }
+                pb.set_position((progress.percent * 100.0) as u64);
+                pb.set_message(format!(
+                    "{} chunks, {:.2}%{}",
+                    format_count(progress.chunks as usize),
+                    progress.percent,
+                    progress
+                        .eta
+                        .as_ref()
+                        .map_or(String::new(), |eta| format!(", ETA {}", eta))
+                ));
+            },
+        )
+        .await;
+        match result {
+            Ok(done) => {
+                pb.stop(format!(
+                    "Generated {} chunks in {}",
+                    format_count(done.chunks as usize),
+                    done.world
+                ));
+                Ok(())
+            }
+            Err(e) => {
+                if started {
+                    pb.error("Pre-generation failed");
+                }
+                log::error(format!("Pre-generation failed: {}", e)).unwrap();
+                Err(())
+            }
+        }
+    }
+}
+
+/// Ask for a server name (defaulting to a slug of `default_name`) and parent folder,
+/// and create the server directory.
+fn prompt_server_dir(default_name: &str) -> Result<PathBuf, String> {
+    let default_name = default_name.to_lowercase().replace(' ', "-");
+    let name: String = input("What do you want to name your server?")
+        .default_input(&default_name)
+        .required(true)
+        .interact()
+        .unwrap();
+
+    let input_dir: String = input("Where do you want to save your server?")
+        .default_input("~/minecraft_server")
+        .required(true)
+        .validate(|path: &String| expand_path(path).map(|_| ()))
+        .interact()
+        .unwrap();
+
+    let dir = expand_path(input_dir.as_str())?.join(name.as_str());
+    create_dir_all(&dir).map_err(|e| format!("Failed to create server directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Only Fabric and vanilla have a server jar OxideMC can download; modpacks for other
+/// loaders get their files and instructions for the loader.
+fn pack_jar_supported(platform: &str) -> bool {
+    matches!(platform, "Fabric" | "Vanilla")
+}
+
+/// Download `server.jar`, pinning the Fabric loader when a modpack names one.
+async fn download_server_jar(
+    dir: &PathBuf,
+    platform: &str,
+    version: &str,
+    loader_version: Option<&str>,
+) -> Result<(), String> {
+    let jar_url = match (platform, loader_version) {
+        ("Fabric", Some(loader)) => {
+            let _ = write_oxide_config(dir, "fabric_loader", loader);
+            fabric_server_jar_url(version, loader)
+        }
+        _ => get_jar_url(platform, version).await?,
+    };
+    download_url(&jar_url, dir, "server.jar")
+        .await
+        .map_err(|e| format!("Failed to download server jar: {}", e))
+}
+
+fn log_install_report(report: &InstallReport) {
+    log::success(format!("Installed {} file(s)", report.installed.len())).unwrap();
+    for name in &report.skipped {
+        log::info(format!("Skipped {}", name)).unwrap();
+    }
+    for (name, e) in &report.failed {
+        log::error(format!("Failed to install {}: {}", name, e)).unwrap();
+    }
 }
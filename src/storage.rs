@@ -0,0 +1,421 @@
+use crate::config::read_oxide_config;
+use crate::utils::{list_entries, to_hex};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::error::Error;
+use std::fs::{self, create_dir_all};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use tokio::process::Command;
+
+pub type StorageFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, Box<dyn Error>>> + 'a>>;
+
+/// A place backups can be stored — a local folder, a host reachable over SSH, or an S3 bucket.
+pub trait StorageBackend {
+    /// Human readable location, e.g. `s3://bucket/prefix/`.
+    fn describe(&self) -> String;
+
+    /// Store the local file `file` under `name` in this backend.
+    fn store<'a>(&'a self, file: &'a Path, name: &'a str) -> StorageFuture<'a, ()>;
+
+    /// List the names of stored files.
+    fn list(&self) -> StorageFuture<'_, Vec<String>>;
+}
+
+/// Plain directory on the local filesystem (the default `backups/` folder).
+pub struct LocalStorage {
+    pub dir: PathBuf,
+}
+
+impl StorageBackend for LocalStorage {
+    fn describe(&self) -> String {
+        self.dir.display().to_string()
+    }
+
+    fn store<'a>(&'a self, file: &'a Path, name: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            create_dir_all(&self.dir)?;
+            fs::copy(file, self.dir.join(name))?;
+            Ok(())
+        })
+    }
+
+    fn list(&self) -> StorageFuture<'_, Vec<String>> {
+        Box::pin(async move { list_entries(&self.dir) })
+    }
+}
+
+/// Remote directory reached with rsync over SSH, e.g. `ssh://backup@nas:2222/srv/mc`.
+pub struct SshStorage {
+    pub host: String,
+    pub port: Option<u16>,
+    pub path: String,
+}
+
+impl SshStorage {
+    fn ssh_command(&self) -> String {
+        match self.port {
+            Some(port) => format!("ssh -p {}", port),
+            None => "ssh".to_string(),
+        }
+    }
+}
+
+impl StorageBackend for SshStorage {
+    fn describe(&self) -> String {
+        match self.port {
+            Some(port) => format!("ssh://{}:{}{}", self.host, port, self.path),
+            None => format!("ssh://{}{}", self.host, self.path),
+        }
+    }
+
+    fn store<'a>(&'a self, file: &'a Path, name: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            let dest = format!("{}:{}/{}", self.host, self.path.trim_end_matches('/'), name);
+            let status = Command::new("rsync")
+                .arg("-az")
+                .arg("--mkpath")
+                .arg("-e")
+                .arg(self.ssh_command())
+                .arg(file)
+                .arg(&dest)
+                .status()
+                .await?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("rsync to {} failed", dest).into())
+            }
+        })
+    }
+
+    fn list(&self) -> StorageFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let mut cmd = Command::new("ssh");
+            if let Some(port) = self.port {
+                cmd.arg("-p").arg(port.to_string());
+            }
+            let output = cmd
+                .arg(&self.host)
+                .arg(format!("ls -1 {}", remote_shell_path(&self.path)))
+                .output()
+                .await?;
+            if !output.status.success() {
+                return Err(format!("Failed to list {}", self.describe()).into());
+            }
+            let mut names: Vec<String> = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(|l| l.to_string())
+                .filter(|l| !l.is_empty())
+                .collect();
+            names.sort();
+            Ok(names)
+        })
+    }
+}
+
+/// S3-compatible object storage (AWS, MinIO, Backblaze B2, ...) using path-style requests.
+pub struct S3Storage {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub prefix: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+impl S3Storage {
+    fn object_path(&self, name: &str) -> String {
+        let key = format!("{}{}", self.prefix, name);
+        let encoded: Vec<String> = key.split('/').map(uri_encode).collect();
+        format!("/{}/{}", self.bucket, encoded.join("/"))
+    }
+
+    fn host(&self) -> Result<String, Box<dyn Error>> {
+        let url = reqwest::Url::parse(&self.endpoint)?;
+        let host = url.host_str().ok_or("S3 endpoint has no host")?;
+        Ok(match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        })
+    }
+
+    /// Build the AWS Signature V4 headers for a request.
+    fn sign(
+        &self,
+        method: &str,
+        path: &str,
+        query: &str,
+        payload_hash: &str,
+        now: u64,
+    ) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let (date, amz_date) = amz_timestamp(now);
+        let host = self.host()?;
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            method, path, query, host, payload_hash, amz_date, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            to_hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut key = hmac_sha256(
+            format!("AWS4{}", self.secret_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = to_hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        Ok(vec![
+            ("x-amz-date".to_string(), amz_date),
+            ("x-amz-content-sha256".to_string(), payload_hash.to_string()),
+            (
+                "authorization".to_string(),
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+                    self.access_key, scope, signature
+                ),
+            ),
+        ])
+    }
+}
+
+impl StorageBackend for S3Storage {
+    fn describe(&self) -> String {
+        format!("s3://{}/{}", self.bucket, self.prefix)
+    }
+
+    fn store<'a>(&'a self, file: &'a Path, name: &'a str) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            // Stream the archive instead of reading it into memory; the body is sent unsigned.
+            let body = tokio::fs::File::open(file).await?;
+            let len = body.metadata().await?.len();
+            let path = self.object_path(name);
+            let headers = self.sign("PUT", &path, "", "UNSIGNED-PAYLOAD", unix_now()?)?;
+
+            let mut req = reqwest::Client::new()
+                .put(format!("{}{}", self.endpoint.trim_end_matches('/'), path))
+                .header(reqwest::header::CONTENT_LENGTH, len)
+                .body(body);
+            for (k, v) in headers {
+                req = req.header(k, v);
+            }
+            let res = req.send().await?;
+            if !res.status().is_success() {
+                return Err(format!("S3 upload failed: {}", res.status()).into());
+            }
+            Ok(())
+        })
+    }
+
+    fn list(&self) -> StorageFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let path = format!("/{}", self.bucket);
+            let payload_hash = to_hex(&Sha256::digest(b""));
+            let mut names = vec![];
+            let mut token: Option<String> = None;
+            // Each response holds at most 1000 keys; follow the continuation tokens
+            loop {
+                // Signed queries must list their parameters in sorted order
+                let query = match &token {
+                    Some(token) => format!(
+                        "continuation-token={}&list-type=2&prefix={}",
+                        uri_encode(token),
+                        uri_encode(&self.prefix)
+                    ),
+                    None => format!("list-type=2&prefix={}", uri_encode(&self.prefix)),
+                };
+                let headers = self.sign("GET", &path, &query, &payload_hash, unix_now()?)?;
+
+                let mut req = reqwest::Client::new().get(format!(
+                    "{}{}?{}",
+                    self.endpoint.trim_end_matches('/'),
+                    path,
+                    query
+                ));
+                for (k, v) in headers {
+                    req = req.header(k, v);
+                }
+                let res = req.send().await?;
+                if !res.status().is_success() {
+                    return Err(format!("S3 listing failed: {}", res.status()).into());
+                }
+                let xml = res.text().await?;
+                names.extend(xml_values(&xml, "Key").into_iter().map(|k| {
+                    k.strip_prefix(self.prefix.as_str())
+                        .unwrap_or(&k)
+                        .to_string()
+                }));
+                let truncated = xml_values(&xml, "IsTruncated")
+                    .first()
+                    .is_some_and(|v| v == "true");
+                token = xml_values(&xml, "NextContinuationToken").into_iter().next();
+                if !truncated || token.is_none() {
+                    break;
+                }
+            }
+            names.sort();
+            Ok(names)
+        })
+    }
+}
+
+/// Parse a backup target. Plain paths are local folders; `ssh://` / `sftp://` targets use
+/// rsync over SSH; `s3://bucket/prefix` targets read credentials from `oxidemc.json`
+/// (`s3_endpoint`, `s3_region`, `s3_access_key`, `s3_secret_key`) or the AWS env vars.
+pub fn parse_backend(dir: &Path, spec: &str) -> Result<Box<dyn StorageBackend>, Box<dyn Error>> {
+    if let Some(rest) = spec
+        .strip_prefix("ssh://")
+        .or_else(|| spec.strip_prefix("sftp://"))
+    {
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((h, p)) => (h, Some(p.parse::<u16>().map_err(|_| "Invalid SSH port")?)),
+            None => (authority, None),
+        };
+        if host.is_empty() {
+            return Err("SSH backup target is missing a host".into());
+        }
+        return Ok(Box::new(SshStorage {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        }));
+    }
+
+    if let Some(rest) = spec.strip_prefix("s3://") {
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err("S3 backup target is missing a bucket".into());
+        }
+        let mut prefix = prefix.to_string();
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+        let setting = |key: &str, var: &str| {
+            read_oxide_config(dir, key)
+                .ok()
+                .or_else(|| env::var(var).ok())
+        };
+        let region = setting("s3_region", "AWS_REGION").unwrap_or_else(|| "us-east-1".to_string());
+        let endpoint = setting("s3_endpoint", "AWS_ENDPOINT_URL")
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region));
+        return Ok(Box::new(S3Storage {
+            endpoint,
+            region,
+            bucket: bucket.to_string(),
+            prefix,
+            access_key: setting("s3_access_key", "AWS_ACCESS_KEY_ID")
+                .ok_or("Missing s3_access_key")?,
+            secret_key: setting("s3_secret_key", "AWS_SECRET_ACCESS_KEY")
+                .ok_or("Missing s3_secret_key")?,
+        }));
+    }
+
+    Ok(Box::new(LocalStorage {
+        dir: PathBuf::from(spec),
+    }))
+}
+
+/// The backend configured by `backup_dir` in `oxidemc.json`, defaulting to `<server>/backups`.
+pub fn backup_backend(dir: &Path) -> Result<Box<dyn StorageBackend>, Box<dyn Error>> {
+    match read_oxide_config(dir, "backup_dir") {
+        Ok(spec) => parse_backend(dir, &spec),
+        Err(_) => Ok(Box::new(LocalStorage {
+            dir: dir.join("backups"),
+        })),
+    }
+}
+
+fn unix_now() -> Result<u64, Box<dyn Error>> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs())
+}
+
+/// Format a unix timestamp as the (`YYYYMMDD`, `YYYYMMDDTHHMMSSZ`) pair used by SigV4.
+pub fn amz_timestamp(secs: u64) -> (String, String) {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    let date = format!("{:04}{:02}{:02}", year, month, day);
+    let stamp = format!(
+        "{}T{:02}{:02}{:02}Z",
+        date,
+        rem / 3_600,
+        (rem % 3_600) / 60,
+        rem % 60
+    );
+    (date, stamp)
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Quote `s` as a single POSIX shell word.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Quote `path` for a remote shell, leaving a leading `~/` outside the quotes so it still
+/// expands to the remote home directory.
+pub fn remote_shell_path(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("~/{}", shell_quote(rest)),
+        None if path == "~" => path.to_string(),
+        None => shell_quote(path),
+    }
+}
+
+/// The text of every `<tag>` element in an S3 XML response, unescaped.
+fn xml_values(xml: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    xml.split(open.as_str())
+        .skip(1)
+        .filter_map(|s| s.split(close.as_str()).next())
+        .map(|v| {
+            v.replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&apos;", "'")
+                .replace("&amp;", "&")
+        })
+        .collect()
+}
+
+fn uri_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
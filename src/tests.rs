@@ -1,5 +1,10 @@
 use super::*;
-use std::collections::HashMap;
+use ferinth::structures::{
+    project::ProjectType,
+    search::{Facet, SearchHit},
+};
+use furse::structures::common_structs::ModLoaderType;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use tempfile::TempDir;
 
@@ -164,6 +169,7 @@ fn list_entries_nonexistent_dir() {
 #[test]
 fn preset_round_trip() {
     let preset = Preset {
+        format: PRESET_FORMAT,
         info: PresetInfo {
             name: "Test".to_string(),
             description: "A test preset".to_string(),
@@ -185,9 +191,12 @@ fn preset_round_trip() {
                 modrinth_id: Some("abc123".to_string()),
                 url: None,
                 version: Some("2.20.0".to_string()),
+                sha512: None,
+                metadata: None,
             }],
             ..Default::default()
         },
+        gamerules: HashMap::new(),
     };
 
     let json = serde_json::to_string_pretty(&preset).unwrap();
@@ -227,6 +236,8 @@ fn preset_skip_serializing_none_fields() {
         modrinth_id: None,
         url: None,
         version: None,
+        sha512: None,
+        metadata: None,
     };
     let json = serde_json::to_string(&entry).unwrap();
     assert!(!json.contains("modrinth_id"));
@@ -557,3 +568,4308 @@ fn get_platform_no_jar_errors() {
     let result = get_platform(&tmp.path().to_path_buf());
     assert!(result.is_err());
 }
+
+// ── storage backends ─────────────────────────────────────────────
+
+#[test]
+fn amz_timestamp_formats_utc() {
+    // 2013-05-24T00:00:00Z, the date used in the AWS SigV4 examples
+    let (date, stamp) = amz_timestamp(1_369_353_600);
+    assert_eq!(date, "20130524");
+    assert_eq!(stamp, "20130524T000000Z");
+
+    let (date, stamp) = amz_timestamp(951_827_696); // 2000-02-29T12:34:56Z
+    assert_eq!(date, "20000229");
+    assert_eq!(stamp, "20000229T123456Z");
+}
+
+#[test]
+fn parse_backend_local_path() {
+    let tmp = TempDir::new().unwrap();
+    let backend = parse_backend(&tmp.path().to_path_buf(), "/srv/backups").unwrap();
+    assert_eq!(backend.describe(), "/srv/backups");
+}
+
+#[test]
+fn parse_backend_ssh_with_port() {
+    let tmp = TempDir::new().unwrap();
+    let backend =
+        parse_backend(&tmp.path().to_path_buf(), "ssh://backup@nas:2222/srv/mc").unwrap();
+    assert_eq!(backend.describe(), "ssh://backup@nas:2222/srv/mc");
+
+    let backend = parse_backend(&tmp.path().to_path_buf(), "sftp://nas/srv/mc").unwrap();
+    assert_eq!(backend.describe(), "ssh://nas/srv/mc");
+}
+
+#[test]
+fn parse_backend_ssh_bad_port_errors() {
+    let tmp = TempDir::new().unwrap();
+    assert!(parse_backend(&tmp.path().to_path_buf(), "ssh://nas:abc/srv").is_err());
+}
+
+#[test]
+fn parse_backend_s3_reads_oxide_config() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    write_oxide_config(&dir, "s3_access_key", "AKID").unwrap();
+    write_oxide_config(&dir, "s3_secret_key", "SECRET").unwrap();
+
+    let backend = parse_backend(&dir, "s3://mc-backups/nightly").unwrap();
+    assert_eq!(backend.describe(), "s3://mc-backups/nightly/");
+}
+
+#[test]
+fn parse_backend_s3_missing_bucket_errors() {
+    let tmp = TempDir::new().unwrap();
+    assert!(parse_backend(&tmp.path().to_path_buf(), "s3://").is_err());
+}
+
+#[tokio::test]
+async fn local_storage_store_and_list() {
+    let tmp = TempDir::new().unwrap();
+    let archive = tmp.path().join("world.tar.gz");
+    fs::write(&archive, "data").unwrap();
+
+    let backend = LocalStorage {
+        dir: tmp.path().join("backups"),
+    };
+    backend.store(&archive, "world_1.tar.gz").await.unwrap();
+
+    assert_eq!(backend.list().await.unwrap(), vec!["world_1.tar.gz"]);
+    assert_eq!(
+        fs::read_to_string(tmp.path().join("backups").join("world_1.tar.gz")).unwrap(),
+        "data"
+    );
+}
+
+/// Minimal one-shot HTTP server standing in for an S3 endpoint. Returns the raw request.
+fn mock_http_server(response: &'static str) -> (String, std::thread::JoinHandle<String>) {
+    mock_http_server_bytes(response.as_bytes().to_vec())
+}
+
+/// `mock_http_server` for responses with a binary body.
+fn mock_http_server_bytes(response: Vec<u8>) -> (String, std::thread::JoinHandle<String>) {
+    let (addr, handle) = mock_http_server_seq(vec![response]);
+    (
+        addr,
+        std::thread::spawn(move || handle.join().unwrap().remove(0)),
+    )
+}
+
+/// `mock_http_server` answering one connection per response, in order. Returns the raw
+/// requests.
+fn mock_http_server_seq(responses: Vec<Vec<u8>>) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    use std::io::{Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut requests = vec![];
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut chunk).unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+                let text = String::from_utf8_lossy(&buf).to_string();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let len = text[..end]
+                        .lines()
+                        .find_map(|l| {
+                            l.to_ascii_lowercase()
+                                .strip_prefix("content-length:")
+                                .map(|v| v.trim().parse::<usize>().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if buf.len() >= end + 4 + len {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            stream.write_all(&response).unwrap();
+            requests.push(String::from_utf8_lossy(&buf).to_string());
+        }
+        requests
+    });
+    (addr, handle)
+}
+
+#[tokio::test]
+async fn s3_storage_signs_and_uploads() {
+    let (endpoint, server) =
+        mock_http_server("HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    let tmp = TempDir::new().unwrap();
+    let archive = tmp.path().join("world.tar.gz");
+    fs::write(&archive, "backup-bytes").unwrap();
+
+    let backend = S3Storage {
+        endpoint,
+        region: "us-east-1".to_string(),
+        bucket: "mc".to_string(),
+        prefix: "nightly/".to_string(),
+        access_key: "AKID".to_string(),
+        secret_key: "SECRET".to_string(),
+    };
+    backend.store(&archive, "world_1.tar.gz").await.unwrap();
+
+    let request = server.join().unwrap();
+    assert!(request.starts_with("PUT /mc/nightly/world_1.tar.gz HTTP/1.1"));
+    assert!(request.contains("AWS4-HMAC-SHA256 Credential=AKID/"));
+    assert!(request.contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date"));
+    assert!(request.contains("x-amz-content-sha256: UNSIGNED-PAYLOAD"));
+    assert!(request.contains("content-length: 12"));
+    assert!(request.ends_with("backup-bytes"));
+}
+
+#[tokio::test]
+async fn s3_storage_lists_keys() {
+    let (endpoint, server) = mock_http_server(
+        "HTTP/1.1 200 OK\r\nContent-Length: 133\r\nConnection: close\r\n\r\n<ListBucketResult><Contents><Key>nightly/a.tar.gz</Key></Contents><Contents><Key>nightly/b.tar.gz</Key></Contents></ListBucketResult>",
+    );
+    let backend = S3Storage {
+        endpoint,
+        region: "us-east-1".to_string(),
+        bucket: "mc".to_string(),
+        prefix: "nightly/".to_string(),
+        access_key: "AKID".to_string(),
+        secret_key: "SECRET".to_string(),
+    };
+    let names = backend.list().await.unwrap();
+    let request = server.join().unwrap();
+
+    assert!(request.starts_with("GET /mc?list-type=2&prefix=nightly%2F HTTP/1.1"));
+    assert_eq!(names, vec!["a.tar.gz", "b.tar.gz"]);
+}
+
+#[tokio::test]
+async fn s3_storage_list_follows_continuation_tokens() {
+    let page = |body: &str| {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .into_bytes()
+    };
+    let (endpoint, server) = mock_http_server_seq(vec![
+        page("<ListBucketResult><IsTruncated>true</IsTruncated><Contents><Key>nightly/b.tar.gz</Key></Contents><NextContinuationToken>1/a=</NextContinuationToken></ListBucketResult>"),
+        page("<ListBucketResult><IsTruncated>false</IsTruncated><Contents><Key>nightly/a&amp;c.tar.gz</Key></Contents></ListBucketResult>"),
+    ]);
+    let backend = S3Storage {
+        endpoint,
+        region: "us-east-1".to_string(),
+        bucket: "mc".to_string(),
+        prefix: "nightly/".to_string(),
+        access_key: "AKID".to_string(),
+        secret_key: "SECRET".to_string(),
+    };
+    let names = backend.list().await.unwrap();
+    let requests = server.join().unwrap();
+
+    assert_eq!(names, vec!["a&c.tar.gz", "b.tar.gz"]);
+    assert!(requests[1]
+        .starts_with("GET /mc?continuation-token=1%2Fa%3D&list-type=2&prefix=nightly%2F HTTP/1.1"));
+}
+
+#[test]
+fn shell_quote_escapes_single_quotes() {
+    assert_eq!(shell_quote("/srv/mc"), "'/srv/mc'");
+    assert_eq!(
+        shell_quote("/srv/it's; rm -rf ~"),
+        "'/srv/it'\\''s; rm -rf ~'"
+    );
+}
+
+#[test]
+fn remote_shell_path_keeps_a_leading_tilde_unquoted() {
+    assert_eq!(remote_shell_path("~/backups"), "~/'backups'");
+    assert_eq!(remote_shell_path("~"), "~");
+    assert_eq!(remote_shell_path("/srv/~mc"), "'/srv/~mc'");
+}
+
+// ── mod updates ──────────────────────────────────────────────────
+
+#[test]
+fn modrinth_loader_per_platform() {
+    assert_eq!(modrinth_loader("Fabric", "mod"), Some("fabric"));
+    assert_eq!(modrinth_loader("Forge", "mod"), Some("forge"));
+    assert_eq!(modrinth_loader("Paper", "plugin"), Some("paper"));
+    assert_eq!(modrinth_loader("Paper", "datapack"), None);
+    assert_eq!(modrinth_loader("Vanilla", "mod"), None);
+}
+
+#[test]
+fn sha1_file_matches_known_digest() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("a.jar");
+    fs::write(&path, "abc").unwrap();
+    assert_eq!(
+        sha1_file(&path).unwrap(),
+        "a9993e364706816aba3e25717850c26c9cd0d89d"
+    );
+}
+
+#[test]
+fn swap_in_update_replaces_old_file() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("sodium-0.5.jar"), "old").unwrap();
+    fs::write(dir.join(".sodium-0.6.jar.part"), "new").unwrap();
+
+    swap_in_update(&dir, "sodium-0.5.jar", ".sodium-0.6.jar.part", "sodium-0.6.jar").unwrap();
+
+    assert_eq!(list_entries(&dir).unwrap(), vec!["sodium-0.6.jar"]);
+    assert_eq!(fs::read_to_string(dir.join("sodium-0.6.jar")).unwrap(), "new");
+}
+
+#[test]
+fn swap_in_update_same_filename() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("lithium.jar"), "old").unwrap();
+    fs::write(dir.join(".lithium.jar.part"), "new").unwrap();
+
+    swap_in_update(&dir, "lithium.jar", ".lithium.jar.part", "lithium.jar").unwrap();
+
+    assert_eq!(list_entries(&dir).unwrap(), vec!["lithium.jar"]);
+    assert_eq!(fs::read_to_string(dir.join("lithium.jar")).unwrap(), "new");
+}
+
+// ── lockfile ─────────────────────────────────────────────────────
+
+#[test]
+fn record_install_writes_hash_and_size() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let mods_dir = dir.join("mods");
+    fs::create_dir(&mods_dir).unwrap();
+    fs::write(mods_dir.join("sodium.jar"), "abc").unwrap();
+
+    let entry = record_install(
+        &dir,
+        &mods_dir,
+        "sodium.jar",
+        FileOrigin {
+            source: "modrinth".to_string(),
+            project_id: Some("AANobbMI".to_string()),
+            version_id: Some("v1".to_string()),
+            version: Some("0.6.0".to_string()),
+            url: Some("https://cdn.modrinth.com/sodium.jar".to_string()),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(entry.path, "mods/sodium.jar");
+    assert_eq!(entry.size, 3);
+    assert!(entry.sha512.starts_with("ddaf35a193617aba"));
+
+    let lock = read_lockfile(&dir).unwrap();
+    assert_eq!(lock.files.len(), 1);
+    assert_eq!(lock.find("mods/sodium.jar"), Some(&entry));
+}
+
+#[test]
+fn record_install_replaces_same_path() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let mods_dir = dir.join("mods");
+    fs::create_dir(&mods_dir).unwrap();
+    fs::write(mods_dir.join("a.jar"), "1").unwrap();
+
+    let origin = |v: &str| FileOrigin {
+        source: "modrinth".to_string(),
+        version: Some(v.to_string()),
+        ..Default::default()
+    };
+    record_install(&dir, &mods_dir, "a.jar", origin("1.0")).unwrap();
+    record_install(&dir, &mods_dir, "a.jar", origin("1.1")).unwrap();
+
+    let lock = read_lockfile(&dir).unwrap();
+    assert_eq!(lock.files.len(), 1);
+    assert_eq!(lock.files[0].origin.version.as_deref(), Some("1.1"));
+}
+
+#[test]
+fn forget_install_removes_entry() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let plugins_dir = dir.join("plugins");
+    fs::create_dir(&plugins_dir).unwrap();
+    fs::write(plugins_dir.join("Vault.jar"), "").unwrap();
+    record_install(&dir, &plugins_dir, "Vault.jar", FileOrigin::default()).unwrap();
+
+    forget_install(&dir, &plugins_dir, "Vault.jar").unwrap();
+
+    assert!(read_lockfile(&dir).unwrap().files.is_empty());
+}
+
+#[test]
+fn read_lockfile_missing_is_empty() {
+    let tmp = TempDir::new().unwrap();
+    assert!(read_lockfile(&tmp.path().to_path_buf()).unwrap().files.is_empty());
+}
+
+#[test]
+fn scan_mods_uses_lockfile() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("server.properties"), "level-name=world\n").unwrap();
+    let mods_dir = dir.join("mods");
+    fs::create_dir(&mods_dir).unwrap();
+    fs::write(mods_dir.join("lithium.jar"), "x").unwrap();
+    fs::write(mods_dir.join("manual.jar"), "y").unwrap();
+    record_install(
+        &dir,
+        &mods_dir,
+        "lithium.jar",
+        FileOrigin {
+            source: "modrinth".to_string(),
+            project_id: Some("gvQqBUqZ".to_string()),
+            version: Some("0.15.0".to_string()),
+            url: Some("https://cdn.modrinth.com/lithium.jar".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let mods = scan_mods(&dir);
+    let lithium = mods.mods.iter().find(|m| m.name == "lithium.jar").unwrap();
+    assert_eq!(lithium.modrinth_id.as_deref(), Some("gvQqBUqZ"));
+    assert_eq!(lithium.version.as_deref(), Some("0.15.0"));
+    assert!(lithium.sha512.is_some());
+
+    let manual = mods.mods.iter().find(|m| m.name == "manual.jar").unwrap();
+    assert!(manual.modrinth_id.is_none());
+    assert!(manual.url.is_none());
+}
+
+// ── Modrinth version selection ───────────────────────────────────
+
+fn modrinth_version(
+    id: &str,
+    game_versions: &[&str],
+    loaders: &[&str],
+) -> ferinth::structures::version::Version {
+    serde_json::from_value(serde_json::json!({
+        "name": id,
+        "version_number": id,
+        "changelog": null,
+        "dependencies": [],
+        "game_versions": game_versions,
+        "version_type": "release",
+        "loaders": loaders,
+        "featured": false,
+        "status": "listed",
+        "requested_status": null,
+        "id": id,
+        "project_id": "P",
+        "author_id": "A",
+        "date_published": "2024-01-01T00:00:00Z",
+        "downloads": 0,
+        "files": []
+    }))
+    .unwrap()
+}
+
+#[test]
+fn pick_modrinth_version_prefers_exact_match() {
+    let versions = vec![
+        modrinth_version("new", &["1.21.5"], &["fabric"]),
+        modrinth_version("match", &["1.21.4"], &["fabric"]),
+        modrinth_version("forge", &["1.21.4"], &["forge"]),
+    ];
+    let picked = pick_modrinth_version(&versions, Some("fabric"), "1.21.4", false).unwrap();
+    assert_eq!(picked.id, "match");
+}
+
+#[test]
+fn pick_modrinth_version_loose_falls_back_to_loader() {
+    let versions = vec![
+        modrinth_version("forge", &["1.21.4"], &["forge"]),
+        modrinth_version("fabric-old", &["1.20.1"], &["fabric"]),
+    ];
+    let picked = pick_modrinth_version(&versions, Some("fabric"), "1.21.4", false).unwrap();
+    assert_eq!(picked.id, "fabric-old");
+}
+
+#[test]
+fn pick_modrinth_version_strict_requires_game_version() {
+    let versions = vec![modrinth_version("fabric-old", &["1.20.1"], &["fabric"])];
+    assert!(pick_modrinth_version(&versions, Some("fabric"), "1.21.4", true).is_none());
+}
+
+#[test]
+fn pick_pinned_modrinth_version_matches_the_server_loader() {
+    let mut forge = modrinth_version("forge-id", &["1.21.4"], &["forge"]);
+    forge.version_number = "mc1.21.4-0.6.0".to_string();
+    let mut fabric = modrinth_version("fabric-id", &["1.21.4"], &["fabric"]);
+    fabric.version_number = "mc1.21.4-0.6.0".to_string();
+    let versions = vec![forge, fabric];
+
+    let picked =
+        pick_pinned_modrinth_version(&versions, "mc1.21.4-0.6.0", Some("fabric"), "1.21.4");
+    assert_eq!(picked.unwrap().id, "fabric-id");
+    // A version ID is exact, whatever it was built for
+    let picked = pick_pinned_modrinth_version(&versions, "forge-id", Some("fabric"), "1.21.4");
+    assert_eq!(picked.unwrap().id, "forge-id");
+    assert!(
+        pick_pinned_modrinth_version(&versions, "mc1.21.4-0.6.0", Some("paper"), "1.21.4")
+            .is_none()
+    );
+}
+
+/// Canned Modrinth data for `resolve_modrinth_plan`.
+struct FakeModrinth {
+    versions: Vec<ferinth::structures::version::Version>,
+}
+
+impl ModrinthApi for FakeModrinth {
+    fn project_versions<'a>(
+        &'a self,
+        project_id: &'a str,
+    ) -> ModrinthFuture<'a, Vec<ferinth::structures::version::Version>> {
+        Box::pin(async move {
+            Ok(self
+                .versions
+                .iter()
+                .filter(|v| v.project_id == project_id)
+                .cloned()
+                .collect())
+        })
+    }
+
+    fn version<'a>(
+        &'a self,
+        version_id: &'a str,
+    ) -> ModrinthFuture<'a, ferinth::structures::version::Version> {
+        Box::pin(async move {
+            let found = self.versions.iter().find(|v| v.id == version_id);
+            Ok(found.cloned().ok_or("unknown version")?)
+        })
+    }
+
+    fn project_titles<'a>(
+        &'a self,
+        ids: &'a [&'a str],
+    ) -> ModrinthFuture<'a, HashMap<String, String>> {
+        Box::pin(async move {
+            Ok(ids
+                .iter()
+                .map(|id| (id.to_string(), format!("Mod {}", id)))
+                .collect())
+        })
+    }
+}
+
+/// A 1.21.4 Fabric build of `project` with the given dependencies
+/// (`[project, version, type]`).
+fn fabric_version(
+    project: &str,
+    id: &str,
+    deps: &[(Option<&str>, Option<&str>, &str)],
+) -> ferinth::structures::version::Version {
+    let mut version = modrinth_version(id, &["1.21.4"], &["fabric"]);
+    version.project_id = project.to_string();
+    version.dependencies = deps
+        .iter()
+        .map(|(project, version, kind)| {
+            serde_json::from_value(serde_json::json!({
+                "project_id": project,
+                "version_id": version,
+                "file_name": null,
+                "dependency_type": kind,
+            }))
+            .unwrap()
+        })
+        .collect();
+    version
+}
+
+#[tokio::test]
+async fn resolve_modrinth_plan_follows_dependencies_recursively() {
+    let api = FakeModrinth {
+        versions: vec![
+            fabric_version("a", "a1", &[(Some("b"), None, "required")]),
+            fabric_version("b", "b1", &[(None, Some("c1"), "required")]),
+            fabric_version("c", "c2", &[]),
+            fabric_version("c", "c1", &[(Some("d"), None, "optional")]),
+        ],
+    };
+    let plan = resolve_modrinth_plan(
+        &api,
+        &HashMap::new(),
+        &["a".to_string()],
+        Some("fabric"),
+        "1.21.4",
+    )
+    .await
+    .unwrap();
+
+    let installs: Vec<(&str, Option<&str>)> = plan
+        .installs
+        .iter()
+        .map(|p| (p.version.id.as_str(), p.required_by.as_deref()))
+        .collect();
+    // The pinned c1 wins over the newer c2; optional dependencies are left out
+    assert_eq!(
+        installs,
+        vec![("a1", None), ("b1", Some("Mod a")), ("c1", Some("Mod b"))]
+    );
+    assert!(plan.warnings.is_empty());
+}
+
+#[tokio::test]
+async fn resolve_modrinth_plan_skips_installed_dependencies() {
+    let api = FakeModrinth {
+        versions: vec![
+            fabric_version("a", "a1", &[(Some("b"), None, "required")]),
+            fabric_version("b", "b1", &[]),
+        ],
+    };
+    let installed = HashMap::from([("b".to_string(), "b.jar".to_string())]);
+    let plan = resolve_modrinth_plan(
+        &api,
+        &installed,
+        &["a".to_string()],
+        Some("fabric"),
+        "1.21.4",
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(plan.installs.len(), 1);
+    assert_eq!(plan.installs[0].version.id, "a1");
+}
+
+#[tokio::test]
+async fn resolve_modrinth_plan_warns_about_incompatible_mods() {
+    let api = FakeModrinth {
+        versions: vec![
+            fabric_version("a", "a1", &[(Some("x"), None, "incompatible")]),
+            fabric_version("b", "b1", &[(Some("y"), None, "incompatible")]),
+        ],
+    };
+    let installed = HashMap::from([("x".to_string(), "x.jar".to_string())]);
+    let plan = resolve_modrinth_plan(
+        &api,
+        &installed,
+        &["a".to_string(), "b".to_string()],
+        Some("fabric"),
+        "1.21.4",
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(plan.warnings, vec!["Mod a is incompatible with x.jar"]);
+    assert_eq!(plan.installs.len(), 2);
+}
+
+#[tokio::test]
+async fn resolve_modrinth_plan_checks_pinned_dependencies() {
+    let mut old = fabric_version("b", "b-old", &[]);
+    old.game_versions = vec!["1.20.1".to_string()];
+    let api = FakeModrinth {
+        versions: vec![
+            fabric_version("a", "a1", &[(Some("b"), Some("b-old"), "required")]),
+            fabric_version("b", "b1", &[]),
+            old,
+        ],
+    };
+    let plan = resolve_modrinth_plan(
+        &api,
+        &HashMap::new(),
+        &["a".to_string()],
+        Some("fabric"),
+        "1.21.4",
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(plan.installs.len(), 1);
+    assert_eq!(
+        plan.warnings,
+        vec!["Mod a requires Mod b b-old, which isn't a fabric build for 1.21.4"]
+    );
+}
+
+// ── install_preset_mods ──────────────────────────────────────────
+
+#[tokio::test]
+async fn install_preset_mods_skips_unsupported_and_sourceless() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("server.properties"), "level-name=world\n").unwrap();
+
+    let entry = |name: &str| ModEntry {
+        name: name.to_string(),
+        modrinth_id: None,
+        url: None,
+        version: None,
+        sha512: None,
+        metadata: None,
+    };
+    let mods = PresetMods {
+        plugins: vec![entry("EssentialsX")],
+        mods: vec![entry("NoSource")],
+        ..Default::default()
+    };
+
+    let report = install_preset_mods(&dir, &mods, "Fabric", "1.21.4").await;
+    assert!(report.installed.is_empty());
+    assert!(report.failed.is_empty());
+    assert_eq!(report.skipped.len(), 2);
+    assert!(report.skipped[0].contains("NoSource"));
+    assert!(report.skipped[1].contains("not supported on Fabric"));
+}
+
+#[tokio::test]
+async fn install_preset_mods_skips_mods_on_paper() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("server.properties"), "level-name=world\n").unwrap();
+
+    let mods = PresetMods {
+        mods: vec![ModEntry {
+            name: "sodium.jar".to_string(),
+            modrinth_id: None,
+            url: Some("http://127.0.0.1:1/sodium.jar".to_string()),
+            version: None,
+            sha512: None,
+            metadata: None,
+        }],
+        ..Default::default()
+    };
+
+    let report = install_preset_mods(&dir, &mods, "Paper", "1.21.4").await;
+    assert_eq!(
+        report.skipped,
+        vec!["sodium.jar (mods are not supported on Paper)"]
+    );
+    assert!(!dir.join("mods").exists());
+}
+
+#[tokio::test]
+async fn install_preset_mods_skips_existing_files() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("server.properties"), "level-name=world\n").unwrap();
+    fs::create_dir(dir.join("plugins")).unwrap();
+    fs::write(dir.join("plugins").join("Vault.jar"), "").unwrap();
+
+    let mods = PresetMods {
+        plugins: vec![ModEntry {
+            name: "Vault.jar".to_string(),
+            modrinth_id: None,
+            url: Some("http://127.0.0.1:1/Vault.jar".to_string()),
+            version: None,
+            sha512: None,
+            metadata: None,
+        }],
+        ..Default::default()
+    };
+
+    let report = install_preset_mods(&dir, &mods, "Paper", "1.21.4").await;
+    assert_eq!(report.skipped, vec!["Vault.jar (already installed)"]);
+}
+
+/// An HTTP response carrying a zip with `entries`.
+fn zip_response(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("body.zip");
+    write_zip(&path, entries);
+    let body = fs::read(&path).unwrap();
+    let mut raw = format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    raw.extend_from_slice(&body);
+    raw
+}
+
+#[tokio::test]
+async fn install_preset_mods_downloads_url_entries() {
+    let (base, server) = mock_http_server_bytes(zip_response(&[("pack.mcmeta", b"{}")]));
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("server.properties"), "level-name=world\n").unwrap();
+
+    let mods = PresetMods {
+        datapacks: vec![ModEntry {
+            name: "Example Datapack".to_string(),
+            modrinth_id: None,
+            url: Some(format!("{}/files/terralith.zip", base)),
+            version: Some("1.0.0".to_string()),
+            sha512: None,
+            metadata: None,
+        }],
+        ..Default::default()
+    };
+
+    let report = install_preset_mods(&dir, &mods, "Vanilla", "1.21.4").await;
+    server.join().unwrap();
+    assert_eq!(report.installed, vec!["terralith.zip"]);
+    assert!(dir.join("world/datapacks/terralith.zip").is_file());
+    let lock = read_lockfile(&dir).unwrap();
+    let locked = lock.find("world/datapacks/terralith.zip").unwrap();
+    assert_eq!(locked.origin.source, "url");
+    assert_eq!(locked.origin.version.as_deref(), Some("1.0.0"));
+}
+
+#[tokio::test]
+async fn install_preset_mods_rejects_hash_mismatch() {
+    let (base, server) = mock_http_server_bytes(zip_response(&[("pack.mcmeta", b"{}")]));
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("server.properties"), "level-name=world\n").unwrap();
+
+    let mods = PresetMods {
+        resource_packs: vec![ModEntry {
+            name: "pack".to_string(),
+            modrinth_id: None,
+            url: Some(format!("{}/pack.zip", base)),
+            version: None,
+            sha512: Some("00".to_string()),
+            metadata: None,
+        }],
+        ..Default::default()
+    };
+
+    let report = install_preset_mods(&dir, &mods, "Vanilla", "1.21.4").await;
+    server.join().unwrap();
+    assert!(report.installed.is_empty());
+    assert_eq!(report.failed.len(), 1);
+    assert!(!dir.join("resourcepacks/pack.zip").exists());
+    assert!(read_lockfile(&dir).unwrap().files.is_empty());
+}
+
+#[tokio::test]
+async fn install_preset_mods_keeps_url_entries_inside_the_content_folder() {
+    let (base, server) = mock_http_server_bytes(zip_response(&[("pack.mcmeta", b"{}")]));
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().join("server");
+    fs::create_dir_all(dir.join("resourcepacks")).unwrap();
+    fs::write(dir.join("resourcepacks/pack.zip"), "mine").unwrap();
+    let entry = |name: &str, url: String| ModEntry {
+        name: name.to_string(),
+        modrinth_id: None,
+        url: Some(url),
+        version: None,
+        sha512: None,
+        metadata: None,
+    };
+    let mods = PresetMods {
+        resource_packs: vec![
+            entry("../../.bashrc", "http://127.0.0.1:1/evil.zip".to_string()),
+            entry("Shared Pack", format!("{}/pack.zip", base)),
+        ],
+        ..Default::default()
+    };
+
+    let report = install_preset_mods(&dir, &mods, "Vanilla", "1.21.4").await;
+    server.join().unwrap();
+    assert!(report.installed.is_empty());
+    assert_eq!(report.failed.len(), 2);
+    assert_eq!(report.failed[0].0, "../../.bashrc");
+    assert!(report.failed[1].1.contains("already exists"));
+    assert!(!tmp.path().join(".bashrc").exists());
+    assert_eq!(
+        fs::read_to_string(dir.join("resourcepacks/pack.zip")).unwrap(),
+        "mine"
+    );
+}
+
+// ── mrpack import ────────────────────────────────────────────────
+
+fn write_zip(path: &std::path::Path, entries: &[(&str, &[u8])]) {
+    use std::io::Write;
+    let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+    for (name, data) in entries {
+        zip.start_file(*name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(data).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+#[test]
+fn mrpack_platform_fabric() {
+    let index: MrpackIndex = serde_json::from_str(
+        r#"{"formatVersion":1,"game":"minecraft","versionId":"1.0","name":"Pack","files":[],
+            "dependencies":{"minecraft":"1.21.4","fabric-loader":"0.16.9"}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        mrpack_platform(&index).unwrap(),
+        (
+            "Fabric".to_string(),
+            "1.21.4".to_string(),
+            Some("0.16.9".to_string())
+        )
+    );
+}
+
+#[test]
+fn mrpack_platform_forge_and_neoforge() {
+    let index = |loader: &str, version: &str| -> MrpackIndex {
+        serde_json::from_value(serde_json::json!({
+            "formatVersion": 1, "game": "minecraft", "versionId": "1.0", "name": "Pack",
+            "files": [], "dependencies": { "minecraft": "1.20.1", loader: version }
+        }))
+        .unwrap()
+    };
+    assert_eq!(
+        mrpack_platform(&index("forge", "47.2.0")).unwrap(),
+        (
+            "Forge".to_string(),
+            "1.20.1".to_string(),
+            Some("47.2.0".to_string())
+        )
+    );
+    assert_eq!(
+        mrpack_platform(&index("neoforge", "20.4.237")).unwrap().0,
+        "NeoForge"
+    );
+}
+
+#[test]
+fn mrpack_platform_unsupported_loader() {
+    let index: MrpackIndex = serde_json::from_str(
+        r#"{"formatVersion":1,"game":"minecraft","versionId":"1.0","name":"Pack","files":[],
+            "dependencies":{"minecraft":"1.20.1","quilt-loader":"0.26.0"}}"#,
+    )
+    .unwrap();
+    assert!(mrpack_platform(&index).unwrap_err().contains("Quilt"));
+}
+
+#[test]
+fn safe_relative_path_rejects_escapes() {
+    assert!(safe_relative_path("mods/sodium.jar").is_some());
+    assert!(safe_relative_path("../evil.jar").is_none());
+    assert!(safe_relative_path("mods/../../evil.jar").is_none());
+    assert!(safe_relative_path("/etc/passwd").is_none());
+    assert!(safe_relative_path("").is_none());
+}
+
+#[test]
+fn origin_from_modrinth_cdn_url() {
+    let origin =
+        origin_from_url("https://cdn.modrinth.com/data/AANobbMI/versions/u1OEbNKx/sodium.jar");
+    assert_eq!(origin.source, "modrinth");
+    assert_eq!(origin.project_id.as_deref(), Some("AANobbMI"));
+    assert_eq!(origin.version_id.as_deref(), Some("u1OEbNKx"));
+
+    let origin = origin_from_url("https://example.com/pack.zip");
+    assert_eq!(origin.source, "url");
+    assert!(origin.project_id.is_none());
+}
+
+#[test]
+fn read_mrpack_index_requires_index() {
+    let tmp = TempDir::new().unwrap();
+    let pack = tmp.path().join("bad.mrpack");
+    write_zip(&pack, &[("overrides/config/a.txt", b"a")]);
+    assert!(read_mrpack_index(&pack).is_err());
+}
+
+#[tokio::test]
+async fn apply_mrpack_downloads_and_applies_overrides() {
+    use sha2::Digest;
+    let (base, server) =
+        mock_http_server("HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\nJAR!");
+    let sha512 = to_hex(&sha2::Sha512::digest(b"JAR!"));
+    let index = serde_json::json!({
+        "formatVersion": 1,
+        "game": "minecraft",
+        "versionId": "1.0",
+        "name": "Pack",
+        "dependencies": {"minecraft": "1.21.4", "fabric-loader": "0.16.9"},
+        "files": [
+            {
+                "path": "mods/lithium.jar",
+                "hashes": {"sha512": sha512, "sha1": "unused"},
+                "env": {"client": "required", "server": "required"},
+                "downloads": [format!("{}/lithium.jar", base)],
+                "fileSize": 4
+            },
+            {
+                "path": "mods/iris.jar",
+                "hashes": {"sha512": "00"},
+                "env": {"client": "required", "server": "unsupported"},
+                "downloads": ["http://127.0.0.1:1/iris.jar"],
+                "fileSize": 1
+            },
+            {
+                "path": "../escape.jar",
+                "hashes": {"sha512": "00"},
+                "downloads": ["http://127.0.0.1:1/escape.jar"],
+                "fileSize": 1
+            }
+        ]
+    });
+    let tmp = TempDir::new().unwrap();
+    let pack = tmp.path().join("pack.mrpack");
+    write_zip(
+        &pack,
+        &[
+            ("modrinth.index.json", index.to_string().as_bytes()),
+            ("overrides/config/a.properties", b"client"),
+            ("overrides/config/b.properties", b"shared"),
+            ("server-overrides/config/a.properties", b"server"),
+        ],
+    );
+    let dir = tmp.path().join("server");
+    fs::create_dir(&dir).unwrap();
+
+    let report = apply_mrpack(&pack, &dir).await.unwrap();
+    server.join().unwrap();
+
+    assert_eq!(
+        fs::read_to_string(dir.join("mods/lithium.jar")).unwrap(),
+        "JAR!"
+    );
+    assert!(!dir.join("mods/iris.jar").exists());
+    assert_eq!(report.skipped, vec!["mods/iris.jar (client only)"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, "../escape.jar");
+    assert_eq!(
+        fs::read_to_string(dir.join("config/a.properties")).unwrap(),
+        "server"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("config/b.properties")).unwrap(),
+        "shared"
+    );
+    assert!(read_lockfile(&dir)
+        .unwrap()
+        .find("mods/lithium.jar")
+        .is_some());
+}
+
+#[tokio::test]
+async fn apply_mrpack_rejects_bad_hash() {
+    let (base, server) =
+        mock_http_server("HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\nJAR!");
+    let index = serde_json::json!({
+        "formatVersion": 1,
+        "game": "minecraft",
+        "versionId": "1.0",
+        "name": "Pack",
+        "dependencies": {"minecraft": "1.21.4"},
+        "files": [{
+            "path": "mods/lithium.jar",
+            "hashes": {"sha1": "0000000000000000000000000000000000000000"},
+            "downloads": [format!("{}/lithium.jar", base)],
+            "fileSize": 4
+        }]
+    });
+    let tmp = TempDir::new().unwrap();
+    let pack = tmp.path().join("pack.mrpack");
+    write_zip(
+        &pack,
+        &[("modrinth.index.json", index.to_string().as_bytes())],
+    );
+
+    let report = apply_mrpack(&pack, &tmp.path().to_path_buf())
+        .await
+        .unwrap();
+    server.join().unwrap();
+
+    assert!(report.installed.is_empty());
+    assert_eq!(report.failed[0].1, "sha1 mismatch");
+    assert!(!tmp.path().join("mods/lithium.jar").exists());
+}
+
+// ── mrpack export ────────────────────────────────────────────────
+
+#[test]
+fn mrpack_dependencies_per_platform() {
+    let deps = mrpack_dependencies("Fabric", "1.21.4", Some("0.16.9")).unwrap();
+    assert_eq!(deps["minecraft"], "1.21.4");
+    assert_eq!(deps["fabric-loader"], "0.16.9");
+
+    let deps = mrpack_dependencies("Paper", "1.21.4", None).unwrap();
+    assert_eq!(deps.len(), 1);
+
+    assert!(mrpack_dependencies("Fabric", "1.21.4", None).is_err());
+    assert!(mrpack_dependencies("Forge", "1.20.1", None).is_err());
+}
+
+#[test]
+fn plan_mrpack_files_links_locked_and_bundles_unknown() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let mods = dir.join("mods");
+    let plugins = dir.join("plugins");
+    fs::create_dir_all(&mods).unwrap();
+    fs::create_dir_all(&plugins).unwrap();
+    fs::write(mods.join("lithium.jar"), b"lithium").unwrap();
+    fs::write(mods.join("custom.jar"), b"custom").unwrap();
+    fs::write(mods.join("notes.txt"), b"not a jar").unwrap();
+    fs::write(plugins.join("chunky.jar"), b"chunky").unwrap();
+
+    let cdn = |p: &str| format!("https://cdn.modrinth.com/data/{}/versions/v1/{}.jar", p, p);
+    for (install_dir, file, project) in [
+        (&mods, "lithium.jar", "lith"),
+        (&plugins, "chunky.jar", "chunk"),
+    ] {
+        record_install(
+            &dir,
+            install_dir,
+            file,
+            FileOrigin {
+                source: "modrinth".to_string(),
+                url: Some(cdn(project)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    }
+
+    let (files, report) = plan_mrpack_files(&dir, &HashMap::new()).unwrap();
+    assert_eq!(
+        report.linked,
+        vec!["mods/lithium.jar", "plugins/chunky.jar"]
+    );
+    assert_eq!(report.bundled, vec!["mods/custom.jar"]);
+    assert_eq!(report.warnings.len(), 1);
+
+    let lithium = files.iter().find(|f| f.path == "mods/lithium.jar").unwrap();
+    assert_eq!(lithium.downloads, vec![cdn("lith")]);
+    assert_eq!(lithium.file_size, 7);
+    assert!(lithium.env.is_none());
+    assert!(verify_hashes(&mods.join("lithium.jar"), &lithium.hashes).is_ok());
+
+    let chunky = files
+        .iter()
+        .find(|f| f.path == "plugins/chunky.jar")
+        .unwrap();
+    assert_eq!(chunky.env.as_ref().unwrap().client, "unsupported");
+}
+
+#[test]
+fn plan_mrpack_files_bundles_modified_locked_jar() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let mods = dir.join("mods");
+    fs::create_dir_all(&mods).unwrap();
+    fs::write(mods.join("lithium.jar"), b"lithium").unwrap();
+    record_install(
+        &dir,
+        &mods,
+        "lithium.jar",
+        FileOrigin {
+            source: "modrinth".to_string(),
+            url: Some("https://cdn.modrinth.com/data/a/versions/b/lithium.jar".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    fs::write(mods.join("lithium.jar"), b"patched").unwrap();
+
+    let (files, report) = plan_mrpack_files(&dir, &HashMap::new()).unwrap();
+    assert!(files.is_empty());
+    assert_eq!(report.bundled, vec!["mods/lithium.jar"]);
+}
+
+#[tokio::test]
+async fn exported_mrpack_round_trips() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().join("src");
+    fs::create_dir_all(dir.join("mods")).unwrap();
+    fs::create_dir_all(dir.join("config/sub")).unwrap();
+    fs::write(dir.join("mods/custom.jar"), b"custom").unwrap();
+    fs::write(dir.join("config/a.toml"), b"a = 1").unwrap();
+    fs::write(dir.join("config/sub/b.toml"), b"b = 2").unwrap();
+    fs::write(
+        dir.join("server.properties"),
+        "motd=Exported\nrcon.password=hunter2\n",
+    )
+    .unwrap();
+
+    let (files, report) = plan_mrpack_files(&dir, &HashMap::new()).unwrap();
+    let index = MrpackIndex {
+        format_version: 1,
+        game: "minecraft".to_string(),
+        version_id: "1.0.0".to_string(),
+        name: "Exported".to_string(),
+        summary: None,
+        files,
+        dependencies: mrpack_dependencies("Fabric", "1.21.4", Some("0.16.9")).unwrap(),
+    };
+    let pack = tmp.path().join("exported.mrpack");
+    write_mrpack(&dir, &pack, &index, &report.bundled).unwrap();
+
+    let read_back = read_mrpack_index(&pack).unwrap();
+    assert_eq!(read_back.name, "Exported");
+    assert_eq!(
+        mrpack_platform(&read_back).unwrap().2.as_deref(),
+        Some("0.16.9")
+    );
+
+    let dest = tmp.path().join("dest");
+    fs::create_dir(&dest).unwrap();
+    apply_mrpack(&pack, &dest).await.unwrap();
+    assert_eq!(fs::read(dest.join("mods/custom.jar")).unwrap(), b"custom");
+    assert_eq!(
+        fs::read_to_string(dest.join("config/sub/b.toml")).unwrap(),
+        "b = 2"
+    );
+    assert!(!dest.join("server.properties").exists());
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(&pack).unwrap()).unwrap();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).unwrap();
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut entry, &mut contents).unwrap();
+        assert!(
+            !contents.contains("hunter2"),
+            "{:?} leaks the RCON password",
+            entry.name()
+        );
+    }
+}
+
+// ── CurseForge modpacks ──────────────────────────────────────────
+
+fn curse_manifest(loaders: &str) -> CurseManifest {
+    serde_json::from_str(&format!(
+        r#"{{"minecraft":{{"version":"1.20.1","modLoaders":{}}},
+            "manifestType":"minecraftModpack","manifestVersion":1,"name":"All The Mods",
+            "version":"1.0","author":"someone",
+            "files":[{{"projectID":238222,"fileID":4712345,"required":true}},
+                     {{"projectID":32274,"fileID":4700000,"required":false}}],
+            "overrides":"overrides"}}"#,
+        loaders
+    ))
+    .unwrap()
+}
+
+#[test]
+fn curseforge_manifest_parses_files() {
+    let manifest = curse_manifest("[]");
+    assert_eq!(manifest.files.len(), 2);
+    assert_eq!(manifest.files[0].project_id, 238222);
+    assert_eq!(manifest.files[0].file_id, 4712345);
+    assert!(!manifest.files[1].required);
+}
+
+#[test]
+fn curseforge_platform_uses_primary_loader() {
+    let manifest = curse_manifest(
+        r#"[{"id":"forge-47.2.0","primary":false},{"id":"neoforge-47.1.99","primary":true}]"#,
+    );
+    assert_eq!(
+        curseforge_platform(&manifest).unwrap(),
+        (
+            "NeoForge".to_string(),
+            "1.20.1".to_string(),
+            Some("47.1.99".to_string())
+        )
+    );
+
+    let manifest = curse_manifest(r#"[{"id":"fabric-0.16.9"}]"#);
+    assert_eq!(curseforge_platform(&manifest).unwrap().0, "Fabric");
+
+    let manifest = curse_manifest("[]");
+    assert_eq!(curseforge_platform(&manifest).unwrap().0, "Vanilla");
+
+    let manifest = curse_manifest(r#"[{"id":"rift-1.0","primary":true}]"#);
+    assert!(curseforge_platform(&manifest).is_err());
+}
+
+#[test]
+fn curseforge_class_dirs() {
+    assert_eq!(
+        curseforge_class_dir(Some(6), "world"),
+        Ok(PathBuf::from("mods"))
+    );
+    assert_eq!(
+        curseforge_class_dir(Some(5), "world"),
+        Ok(PathBuf::from("plugins"))
+    );
+    assert_eq!(
+        curseforge_class_dir(Some(6945), "survival"),
+        Ok(PathBuf::from("survival").join("datapacks"))
+    );
+    assert!(curseforge_class_dir(Some(12), "world").is_err());
+    assert!(curseforge_class_dir(Some(17), "world").is_err());
+    assert!(curseforge_class_dir(Some(4471), "world").is_err());
+    assert_eq!(
+        curseforge_class_dir(None, "world"),
+        Ok(PathBuf::from("mods"))
+    );
+}
+
+#[test]
+fn loader_install_hint_links_the_installer() {
+    let hint = loader_install_hint("Forge", "1.20.1", "47.2.0");
+    assert!(hint.contains("/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-installer.jar"));
+    let hint = loader_install_hint("NeoForge", "1.21.1", "21.1.77");
+    assert!(hint.contains("/neoforge/21.1.77/neoforge-21.1.77-installer.jar"));
+}
+
+#[test]
+fn curseforge_manual_download_url() {
+    assert_eq!(
+        curseforge_file_url("https://www.curseforge.com/minecraft/mc-mods/jei/", 4712345),
+        "https://www.curseforge.com/minecraft/mc-mods/jei/files/4712345"
+    );
+}
+
+#[tokio::test]
+async fn curseforge_pack_without_required_files_applies_overrides() {
+    let tmp = TempDir::new().unwrap();
+    let pack = tmp.path().join("pack.zip");
+    let manifest = r#"{"minecraft":{"version":"1.20.1","modLoaders":[]},
+        "manifestType":"minecraftModpack","name":"Configs","overrides":"custom",
+        "files":[{"projectID":1,"fileID":2,"required":false}]}"#;
+    write_zip(
+        &pack,
+        &[
+            ("manifest.json", manifest.as_bytes()),
+            ("custom/config/a.toml", b"a = 1"),
+            ("overrides/config/ignored.toml", b"x"),
+        ],
+    );
+    let manifest = read_curseforge_manifest(&pack).unwrap().unwrap();
+    let dir = tmp.path().join("server");
+    fs::create_dir(&dir).unwrap();
+
+    let (report, manual) = apply_curseforge_pack(&pack, &manifest, &dir, "unused")
+        .await
+        .unwrap();
+    assert!(manual.is_empty());
+    assert_eq!(report.skipped, vec!["project 1 (optional)"]);
+    assert_eq!(
+        fs::read_to_string(dir.join("config/a.toml")).unwrap(),
+        "a = 1"
+    );
+    assert!(!dir.join("config/ignored.toml").exists());
+}
+
+#[test]
+fn server_pack_has_no_manifest() {
+    let tmp = TempDir::new().unwrap();
+    let pack = tmp.path().join("server.zip");
+    write_zip(&pack, &[("Pack Server/mods/a.jar", b"a")]);
+    assert!(read_curseforge_manifest(&pack).unwrap().is_none());
+}
+
+#[test]
+fn server_pack_root_detection() {
+    let tmp = TempDir::new().unwrap();
+    let wrapped = tmp.path().join("wrapped.zip");
+    write_zip(
+        &wrapped,
+        &[
+            ("Pack Server/mods/a.jar", b"a"),
+            ("Pack Server/variables.txt", b"MINECRAFT_VERSION=1.20.1"),
+        ],
+    );
+    assert_eq!(server_pack_root(&wrapped).unwrap(), "Pack Server/");
+
+    let flat = tmp.path().join("flat.zip");
+    write_zip(&flat, &[("mods/a.jar", b"a"), ("run.sh", b"#!/bin/sh")]);
+    assert_eq!(server_pack_root(&flat).unwrap(), "");
+
+    let dir = tmp.path().join("extracted");
+    extract_zip_dir(&wrapped, &server_pack_root(&wrapped).unwrap(), &dir).unwrap();
+    assert!(dir.join("mods/a.jar").exists());
+    assert_eq!(server_pack_game_version(&dir).as_deref(), Some("1.20.1"));
+}
+
+#[test]
+fn server_pack_platform_detection() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    assert_eq!(detect_server_pack_platform(&dir), None);
+    fs::write(dir.join("fabric-server-launch.jar"), b"").unwrap();
+    assert_eq!(detect_server_pack_platform(&dir), Some("Fabric"));
+    fs::create_dir_all(dir.join("libraries/net/minecraftforge")).unwrap();
+    assert_eq!(detect_server_pack_platform(&dir), Some("Forge"));
+    fs::write(dir.join("neoforge-21.1.77-installer.jar"), b"").unwrap();
+    assert_eq!(detect_server_pack_platform(&dir), Some("NeoForge"));
+}
+
+// ── URL / local file installs ────────────────────────────────────
+
+#[test]
+fn content_disposition_filenames() {
+    assert_eq!(
+        content_disposition_filename(r#"attachment; filename="sodium-0.6.jar""#).as_deref(),
+        Some("sodium-0.6.jar")
+    );
+    assert_eq!(
+        content_disposition_filename("attachment; filename=plain.jar").as_deref(),
+        Some("plain.jar")
+    );
+    assert_eq!(
+        content_disposition_filename(
+            r#"attachment; filename="fallback.jar"; filename*=UTF-8''My%20Mod%E2%9C%93.jar"#
+        )
+        .as_deref(),
+        Some("My Mod✓.jar")
+    );
+    assert_eq!(
+        content_disposition_filename(r#"attachment; filename="../../evil.jar""#).as_deref(),
+        Some("evil.jar")
+    );
+    assert_eq!(content_disposition_filename("inline"), None);
+}
+
+#[test]
+fn url_filenames() {
+    assert_eq!(
+        url_filename("https://example.com/files/My%20Pack.zip?token=1#x").as_deref(),
+        Some("My Pack.zip")
+    );
+    assert_eq!(url_filename("https://example.com/"), None);
+}
+
+#[test]
+fn validate_content_file_checks_kind() {
+    let tmp = TempDir::new().unwrap();
+    let jar = tmp.path().join("mod.jar");
+    write_zip(&jar, &[("fabric.mod.json", b"{}")]);
+    let pack = tmp.path().join("pack.zip");
+    write_zip(&pack, &[("pack.mcmeta", b"{}")]);
+    let not_zip = tmp.path().join("fake.jar");
+    fs::write(&not_zip, b"<html>nope</html>").unwrap();
+
+    assert!(validate_content_file(&jar, "mod.jar", "mod").is_ok());
+    assert!(validate_content_file(&jar, "mod.jar", "plugin").is_ok());
+    assert!(validate_content_file(&pack, "pack.zip", "datapack").is_ok());
+    assert!(validate_content_file(&jar, "mod.jar", "resourcepack").is_err());
+    assert!(validate_content_file(&jar, "mod.zip", "resourcepack")
+        .unwrap_err()
+        .contains("pack.mcmeta"));
+    assert!(validate_content_file(&not_zip, "fake.jar", "mod")
+        .unwrap_err()
+        .contains("not a valid"));
+}
+
+#[test]
+fn install_from_file_records_source() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().join("server");
+    let src = tmp.path().join("custom.jar");
+    write_zip(&src, &[("plugin.yml", b"name: Custom")]);
+
+    let name = install_from_file(&dir, &src, &dir.join("plugins"), "plugin").unwrap();
+    assert_eq!(name, "custom.jar");
+    assert!(dir.join("plugins/custom.jar").exists());
+    let lock = read_lockfile(&dir).unwrap();
+    let entry = lock.find("plugins/custom.jar").unwrap();
+    assert_eq!(entry.origin.source, "file");
+    assert!(entry.origin.url.as_ref().unwrap().starts_with("file://"));
+
+    // A second copy doesn't replace the installed one
+    write_zip(&src, &[("plugin.yml", b"name: Other")]);
+    let err = install_from_file(&dir, &src, &dir.join("plugins"), "plugin").unwrap_err();
+    assert!(err.to_string().contains("already exists"));
+    assert_ne!(
+        fs::read(dir.join("plugins/custom.jar")).unwrap(),
+        fs::read(&src).unwrap()
+    );
+
+    let bad = tmp.path().join("notes.txt");
+    fs::write(&bad, b"hi").unwrap();
+    assert!(install_from_file(&dir, &bad, &dir.join("plugins"), "plugin").is_err());
+}
+
+#[tokio::test]
+async fn install_from_url_uses_content_disposition() {
+    let tmp = TempDir::new().unwrap();
+    let jar = tmp.path().join("src.jar");
+    write_zip(&jar, &[("fabric.mod.json", b"{}")]);
+    let body = fs::read(&jar).unwrap();
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nContent-Disposition: attachment; filename=\"lithium-1.0.jar\"\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    let mut raw = response.into_bytes();
+    raw.extend_from_slice(&body);
+    let (base, server) = mock_http_server_bytes(raw);
+
+    let dir = tmp.path().join("server");
+    let url = format!("{}/download?id=42", base);
+    let name = install_from_url(&dir, &url, &dir.join("mods"), "mod")
+        .await
+        .unwrap();
+    server.join().unwrap();
+
+    assert_eq!(name, "lithium-1.0.jar");
+    assert!(dir.join("mods/lithium-1.0.jar").exists());
+    assert!(!dir.join("mods/.oxidemc-download.part").exists());
+    let lock = read_lockfile(&dir).unwrap();
+    let entry = lock.find("mods/lithium-1.0.jar").unwrap();
+    assert_eq!(entry.origin.source, "url");
+    assert_eq!(entry.origin.url.as_deref(), Some(url.as_str()));
+}
+
+#[tokio::test]
+async fn install_from_url_keeps_existing_file() {
+    let tmp = TempDir::new().unwrap();
+    let jar = tmp.path().join("src.jar");
+    write_zip(&jar, &[("fabric.mod.json", b"{}")]);
+    let body = fs::read(&jar).unwrap();
+    let mut raw = format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    raw.extend_from_slice(&body);
+    let (base, server) = mock_http_server_bytes(raw);
+
+    let dir = tmp.path().join("server");
+    fs::create_dir_all(dir.join("mods")).unwrap();
+    fs::write(dir.join("mods/lithium.jar"), b"installed").unwrap();
+    let err = install_from_url(
+        &dir,
+        &format!("{}/lithium.jar", base),
+        &dir.join("mods"),
+        "mod",
+    )
+    .await
+    .unwrap_err();
+    server.join().unwrap();
+
+    assert!(err.to_string().contains("already exists"));
+    assert_eq!(
+        fs::read(dir.join("mods/lithium.jar")).unwrap(),
+        b"installed"
+    );
+    assert_eq!(
+        list_entries(&dir.join("mods")).unwrap(),
+        vec!["lithium.jar"]
+    );
+}
+
+#[tokio::test]
+async fn install_from_url_rejects_non_archives() {
+    let (base, server) =
+        mock_http_server("HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello");
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let err = install_from_url(&dir, &format!("{}/mod.jar", base), &dir.join("mods"), "mod")
+        .await
+        .unwrap_err();
+    server.join().unwrap();
+    assert!(err.to_string().contains("not a valid"));
+    assert!(list_entries(&dir.join("mods")).unwrap().is_empty());
+}
+
+// ── jar metadata ─────────────────────────────────────────────────
+
+#[test]
+fn parses_fabric_mod_json() {
+    let m = parse_fabric_mod_json(
+        r#"{"schemaVersion":1,"id":"lithium","name":"Lithium","version":"0.14.3",
+            "depends":{"fabricloader":">=0.15","minecraft":["1.21","1.21.1"],"fabric-api":"*"},
+            "recommends":{"modmenu":"*"}}"#,
+    )
+    .unwrap();
+    assert_eq!(m.loader, "fabric");
+    assert_eq!(m.id, "lithium");
+    assert_eq!(m.name, "Lithium");
+    assert_eq!(m.version, "0.14.3");
+    assert_eq!(m.game_versions.as_deref(), Some("1.21 || 1.21.1"));
+    assert_eq!(m.dependencies.len(), 3);
+    assert!(
+        !m.dependencies
+            .iter()
+            .find(|d| d.id == "modmenu")
+            .unwrap()
+            .required
+    );
+}
+
+#[test]
+fn parses_quilt_mod_json() {
+    let m = parse_quilt_mod_json(
+        r#"{"schema_version":1,"quilt_loader":{"id":"qsl_mod","version":"1.0",
+            "metadata":{"name":"Quilt Mod"},
+            "depends":["quilted_fabric_api",{"id":"minecraft","versions":">=1.20"},
+                       {"id":"extra","optional":true}]}}"#,
+    )
+    .unwrap();
+    assert_eq!(m.loader, "quilt");
+    assert_eq!(m.name, "Quilt Mod");
+    assert_eq!(m.game_versions.as_deref(), Some(">=1.20"));
+    assert_eq!(
+        m.dependencies
+            .iter()
+            .map(|d| (d.id.as_str(), d.required))
+            .collect::<Vec<_>>(),
+        vec![("quilted_fabric_api", true), ("extra", false)]
+    );
+}
+
+#[test]
+fn parses_forge_and_neoforge_mods_toml() {
+    let forge = r#"
+modLoader="javafml"
+loaderVersion="[47,)"
+[[mods]]
+modId="jei"
+version="${file.jarVersion}"
+displayName="Just Enough Items"
+[[dependencies.jei]]
+modId="forge"
+mandatory=true
+versionRange="[47,)"
+[[dependencies.jei]]
+modId="minecraft"
+mandatory=true
+versionRange="[1.20.1,1.21)"
+[[dependencies.jei]]
+modId="optional_thing"
+mandatory=false
+"#;
+    let m = parse_mods_toml(
+        forge,
+        "forge",
+        Some("Manifest-Version: 1.0\r\nImplementation-Version: 15.2.0.27\r\n"),
+    )
+    .unwrap();
+    assert_eq!(m.id, "jei");
+    assert_eq!(m.name, "Just Enough Items");
+    assert_eq!(m.version, "15.2.0.27");
+    assert_eq!(m.game_versions.as_deref(), Some("[1.20.1,1.21)"));
+    assert_eq!(m.dependencies.len(), 2);
+    assert!(!m.dependencies[1].required);
+
+    let neoforge = r#"
+[[mods]]
+modId="sophisticated"
+version="1.0"
+[[dependencies.sophisticated]]
+modId="core"
+type="required"
+[[dependencies.sophisticated]]
+modId="curios"
+type="optional"
+"#;
+    let m = parse_mods_toml(neoforge, "neoforge", None).unwrap();
+    assert_eq!(m.name, "sophisticated");
+    assert!(m.dependencies[0].required);
+    assert!(!m.dependencies[1].required);
+}
+
+#[test]
+fn parses_plugin_yml_and_paper_plugin_yml() {
+    let m = parse_plugin_yml(
+        "name: Essentials\nversion: 2.20.1\nmain: x.Y\napi-version: 1.13\ndepend: [Vault]\nsoftdepend: [LuckPerms]\n",
+        "bukkit",
+    )
+    .unwrap();
+    assert_eq!(m.id, "Essentials");
+    assert_eq!(m.version, "2.20.1");
+    assert_eq!(m.game_versions.as_deref(), Some("1.13"));
+    assert_eq!(m.dependencies.len(), 2);
+    assert!(m.dependencies[0].required);
+
+    let m = parse_plugin_yml(
+        "name: Modern\nversion: '1.0'\napi-version: '1.20'\ndependencies:\n  server:\n    Vault:\n      load: BEFORE\n      required: false\n    ProtocolLib: {}\n",
+        "paper",
+    )
+    .unwrap();
+    assert_eq!(m.loader, "paper");
+    assert_eq!(m.dependencies.len(), 2);
+    assert!(
+        !m.dependencies
+            .iter()
+            .find(|d| d.id == "Vault")
+            .unwrap()
+            .required
+    );
+    assert!(
+        m.dependencies
+            .iter()
+            .find(|d| d.id == "ProtocolLib")
+            .unwrap()
+            .required
+    );
+}
+
+#[test]
+fn reads_metadata_from_jar_with_nested_jars() {
+    use std::io::Write;
+    let tmp = TempDir::new().unwrap();
+    let inner = tmp.path().join("inner.jar");
+    write_zip(
+        &inner,
+        &[(
+            "fabric.mod.json",
+            br#"{"id":"fabric-api-base","version":"1"}"#,
+        )],
+    );
+    let outer = tmp.path().join("fabric-api.jar");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&outer).unwrap());
+    let opts = zip::write::SimpleFileOptions::default();
+    zip.start_file("fabric.mod.json", opts).unwrap();
+    zip.write_all(
+        br#"{"id":"fabric-api","version":"0.100","provides":["fabric"],
+             "jars":[{"file":"META-INF/jars/inner.jar"}]}"#,
+    )
+    .unwrap();
+    zip.start_file("META-INF/jars/inner.jar", opts).unwrap();
+    zip.write_all(&fs::read(&inner).unwrap()).unwrap();
+    zip.finish().unwrap();
+
+    let m = read_jar_metadata(&outer).unwrap().unwrap();
+    assert_eq!(m.id, "fabric-api");
+    assert_eq!(m.provides, vec!["fabric", "fabric-api-base"]);
+
+    let plain = tmp.path().join("plain.jar");
+    write_zip(&plain, &[("a.class", b"")]);
+    assert!(read_jar_metadata(&plain).unwrap().is_none());
+}
+
+#[test]
+fn game_version_support() {
+    let meta = |loader: &str, range: &str| ContentMetadata {
+        loader: loader.to_string(),
+        game_versions: Some(range.to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        supports_game_version(&meta("fabric", ">=1.20 <1.21"), "1.20.4"),
+        Some(true)
+    );
+    assert_eq!(
+        supports_game_version(&meta("fabric", ">=1.20 <1.21"), "1.21"),
+        Some(false)
+    );
+    assert_eq!(
+        supports_game_version(&meta("fabric", "~1.20.1"), "1.20.6"),
+        Some(true)
+    );
+    assert_eq!(
+        supports_game_version(&meta("fabric", "~1.20.1"), "1.21"),
+        Some(false)
+    );
+    assert_eq!(
+        supports_game_version(&meta("fabric", "1.21.x"), "1.21.4"),
+        Some(true)
+    );
+    assert_eq!(
+        supports_game_version(&meta("fabric", "1.20 || 1.21.1"), "1.21.1"),
+        Some(true)
+    );
+    assert_eq!(
+        supports_game_version(&meta("fabric", "*"), "1.8.9"),
+        Some(true)
+    );
+    assert_eq!(
+        supports_game_version(&meta("forge", "[1.20.1,1.21)"), "1.20.1"),
+        Some(true)
+    );
+    assert_eq!(
+        supports_game_version(&meta("forge", "[1.20.1,1.21)"), "1.21"),
+        Some(false)
+    );
+    assert_eq!(
+        supports_game_version(&meta("forge", "[1.18,1.19),[1.20,)"), "1.21.4"),
+        Some(true)
+    );
+    assert_eq!(
+        supports_game_version(&meta("neoforge", "[1.21.1]"), "1.21"),
+        Some(false)
+    );
+    assert_eq!(
+        supports_game_version(&meta("bukkit", "1.20"), "1.21.4"),
+        Some(true)
+    );
+    assert_eq!(
+        supports_game_version(&meta("bukkit", "1.21"), "1.20.6"),
+        Some(false)
+    );
+    assert_eq!(
+        supports_game_version(&meta("fabric", "${mc}"), "1.21"),
+        None
+    );
+    assert_eq!(
+        supports_game_version(&ContentMetadata::default(), "1.21"),
+        None
+    );
+}
+
+#[test]
+fn compatibility_warnings_cover_loader_version_and_deps() {
+    let m = ContentMetadata {
+        loader: "fabric".to_string(),
+        id: "sodium".to_string(),
+        name: "Sodium".to_string(),
+        game_versions: Some(">=1.21".to_string()),
+        dependencies: vec![
+            DeclaredDependency {
+                id: "fabricloader".to_string(),
+                versions: ">=0.15".to_string(),
+                required: true,
+            },
+            DeclaredDependency {
+                id: "fabric-api".to_string(),
+                versions: String::new(),
+                required: true,
+            },
+            DeclaredDependency {
+                id: "modmenu".to_string(),
+                versions: String::new(),
+                required: false,
+            },
+        ],
+        ..Default::default()
+    };
+    assert!(compatibility_warnings(
+        "sodium.jar",
+        &m,
+        "Fabric",
+        "1.21.4",
+        &["fabric-api".to_string()]
+    )
+    .is_empty());
+
+    let warnings = compatibility_warnings("sodium.jar", &m, "Paper", "1.20.4", &[]);
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings[0].contains("Paper servers can't load"));
+    assert!(warnings[1].contains("does not support Minecraft 1.20.4"));
+    assert!(warnings[2].contains("requires fabric-api"));
+}
+
+#[test]
+fn scan_mods_includes_jar_metadata() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::create_dir_all(dir.join("plugins")).unwrap();
+    write_zip(
+        &dir.join("plugins/Vault.jar"),
+        &[("plugin.yml", b"name: Vault\nversion: 1.7.3\n")],
+    );
+    fs::write(dir.join("plugins/readme.txt"), b"x").unwrap();
+
+    let scanned = scan_mods(&dir);
+    let vault = scanned
+        .plugins
+        .iter()
+        .find(|e| e.name == "Vault.jar")
+        .unwrap();
+    assert_eq!(vault.metadata.as_ref().unwrap().version, "1.7.3");
+    let readme = scanned
+        .plugins
+        .iter()
+        .find(|e| e.name == "readme.txt")
+        .unwrap();
+    assert!(readme.metadata.is_none());
+}
+
+// ── doctor ───────────────────────────────────────────────────────
+
+#[test]
+fn metadata_reads_environment_and_incompatibilities() {
+    let m = parse_fabric_mod_json(
+        r#"{"id":"iris","version":"1.7","environment":"client",
+            "breaks":{"optifabric":"*"},"conflicts":{"sodium":"<0.5"}}"#,
+    )
+    .unwrap();
+    assert_eq!(m.environment.as_deref(), Some("client"));
+    assert_eq!(m.incompatibilities.len(), 2);
+    assert!(
+        m.incompatibilities
+            .iter()
+            .find(|i| i.id == "optifabric")
+            .unwrap()
+            .required
+    );
+
+    let m = parse_quilt_mod_json(
+        r#"{"quilt_loader":{"id":"q","version":"1","breaks":["bad"]},
+            "minecraft":{"environment":"dedicated_server"}}"#,
+    )
+    .unwrap();
+    assert_eq!(m.environment.as_deref(), Some("server"));
+    assert_eq!(m.incompatibilities[0].id, "bad");
+
+    let m = parse_mods_toml(
+        r#"
+[[mods]]
+modId="minimap"
+version="1"
+[[dependencies.minimap]]
+modId="minecraft"
+mandatory=true
+side="CLIENT"
+[[dependencies.minimap]]
+modId="othermap"
+type="incompatible"
+"#,
+        "neoforge",
+        None,
+    )
+    .unwrap();
+    assert_eq!(m.environment.as_deref(), Some("client"));
+    assert_eq!(m.incompatibilities[0].id, "othermap");
+    assert!(m.dependencies.is_empty());
+
+    let m = parse_mods_toml(
+        "clientSideOnly=true\n[[mods]]\nmodId=\"x\"\n",
+        "forge",
+        None,
+    )
+    .unwrap();
+    assert_eq!(m.environment.as_deref(), Some("client"));
+}
+
+fn doctor_mod(id: &str, version: &str) -> ContentMetadata {
+    ContentMetadata {
+        loader: "fabric".to_string(),
+        id: id.to_string(),
+        name: id.to_string(),
+        version: version.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn doctor_flags_each_kind_of_problem() {
+    let mut iris = doctor_mod("iris", "1.7");
+    iris.environment = Some("client".to_string());
+    let mut needs_api = doctor_mod("needs-api", "1.0");
+    needs_api.dependencies.push(DeclaredDependency {
+        id: "fabric-api".to_string(),
+        versions: "*".to_string(),
+        required: true,
+    });
+    needs_api.dependencies.push(DeclaredDependency {
+        id: "minecraft".to_string(),
+        versions: String::new(),
+        required: true,
+    });
+    let mut breaker = doctor_mod("breaker", "1.0");
+    breaker.incompatibilities.push(DeclaredDependency {
+        id: "lithium".to_string(),
+        versions: "<0.12".to_string(),
+        required: true,
+    });
+    let mut old_breaker = doctor_mod("old-breaker", "1.0");
+    old_breaker.incompatibilities.push(DeclaredDependency {
+        id: "lithium".to_string(),
+        versions: "<0.10".to_string(),
+        required: true,
+    });
+
+    let content = vec![
+        ("mods/iris.jar".to_string(), iris),
+        ("mods/needs-api.jar".to_string(), needs_api),
+        (
+            "mods/lithium-0.11.jar".to_string(),
+            doctor_mod("lithium", "0.11"),
+        ),
+        (
+            "mods/lithium-0.9.jar".to_string(),
+            doctor_mod("lithium", "0.9"),
+        ),
+        ("mods/breaker.jar".to_string(), breaker),
+        ("mods/old-breaker.jar".to_string(), old_breaker),
+    ];
+    let remote = HashSet::from(["mods/iris.jar".to_string(), "mods/minimap.jar".to_string()]);
+    let issues = diagnose(&content, &remote);
+
+    let of = |kind: IssueKind| -> Vec<&DoctorIssue> {
+        issues.iter().filter(|i| i.kind == kind).collect()
+    };
+    let client = of(IssueKind::ClientOnly);
+    assert_eq!(client.len(), 2);
+    assert_eq!(client[0].files, vec!["mods/iris.jar"]);
+    assert_eq!(client[1].files, vec!["mods/minimap.jar"]);
+
+    let duplicates = of(IssueKind::DuplicateId);
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].files, vec!["mods/lithium-0.9.jar"]);
+
+    let missing = of(IssueKind::MissingDependency);
+    assert_eq!(missing.len(), 1);
+    assert!(missing[0].message.contains("fabric-api"));
+
+    // Only the break whose version range matches an installed lithium counts.
+    let incompatible = of(IssueKind::Incompatible);
+    assert_eq!(incompatible.len(), 1);
+    assert_eq!(incompatible[0].files, vec!["mods/breaker.jar"]);
+}
+
+#[test]
+fn doctor_disables_offending_files() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let mods = dir.join("mods");
+    fs::create_dir_all(&mods).unwrap();
+    write_zip(
+        &mods.join("iris.jar"),
+        &[(
+            "fabric.mod.json",
+            br#"{"id":"iris","version":"1","environment":"client"}"#,
+        )],
+    );
+    write_zip(
+        &mods.join("lithium.jar"),
+        &[(
+            "fabric.mod.json",
+            br#"{"id":"lithium","version":"1","environment":"*"}"#,
+        )],
+    );
+    record_install(
+        &dir,
+        &mods,
+        "iris.jar",
+        FileOrigin {
+            source: "url".to_string(),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let issues = diagnose(&scan_content(&dir), &HashSet::new());
+    assert_eq!(issues.len(), 1);
+    let (disabled, failed) = disable_issue_files(&dir, &issues);
+    assert_eq!(disabled, vec!["mods/iris.jar"]);
+    assert!(failed.is_empty());
+    assert!(mods.join("iris.jar.disabled").exists());
+    assert!(!mods.join("iris.jar").exists());
+    assert!(mods.join("lithium.jar").exists());
+
+    let lock = read_lockfile(&dir).unwrap();
+    assert!(lock.find("mods/iris.jar").is_none());
+    assert!(lock.find("mods/iris.jar.disabled").is_some());
+    // Disabled jars are no longer scanned.
+    assert!(diagnose(&scan_content(&dir), &HashSet::new()).is_empty());
+}
+
+// ── enable / disable / bisect ────────────────────────────────────
+
+#[test]
+fn enable_disable_round_trip() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let mods = dir.join("mods");
+    fs::create_dir_all(&mods).unwrap();
+    fs::write(mods.join("a.jar"), b"a").unwrap();
+    fs::write(mods.join("b.jar"), b"b").unwrap();
+    fs::write(mods.join("notes.txt"), b"x").unwrap();
+    record_install(
+        &dir,
+        &mods,
+        "a.jar",
+        FileOrigin {
+            source: "url".to_string(),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        disable_content(&dir, &mods, "a.jar").unwrap(),
+        "a.jar.disabled"
+    );
+    let states = list_content(&mods).unwrap();
+    assert_eq!(
+        states,
+        vec![
+            ContentState {
+                name: "a.jar".to_string(),
+                enabled: false
+            },
+            ContentState {
+                name: "b.jar".to_string(),
+                enabled: true
+            },
+        ]
+    );
+    assert_eq!(states[0].file_name(), "a.jar.disabled");
+    assert!(read_lockfile(&dir)
+        .unwrap()
+        .find("mods/a.jar.disabled")
+        .is_some());
+
+    assert_eq!(
+        enable_content(&dir, &mods, "a.jar.disabled").unwrap(),
+        "a.jar"
+    );
+    assert!(mods.join("a.jar").exists());
+    assert!(read_lockfile(&dir).unwrap().find("mods/a.jar").is_some());
+
+    // Refuse to clobber an enabled copy.
+    fs::write(mods.join("b.jar.disabled"), b"old b").unwrap();
+    assert!(enable_content(&dir, &mods, "b.jar.disabled").is_err());
+}
+
+fn fabric_jar(path: &std::path::Path, id: &str, depends: &[&str]) {
+    let deps: Vec<String> = depends.iter().map(|d| format!("\"{}\":\"*\"", d)).collect();
+    let json = format!(
+        r#"{{"id":"{}","version":"1","depends":{{{}}}}}"#,
+        id,
+        deps.join(",")
+    );
+    write_zip(path, &[("fabric.mod.json", json.as_bytes())]);
+}
+
+#[test]
+fn bisect_finds_culprit_and_restores() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let mods = dir.join("mods");
+    fs::create_dir_all(&mods).unwrap();
+    for id in ["a", "b", "c", "d", "e", "f"] {
+        fabric_jar(&mods.join(format!("{}.jar", id)), id, &[]);
+    }
+    let culprit = "e.jar";
+
+    let mut outcome = start_bisect(&dir, "mods").unwrap();
+    let mut rounds = 0;
+    let found = loop {
+        match outcome {
+            BisectOutcome::Continue(state) => {
+                rounds += 1;
+                assert!(rounds < 6);
+                assert!(read_bisect(&dir).is_some());
+                let crashed = mods.join(culprit).exists();
+                assert_eq!(crashed, !state.disabled.contains(&culprit.to_string()));
+                outcome = bisect_step(&dir, crashed).unwrap();
+            }
+            BisectOutcome::Found(name) => break name,
+        }
+    };
+    assert_eq!(found, culprit);
+    assert!(read_bisect(&dir).is_none());
+    assert!(list_content(&mods).unwrap().iter().all(|c| c.enabled));
+}
+
+#[test]
+fn bisect_with_one_jar_confirms_it_first() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let mods = dir.join("mods");
+    fs::create_dir_all(&mods).unwrap();
+    fabric_jar(&mods.join("only.jar"), "only", &[]);
+
+    let BisectOutcome::Continue(state) = start_bisect(&dir, "mods").unwrap() else {
+        panic!("expected a confirmation run");
+    };
+    assert_eq!(state.disabled, vec!["only.jar"]);
+    assert!(mods.join("only.jar.disabled").exists());
+    assert_eq!(
+        bisect_step(&dir, false).unwrap(),
+        BisectOutcome::Found("only.jar".to_string())
+    );
+    assert!(mods.join("only.jar").exists());
+
+    // Still crashing without it: the jar isn't to blame
+    start_bisect(&dir, "mods").unwrap();
+    assert!(bisect_step(&dir, true).is_err());
+    assert!(read_bisect(&dir).is_none());
+    assert!(mods.join("only.jar").exists());
+}
+
+#[test]
+fn bisect_disables_dependents_with_their_library() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let mods = dir.join("mods");
+    fs::create_dir_all(&mods).unwrap();
+    fabric_jar(&mods.join("a-lib.jar"), "lib", &[]);
+    fabric_jar(&mods.join("b.jar"), "b", &[]);
+    fabric_jar(&mods.join("c.jar"), "c", &[]);
+    fabric_jar(&mods.join("d-uses-lib.jar"), "uses", &["lib", "minecraft"]);
+
+    let BisectOutcome::Continue(state) = start_bisect(&dir, "mods").unwrap() else {
+        panic!("expected another round");
+    };
+    assert_eq!(state.disabled, vec!["a-lib.jar", "b.jar", "d-uses-lib.jar"]);
+    assert!(mods.join("d-uses-lib.jar.disabled").exists());
+    assert!(start_bisect(&dir, "mods").is_err());
+
+    abort_bisect(&dir).unwrap();
+    assert!(read_bisect(&dir).is_none());
+    assert!(list_content(&mods).unwrap().iter().all(|c| c.enabled));
+}
+
+// ── Modrinth search ──────────────────────────────────────────────
+
+#[test]
+fn truncate_chars_never_splits_multibyte_text() {
+    let text = "Ein Mod für Server – schnell, leicht und überall einsetzbar ✓✓✓";
+    let cut = truncate_chars(text, 45);
+    assert_eq!(cut.chars().count(), 45);
+    assert!(cut.ends_with('…'));
+    assert_eq!(truncate_chars("short", 45), "short");
+    assert_eq!(truncate_chars("日本語のテキスト", 4), "日本語…");
+}
+
+#[test]
+fn format_count_groups_thousands() {
+    assert_eq!(format_count(0), "0");
+    assert_eq!(format_count(999), "999");
+    assert_eq!(format_count(1000), "1,000");
+    assert_eq!(format_count(1234567), "1,234,567");
+}
+
+#[test]
+fn modrinth_facets_include_categories_and_side() {
+    let mut search = ModrinthSearch::new("lag", ProjectType::Mod);
+    search.loader = Some("fabric".to_string());
+    search.categories = vec!["optimization".to_string(), "utility".to_string()];
+    search.side = SideFilter::ServerOnly;
+    let facets = modrinth_facets(&search);
+    assert_eq!(facets[0], vec![Facet::ProjectType(ProjectType::Mod)]);
+    assert!(facets.contains(&vec![Facet::Categories("fabric".to_string())]));
+    assert!(facets.contains(&vec![Facet::Categories("optimization".to_string())]));
+    assert!(facets.contains(&vec![Facet::Categories("utility".to_string())]));
+    assert!(facets.contains(&vec![Facet::Custom {
+        _type: "client_side".to_string(),
+        operation: "=".to_string(),
+        value: "unsupported".to_string(),
+    }]));
+    assert!(facets.contains(&vec![Facet::Custom {
+        _type: "server_side".to_string(),
+        operation: "!=".to_string(),
+        value: "unsupported".to_string(),
+    }]));
+
+    let plain = ModrinthSearch::new("lag", ProjectType::Plugin);
+    assert_eq!(modrinth_facets(&plain).len(), 1);
+}
+
+#[test]
+fn search_page_counts_pages() {
+    let page = |page, total_hits| SearchPage::<SearchHit> {
+        hits: vec![],
+        page,
+        total_hits,
+    };
+    assert_eq!(page(0, 0).page_count(), 1);
+    assert!(!page(0, 0).has_next());
+    assert_eq!(page(0, 25).page_count(), 3);
+    assert!(page(1, 25).has_next());
+    assert!(!page(2, 25).has_next());
+}
+
+#[test]
+fn game_version_span_skips_snapshots() {
+    let versions: Vec<String> = ["1.20.1", "23w31a", "1.19.4", "1.21", "1.20.10"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert_eq!(game_version_span(&versions), "1.19.4 – 1.21 (4 releases)");
+    assert_eq!(game_version_span(&["1.21".to_string()]), "1.21");
+    assert_eq!(game_version_span(&["24w10a".to_string()]), "24w10a");
+}
+
+#[test]
+fn modrinth_details_summarise_a_hit() {
+    let hit: SearchHit = serde_json::from_value(serde_json::json!({
+        "slug": "lithium",
+        "title": "Lithium",
+        "description": "No-compromises game logic optimization",
+        "categories": ["optimization"],
+        "client_side": "optional",
+        "server_side": "required",
+        "project_type": "mod",
+        "downloads": 12345678,
+        "icon_url": null,
+        "color": null,
+        "thread_id": null,
+        "monetization_status": null,
+        "project_id": "gvQqBUqZ",
+        "author": "jellysquid3",
+        "display_categories": ["optimization"],
+        "versions": ["1.20.1", "1.21"],
+        "follows": 4200,
+        "date_created": "2020-01-01T00:00:00Z",
+        "date_modified": "2024-06-02T12:00:00Z",
+        "latest_version": "1.21",
+        "license": "LGPL-3.0-only",
+        "gallery": [],
+        "featured_gallery": null
+    }))
+    .unwrap();
+    let details = modrinth_details(&hit);
+    assert!(details.contains("Lithium by jellysquid3"));
+    assert!(details.contains("Downloads: 12,345,678 · Followers: 4,200"));
+    assert!(details.contains("Minecraft: 1.20.1 – 1.21 (2 releases)"));
+    assert!(details.contains("Server: required · Client: optional"));
+    assert!(details.contains("License: LGPL-3.0-only"));
+    assert!(details.contains("Updated: 2024-06-02"));
+}
+
+// ── CurseForge search ────────────────────────────────────────────
+
+#[test]
+fn curseforge_search_url_carries_filters() {
+    let search = CurseForgeSearch {
+        query: "just enough items".to_string(),
+        class_id: curseforge_class_id("mod"),
+        game_version: Some("1.20.1".to_string()),
+        loader: curseforge_loader("Forge", "mod"),
+        page: 2,
+    };
+    let url = curseforge_search_url("https://cf.example/v1", &search);
+    assert!(url.starts_with("https://cf.example/v1/mods/search?gameId=432&classId=6"));
+    assert!(url.contains("searchFilter=just+enough+items"));
+    assert!(url.contains("index=20&pageSize=10"));
+    assert!(url.contains("gameVersion=1.20.1"));
+    assert!(url.contains("modLoaderType=1"));
+
+    let plugins = CurseForgeSearch {
+        loader: curseforge_loader("Paper", "plugin"),
+        class_id: curseforge_class_id("plugin"),
+        game_version: None,
+        ..search
+    };
+    let url = curseforge_search_url("https://cf.example/v1", &plugins);
+    assert!(url.contains("classId=5"));
+    assert!(!url.contains("modLoaderType"));
+    assert!(!url.contains("gameVersion"));
+}
+
+#[test]
+fn curseforge_class_ids_round_trip() {
+    for content_type in ["mod", "plugin", "resourcepack", "datapack"] {
+        assert_eq!(
+            curseforge_content_type(curseforge_class_id(content_type)),
+            content_type
+        );
+    }
+}
+
+fn curse_file(
+    id: i32,
+    versions: &[&str],
+    date: &str,
+    release_type: u8,
+) -> furse::structures::file_structs::File {
+    serde_json::from_value(serde_json::json!({
+        "id": id,
+        "gameId": 432,
+        "modId": 1,
+        "isAvailable": true,
+        "displayName": format!("file {}", id),
+        "fileName": format!("file-{}.jar", id),
+        "releaseType": release_type,
+        "fileStatus": 4,
+        "hashes": [],
+        "fileDate": date,
+        "fileLength": 1,
+        "downloadCount": 0,
+        "downloadUrl": null,
+        "gameVersions": versions,
+        "sortableGameVersions": [],
+        "dependencies": [],
+        "fileFingerprint": 0
+    }))
+    .unwrap()
+}
+
+#[test]
+fn pick_curseforge_file_never_falls_back_to_another_version() {
+    let files = vec![curse_file(
+        1,
+        &["1.21", "Fabric"],
+        "2024-06-01T00:00:00Z",
+        1,
+    )];
+    assert!(pick_curseforge_file(&files, "1.20.1", Some(ModLoaderType::Fabric)).is_none());
+}
+
+#[test]
+fn pick_curseforge_file_matches_loader_and_prefers_releases() {
+    let files = vec![
+        curse_file(1, &["1.20.1", "Forge"], "2024-01-01T00:00:00Z", 1),
+        curse_file(2, &["1.20.1", "Fabric"], "2024-02-01T00:00:00Z", 1),
+        curse_file(3, &["1.20.1", "Fabric"], "2024-03-01T00:00:00Z", 2),
+        curse_file(4, &["1.20.1", "Fabric"], "2023-12-01T00:00:00Z", 1),
+    ];
+    let pick = |loader| pick_curseforge_file(&files, "1.20.1", loader).map(|f| f.id);
+    assert_eq!(pick(Some(ModLoaderType::Fabric)), Some(2));
+    assert_eq!(pick(Some(ModLoaderType::Forge)), Some(1));
+    assert_eq!(pick(Some(ModLoaderType::Quilt)), None);
+
+    // Plugins and resource packs aren't tagged with a loader
+    let untagged = vec![curse_file(5, &["1.20.1"], "2024-01-01T00:00:00Z", 1)];
+    let picked = pick_curseforge_file(&untagged, "1.20.1", Some(ModLoaderType::Forge));
+    assert_eq!(picked.map(|f| f.id), Some(5));
+}
+
+#[test]
+fn curseforge_file_fits_loader_only_checks_tagged_files() {
+    let fabric = curse_file(1, &["1.21.4", "Fabric"], "2024-06-01T00:00:00Z", 1);
+    assert!(curseforge_file_fits_loader(&fabric, Some("fabric")));
+    assert!(!curseforge_file_fits_loader(&fabric, Some("forge")));
+    // Bukkit plugins are rarely tagged at all, let alone with "paper"
+    let plugin = curse_file(2, &["1.21.4", "Bukkit"], "2024-06-01T00:00:00Z", 1);
+    assert!(curseforge_file_fits_loader(&plugin, Some("paper")));
+}
+
+// ── properties files ─────────────────────────────────────────────
+
+#[test]
+fn properties_parse_java_forms() {
+    let props = Properties::parse(
+        "a=1\nb = 2\nc:3\nd 4\n  e\t=\t5\nf=\nlevel-type=minecraft\\:normal\nkey\\ with\\ spaces=x\n",
+    );
+    assert_eq!(props.get("a"), Some("1"));
+    assert_eq!(props.get("b"), Some("2"));
+    assert_eq!(props.get("c"), Some("3"));
+    assert_eq!(props.get("d"), Some("4"));
+    assert_eq!(props.get("e"), Some("5"));
+    assert_eq!(props.get("f"), Some(""));
+    assert_eq!(props.get("level-type"), Some("minecraft:normal"));
+    assert_eq!(props.get("key with spaces"), Some("x"));
+}
+
+#[test]
+fn properties_parse_escapes_and_continuations() {
+    let props = Properties::parse(
+        "motd=\\u00A7aHello\\tWorld \\uD83D\\uDE00\nlong=one, \\\n    two, \\\n    three\npath=C:\\\\server\\\\\nnext=ok\n",
+    );
+    assert_eq!(props.get("motd"), Some("§aHello\tWorld 😀"));
+    assert_eq!(props.get("long"), Some("one, two, three"));
+    // An even number of trailing backslashes is an escaped backslash, not a continuation
+    assert_eq!(props.get("path"), Some("C:\\server\\"));
+    assert_eq!(props.get("next"), Some("ok"));
+}
+
+#[test]
+fn properties_round_trip_untouched_text() {
+    let text = "#Minecraft server properties\n#Mon Jan 01 00:00:00 UTC 2024\n\n! bang comment\nmotd = Spaced \\\n  out\nlevel-type=minecraft\\:normal\npvp:true\n";
+    assert_eq!(Properties::parse(text).to_string(), text);
+}
+
+#[test]
+fn properties_set_keeps_order_and_escapes() {
+    let mut props = Properties::parse("# header\na=1\nlevel-type=minecraft\\:normal\nb=2\n");
+    props.set("level-type", "minecraft:flat");
+    props.set("motd", "§6Gold server!");
+    props.set("a", "1");
+    assert_eq!(
+        props.to_string(),
+        "# header\na=1\nlevel-type=minecraft\\:flat\nb=2\nmotd=\\u00A76Gold server\\!\n"
+    );
+    assert_eq!(
+        Properties::parse(&props.to_string()).get("motd"),
+        Some("§6Gold server!")
+    );
+}
+
+#[test]
+fn properties_duplicates_last_wins_and_set_collapses() {
+    let mut props = Properties::parse("a=1\nb=2\na=3\n");
+    assert_eq!(props.get("a"), Some("3"));
+    assert_eq!(props.entries(), vec![("a", "3"), ("b", "2")]);
+    props.set("a", "4");
+    assert_eq!(props.to_string(), "a=4\nb=2\n");
+    assert_eq!(props.remove("b"), Some("2".to_string()));
+    assert_eq!(props.to_string(), "a=4\n");
+}
+
+#[test]
+fn escape_leading_space_and_control_chars() {
+    assert_eq!(escape(" padded", false), "\\ padded");
+    assert_eq!(escape("a b", false), "a b");
+    assert_eq!(escape("a b", true), "a\\ b");
+    assert_eq!(escape("line\nbreak", false), "line\\nbreak");
+    assert_eq!(unescape(&escape("#!=:\\ é😀", false)), "#!=:\\ é😀");
+}
+
+#[test]
+fn update_properties_batch_writes_once() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("server.properties");
+    fs::write(
+        &path,
+        "# keep me\ndifficulty=easy\nmotd=A Minecraft Server\n",
+    )
+    .unwrap();
+    update_properties(&path, &[("difficulty", "hard"), ("max-players", "50")]).unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "# keep me\ndifficulty=hard\nmotd=A Minecraft Server\nmax-players=50\n"
+    );
+    let leftovers: Vec<_> = fs::read_dir(tmp.path()).unwrap().collect();
+    assert_eq!(leftovers.len(), 1);
+}
+
+#[test]
+fn read_property_unescapes_values() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(
+        dir.join("server.properties"),
+        "motd = \\u00A7bWelcome\nlevel-type=minecraft\\:normal\n",
+    )
+    .unwrap();
+    assert_eq!(
+        read_property(&dir, "server.properties", "motd").unwrap(),
+        "§bWelcome"
+    );
+    assert_eq!(
+        read_property(&dir, "server.properties", "level-type").unwrap(),
+        "minecraft:normal"
+    );
+}
+
+#[test]
+fn load_preset_unescapes_legacy_values() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(
+        dir.join("server.properties"),
+        "level-type=minecraft\\:normal\n",
+    )
+    .unwrap();
+    let preset_json = r#"{
+        "info": {
+            "name": "Old Flat",
+            "compatible_versions": ["1.21.*"],
+            "compatible_platforms": ["Paper"]
+        },
+        "settings": { "server.properties": { "level-type": "minecraft\\:flat" } }
+    }"#;
+    let preset_path = dir.join("old.json");
+    fs::write(&preset_path, preset_json).unwrap();
+    load_preset(&dir, &preset_path, "Paper", "1.21.4").unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("server.properties")).unwrap(),
+        "level-type=minecraft\\:flat\n"
+    );
+}
+
+#[test]
+fn load_preset_keeps_backslashes_in_current_format() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("server.properties"), "motd=A Minecraft Server\n").unwrap();
+    let preset_json = r#"{
+        "format": 2,
+        "info": {
+            "name": "Slashes",
+            "compatible_versions": ["1.21.*"],
+            "compatible_platforms": ["Paper"]
+        },
+        "settings": { "server.properties": { "motd": "C:\\=\\: path" } }
+    }"#;
+    let preset_path = dir.join("slashes.json");
+    fs::write(&preset_path, preset_json).unwrap();
+    load_preset(&dir, &preset_path, "Paper", "1.21.4").unwrap();
+    assert_eq!(
+        read_property(&dir, "server.properties", "motd").unwrap(),
+        "C:\\=\\: path"
+    );
+}
+
+// ── server.properties schema ─────────────────────────────────────
+
+#[test]
+fn schema_defaults_pass_their_own_checks() {
+    for spec in SERVER_PROPERTIES {
+        assert!(spec.check(spec.default).is_ok(), "{}", spec.key);
+    }
+    for key in PRESET_PROPERTIES {
+        assert!(property_spec(key).is_some(), "{}", key);
+    }
+}
+
+#[test]
+fn schema_checks_types_and_ranges() {
+    assert!(check_property("view-distance", "32").is_ok());
+    assert!(check_property("view-distance", "33").is_err());
+    assert!(check_property("view-distance", "far").is_err());
+    assert!(check_property("server-port", "0").is_err());
+    assert!(check_property("server-port", "25565").is_ok());
+    assert!(check_property("pvp", "yes").is_err());
+    assert!(check_property("difficulty", "HARD").is_ok());
+    assert!(check_property("difficulty", "insane").is_err());
+    assert!(check_property("level-type", "minecraft:flat").is_ok());
+    assert!(check_property("level-type", "large_biomes").is_ok());
+    assert!(check_property("level-type", "terralith:default").is_ok());
+    assert!(check_property("level-type", "not a type").is_err());
+    assert!(check_property("level-type", "minecraft:").is_err());
+    assert!(check_property("motd", "anything at all").is_ok());
+    assert!(check_property("not-a-key", "x").is_ok());
+}
+
+#[test]
+fn validate_property_warns_by_version() {
+    assert!(validate_property("simulation-distance", "12", "1.21.4")
+        .unwrap()
+        .is_empty());
+    let added = validate_property("simulation-distance", "12", "1.16.5").unwrap();
+    assert_eq!(
+        added,
+        vec!["simulation-distance was added in Minecraft 1.18; this server is 1.16.5"]
+    );
+    let removed = validate_property("spawn-animals", "false", "1.21.4").unwrap();
+    assert_eq!(
+        removed,
+        vec!["spawn-animals was removed in Minecraft 1.21.2; this server is 1.21.4"]
+    );
+    assert!(validate_property("spawn-animals", "false", "1.21.1")
+        .unwrap()
+        .is_empty());
+    let unknown = validate_property("max-playerz", "10", "1.21.4").unwrap();
+    assert!(unknown[0].contains("not a vanilla server.properties key"));
+    assert!(validate_property("max-players", "-5", "1.21.4").is_err());
+}
+
+#[test]
+fn stale_property_warnings_flag_removed_keys() {
+    let warnings = stale_property_warnings(&["snooper-enabled", "motd", "custom"], "1.20.1");
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].starts_with("snooper-enabled was removed"));
+}
+
+#[test]
+fn load_preset_rejects_invalid_values_without_writing() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    write_test_properties(tmp.path());
+    let before = fs::read_to_string(dir.join("server.properties")).unwrap();
+    let preset_json = r#"{
+        "info": {
+            "name": "Broken",
+            "compatible_versions": ["1.21.*"],
+            "compatible_platforms": ["Paper"]
+        },
+        "settings": {
+            "server.properties": { "difficulty": "hard", "view-distance": "64" }
+        }
+    }"#;
+    let preset_path = dir.join("broken.json");
+    fs::write(&preset_path, preset_json).unwrap();
+    let err = load_preset(&dir, &preset_path, "Paper", "1.21.4").unwrap_err();
+    assert!(err.to_string().contains("view-distance"));
+    assert_eq!(
+        fs::read_to_string(dir.join("server.properties")).unwrap(),
+        before
+    );
+}
+
+#[test]
+fn preset_property_warnings_list_unknown_keys() {
+    let preset: Preset = serde_json::from_str(
+        r#"{
+        "info": { "name": "x", "compatible_versions": [], "compatible_platforms": [] },
+        "settings": { "server.properties": { "pvp": "false", "spawn-npcs": "false" } }
+    }"#,
+    )
+    .unwrap();
+    let warnings = preset_property_warnings(&preset, "1.21.4");
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].starts_with("spawn-npcs was removed"));
+}
+
+// ── properties editor ────────────────────────────────────────────
+
+#[test]
+fn fuzzy_score_matches_subsequences() {
+    assert!(fuzzy_score("vd", "view-distance").is_some());
+    assert!(fuzzy_score("dv", "view-distance").is_none());
+    assert!(fuzzy_score("", "anything").is_some());
+    assert!(fuzzy_score("VIEW", "view-distance").is_some());
+    // Consecutive, word-start matches beat scattered ones
+    assert!(
+        fuzzy_score("dist", "view-distance").unwrap()
+            > fuzzy_score("dist", "disable-status").unwrap()
+    );
+}
+
+#[test]
+fn property_rows_mark_changed_values() {
+    let props = Properties::parse("# c\npvp=false\nmotd=A Minecraft Server\ncustom-key=1\n");
+    let rows = property_rows(&props);
+    let keys: Vec<&str> = rows.iter().map(|r| r.key.as_str()).collect();
+    assert_eq!(keys, vec!["pvp", "motd", "custom-key"]);
+    assert!(rows[0].is_changed());
+    assert_eq!(rows[0].default(), Some("true"));
+    assert!(!rows[1].is_changed());
+    assert!(!rows[2].is_changed());
+    assert!(rows[2].spec.is_none());
+}
+
+#[test]
+fn search_property_rows_ranks_key_hits_first() {
+    let props = Properties::parse(
+        "spawn-protection=16\nview-distance=10\nsimulation-distance=10\nmotd=hi\n",
+    );
+    let rows = property_rows(&props);
+    let all: Vec<&str> = search_property_rows(&rows, "")
+        .iter()
+        .map(|r| r.key.as_str())
+        .collect();
+    assert_eq!(
+        all,
+        vec![
+            "spawn-protection",
+            "view-distance",
+            "simulation-distance",
+            "motd"
+        ]
+    );
+    let hits: Vec<&str> = search_property_rows(&rows, "view")
+        .iter()
+        .map(|r| r.key.as_str())
+        .collect();
+    assert_eq!(hits[0], "view-distance");
+    // "server list" only appears in motd's description
+    let by_description: Vec<&str> = search_property_rows(&rows, "server list")
+        .iter()
+        .map(|r| r.key.as_str())
+        .collect();
+    assert_eq!(by_description.first(), Some(&"motd"));
+}
+
+// ── YAML configs ─────────────────────────────────────────────────
+
+const SPIGOT_YML: &str = "# This is the main configuration file for Spigot.
+settings:
+  debug: false
+  bungeecord: false # proxy support
+world-settings:
+  default:
+    mob-spawn-range: 8
+    entity-activation-range:
+      animals: 32
+      monsters: 32
+    # merge radius for drops
+    merge-radius:
+      item: 0.5
+commands:
+  spam-exclusions:
+  - /skill
+  silent-commandblock-console: false
+";
+
+#[test]
+fn yaml_get_reads_dotted_paths() {
+    assert_eq!(
+        yaml_get(SPIGOT_YML, "world-settings.default.mob-spawn-range").unwrap(),
+        Some("8".to_string())
+    );
+    assert_eq!(
+        yaml_get(SPIGOT_YML, "settings.bungeecord").unwrap(),
+        Some("false".to_string())
+    );
+    assert_eq!(yaml_get(SPIGOT_YML, "settings.missing").unwrap(), None);
+}
+
+#[test]
+fn yaml_set_keeps_comments_and_layout() {
+    let out = yaml_set(
+        SPIGOT_YML,
+        "world-settings.default.entity-activation-range.animals",
+        "16",
+    )
+    .unwrap();
+    let out = yaml_set(&out, "settings.bungeecord", "true").unwrap();
+    assert_eq!(
+        out,
+        SPIGOT_YML
+            .replace("      animals: 32", "      animals: 16")
+            .replace(
+                "  bungeecord: false # proxy support",
+                "  bungeecord: true # proxy support"
+            )
+    );
+}
+
+#[test]
+fn yaml_set_adds_missing_sections() {
+    let out = yaml_set(
+        SPIGOT_YML,
+        "world-settings.default.entity-activation-range.raiders",
+        "48",
+    )
+    .unwrap();
+    assert!(out.contains("      monsters: 32\n      raiders: 48\n    # merge radius"));
+    let out = yaml_set(
+        &out,
+        "world-settings.default.ticks-per.hopper-transfer",
+        "8",
+    )
+    .unwrap();
+    assert!(out.contains("      item: 0.5\n    ticks-per:\n      hopper-transfer: 8\ncommands:"));
+    let out = yaml_set(&out, "stats.disable-saving", "true").unwrap();
+    assert!(out.ends_with("  silent-commandblock-console: false\nstats:\n  disable-saving: true\n"));
+    assert_eq!(
+        yaml_get(&out, "world-settings.default.ticks-per.hopper-transfer").unwrap(),
+        Some("8".to_string())
+    );
+    assert_eq!(yaml_set("", "a.b", "1").unwrap(), "a:\n  b: 1\n");
+}
+
+#[test]
+fn yaml_set_refuses_sections_and_lists() {
+    assert!(yaml_set(SPIGOT_YML, "world-settings.default", "1").is_err());
+    assert!(yaml_set(SPIGOT_YML, "commands.spam-exclusions", "x").is_err());
+    assert!(yaml_set(SPIGOT_YML, "settings..debug", "x").is_err());
+}
+
+#[test]
+fn yaml_set_falls_back_for_flow_style() {
+    let out = yaml_set("settings: {debug: false}\n", "settings.debug", "true").unwrap();
+    assert_eq!(
+        yaml_get(&out, "settings.debug").unwrap(),
+        Some("true".to_string())
+    );
+}
+
+#[test]
+fn format_scalar_quotes_only_when_needed() {
+    assert_eq!(format_scalar("ALTERNATE_CURRENT"), "ALTERNATE_CURRENT");
+    assert_eq!(format_scalar("3.5"), "3.5");
+    assert_eq!(format_scalar("-1"), "-1");
+    assert_eq!(format_scalar("true"), "true");
+    assert_eq!(format_scalar("yes"), "'yes'");
+    assert_eq!(format_scalar(""), "''");
+    assert_eq!(format_scalar("a: b"), "'a: b'");
+    assert_eq!(format_scalar("it's"), "it's");
+    assert_eq!(format_scalar("'quoted'"), "'''quoted'''");
+    assert_eq!(format_scalar("&Welcome"), "'&Welcome'");
+}
+
+#[test]
+fn apply_settings_writes_yaml_and_properties() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("spigot.yml"), SPIGOT_YML).unwrap();
+    fs::write(dir.join("server.properties"), "view-distance=10\n").unwrap();
+    let settings = HashMap::from([
+        (
+            "spigot.yml".to_string(),
+            HashMap::from([(
+                "world-settings.default.mob-spawn-range".to_string(),
+                "6".to_string(),
+            )]),
+        ),
+        (
+            "config/paper-world-defaults.yml".to_string(),
+            HashMap::from([(
+                "misc.redstone-implementation".to_string(),
+                "ALTERNATE_CURRENT".to_string(),
+            )]),
+        ),
+        (
+            "server.properties".to_string(),
+            HashMap::from([("view-distance".to_string(), "8".to_string())]),
+        ),
+    ]);
+    apply_settings(&dir, &settings, "1.21.4").unwrap();
+    let spigot = fs::read_to_string(dir.join("spigot.yml")).unwrap();
+    assert!(spigot.starts_with("# This is the main configuration file for Spigot."));
+    assert!(spigot.contains("    mob-spawn-range: 6\n"));
+    assert_eq!(
+        fs::read_to_string(dir.join("config/paper-world-defaults.yml")).unwrap(),
+        "misc:\n  redstone-implementation: ALTERNATE_CURRENT\n"
+    );
+    assert_eq!(
+        read_property(&dir, "server.properties", "view-distance").unwrap(),
+        "8"
+    );
+}
+
+#[test]
+fn apply_settings_checks_everything_before_writing() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("spigot.yml"), SPIGOT_YML).unwrap();
+    let settings = HashMap::from([
+        (
+            "spigot.yml".to_string(),
+            HashMap::from([("settings.debug".to_string(), "true".to_string())]),
+        ),
+        (
+            "server.properties".to_string(),
+            HashMap::from([("view-distance".to_string(), "99".to_string())]),
+        ),
+    ]);
+    assert!(apply_settings(&dir, &settings, "1.21.4").is_err());
+    assert_eq!(
+        fs::read_to_string(dir.join("spigot.yml")).unwrap(),
+        SPIGOT_YML
+    );
+
+    let escape = HashMap::from([(
+        "../outside.yml".to_string(),
+        HashMap::from([("a".to_string(), "1".to_string())]),
+    )]);
+    assert!(apply_settings(&dir, &escape, "1.21.4").is_err());
+}
+
+#[test]
+fn preset_json_keeps_tracking_yaml_settings() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("bukkit.yml"), "spawn-limits:\n  monsters: 70\n").unwrap();
+    auto_save_preset(&dir, "Paper", "1.21.4");
+    let settings = HashMap::from([(
+        "bukkit.yml".to_string(),
+        HashMap::from([("spawn-limits.monsters".to_string(), "50".to_string())]),
+    )]);
+    apply_settings(&dir, &settings, "1.21.4").unwrap();
+
+    // A later manual edit shows up on the next auto-save
+    fs::write(dir.join("bukkit.yml"), "spawn-limits:\n  monsters: 40\n").unwrap();
+    auto_save_preset(&dir, "Paper", "1.21.4");
+    let preset: Preset =
+        serde_json::from_str(&fs::read_to_string(dir.join("preset.json")).unwrap()).unwrap();
+    assert_eq!(preset.settings["bukkit.yml"]["spawn-limits.monsters"], "40");
+}
+
+#[test]
+fn tuning_profiles_apply_cleanly() {
+    for profile in tuning_profiles() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().to_path_buf();
+        apply_tuning_profile(&dir, &profile, "Paper", "1.21.4").unwrap();
+        assert!(preset_property_warnings(&profile, "1.21.4").is_empty());
+        for (file, values) in &profile.settings {
+            for (key, value) in values {
+                let current = if is_yaml_file(file) {
+                    read_yaml_value(&dir.join(file), key).unwrap().unwrap()
+                } else {
+                    read_property(&dir, file, key).unwrap()
+                };
+                assert_eq!(&current, value, "{} {} {}", profile.info.name, file, key);
+            }
+        }
+    }
+    let performance = &tuning_profiles()[0];
+    assert!(apply_tuning_profile(
+        &TempDir::new().unwrap().path().to_path_buf(),
+        performance,
+        "Fabric",
+        "1.21.4"
+    )
+    .is_err());
+}
+
+// ── players / RCON ───────────────────────────────────────────────
+
+#[test]
+fn offline_uuids_match_the_server() {
+    assert_eq!(
+        offline_uuid("Notch"),
+        "b50ad385-829d-3141-a216-7e7d7539ba7f"
+    );
+    assert_eq!(
+        offline_uuid("Steve"),
+        "5627dd98-e6be-3c21-b8a8-e92344183641"
+    );
+    assert_eq!(
+        dashed_uuid("069A79F444E94726A5BEFCA90E38AAF5").unwrap(),
+        "069a79f4-44e9-4726-a5be-fca90e38aaf5"
+    );
+    assert!(dashed_uuid("not-a-uuid").is_err());
+    assert!(valid_player_name("Steve_99"));
+    assert!(!valid_player_name("ab"));
+    assert!(!valid_player_name("bad name"));
+    assert_eq!(java_date(0), "1970-01-01 00:00:00 +0000");
+}
+
+#[test]
+fn parse_player_commands() {
+    let args = |s: &str| -> Vec<String> { s.split(' ').map(String::from).collect() };
+    assert_eq!(
+        parse_player_command(&args("--dir /srv op Steve --level 2")).unwrap(),
+        PlayerCommand::Change(PlayerChange::Op {
+            name: "Steve".to_string(),
+            level: 2,
+            bypasses_player_limit: false,
+        })
+    );
+    assert_eq!(
+        parse_player_command(&args("whitelist add Alex")).unwrap(),
+        PlayerCommand::Change(PlayerChange::WhitelistAdd("Alex".to_string()))
+    );
+    assert_eq!(
+        parse_player_command(&args("ban Steve --reason griefing")).unwrap(),
+        PlayerCommand::Change(PlayerChange::Ban {
+            name: "Steve".to_string(),
+            reason: Some("griefing".to_string()),
+        })
+    );
+    assert_eq!(
+        parse_player_command(&args("list ip-bans")).unwrap(),
+        PlayerCommand::List(PlayerList::IpBans)
+    );
+    assert_eq!(
+        parse_player_command(&[]).unwrap(),
+        PlayerCommand::ListPlayers
+    );
+    assert_eq!(
+        parse_player_command(&args("show Steve")).unwrap(),
+        PlayerCommand::ShowPlayer("Steve".to_string())
+    );
+    assert!(parse_player_command(&args("op Steve --level 5")).is_err());
+    assert!(parse_player_command(&args("whitelist add")).is_err());
+    assert!(parse_player_command(&args("kick Steve")).is_err());
+    assert_eq!(
+        PlayerChange::Ban {
+            name: "Steve".to_string(),
+            reason: Some("griefing".to_string()),
+        }
+        .command(),
+        "ban Steve griefing"
+    );
+}
+
+#[tokio::test]
+async fn offline_player_changes_edit_json_files() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("server.properties"), "online-mode=false\n").unwrap();
+
+    let change = |c: PlayerChange| {
+        let dir = dir.clone();
+        async move { apply_player_change_offline(&dir, &c).await }
+    };
+    change(PlayerChange::Op {
+        name: "Steve".to_string(),
+        level: 2,
+        bypasses_player_limit: true,
+    })
+    .await
+    .unwrap();
+    change(PlayerChange::WhitelistAdd("Steve".to_string()))
+        .await
+        .unwrap();
+    change(PlayerChange::WhitelistAdd("Alex".to_string()))
+        .await
+        .unwrap();
+    change(PlayerChange::Ban {
+        name: "Notch".to_string(),
+        reason: None,
+    })
+    .await
+    .unwrap();
+    change(PlayerChange::BanIp {
+        ip: "10.0.0.1".to_string(),
+        reason: Some("spam".to_string()),
+    })
+    .await
+    .unwrap();
+    assert!(change(PlayerChange::BanIp {
+        ip: "nope".to_string(),
+        reason: None
+    })
+    .await
+    .is_err());
+
+    let ops: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("ops.json")).unwrap()).unwrap();
+    assert_eq!(
+        ops,
+        serde_json::json!([{
+            "uuid": "5627dd98-e6be-3c21-b8a8-e92344183641",
+            "name": "Steve",
+            "level": 2,
+            "bypassesPlayerLimit": true
+        }])
+    );
+    let bans: Vec<BanEntry> = read_player_list(&dir, PlayerList::Bans).unwrap();
+    assert_eq!(bans[0].uuid, "b50ad385-829d-3141-a216-7e7d7539ba7f");
+    assert_eq!(bans[0].expires, "forever");
+    assert_eq!(bans[0].reason, "Banned by an operator.");
+    assert_eq!(
+        player_list_rows(&dir, PlayerList::IpBans).unwrap(),
+        vec![("10.0.0.1".to_string(), "spam".to_string())]
+    );
+
+    // Removal matches names case-insensitively and reports unknown names
+    change(PlayerChange::WhitelistRemove("steve".to_string()))
+        .await
+        .unwrap();
+    let whitelist: Vec<WhitelistEntry> = read_player_list(&dir, PlayerList::Whitelist).unwrap();
+    assert_eq!(whitelist.len(), 1);
+    assert_eq!(whitelist[0].name, "Alex");
+    assert!(change(PlayerChange::Pardon("Herobrine".to_string()))
+        .await
+        .is_err());
+    change(PlayerChange::Deop("Steve".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("ops.json")).unwrap().trim(),
+        "[]"
+    );
+}
+
+#[tokio::test]
+async fn online_mode_resolves_names_through_profile_api() {
+    let body = r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch"}"#;
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    let (api, server) = mock_http_server_bytes(response.into_bytes());
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    write_oxide_config(&dir, "profile_api", &format!("{}/profiles", api)).unwrap();
+    let profile = resolve_profile(&dir, "notch").await.unwrap();
+    assert_eq!(
+        profile,
+        Profile {
+            uuid: "069a79f4-44e9-4726-a5be-fca90e38aaf5".to_string(),
+            name: "Notch".to_string(),
+        }
+    );
+    assert!(server
+        .join()
+        .unwrap()
+        .starts_with("GET /profiles/notch HTTP/1.1"));
+
+    let (api, _server) = mock_http_server(
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    );
+    assert!(lookup_profile(&api, "Nobody123")
+        .await
+        .unwrap_err()
+        .contains("No Minecraft account"));
+}
+
+/// One-connection RCON server: accepts `password`, answers each command with `reply`
+/// and returns the commands it received.
+fn mock_rcon_server(
+    password: &'static str,
+    reply: &'static str,
+) -> (u16, std::thread::JoinHandle<Vec<String>>) {
+    use std::io::{Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut commands = Vec::new();
+        loop {
+            let mut len = [0u8; 4];
+            if stream.read_exact(&mut len).is_err() {
+                break;
+            }
+            let mut packet = vec![0u8; i32::from_le_bytes(len) as usize];
+            stream.read_exact(&mut packet).unwrap();
+            let (id, kind, body) = decode_packet(&packet).unwrap();
+            if kind == 3 {
+                let id = if body == password { id } else { -1 };
+                stream.write_all(&encode_packet(id, 2, "")).unwrap();
+            } else {
+                commands.push(body);
+                stream.write_all(&encode_packet(id, 0, reply)).unwrap();
+            }
+        }
+        commands
+    });
+    (port, handle)
+}
+
+#[test]
+fn rcon_packets_round_trip() {
+    let packet = encode_packet(7, 2, "list");
+    assert_eq!(&packet[..4], &14i32.to_le_bytes());
+    assert_eq!(
+        decode_packet(&packet[4..]),
+        Some((7, 2, "list".to_string()))
+    );
+    assert_eq!(decode_packet(&[0; 4]), None);
+
+    let (port, server) = mock_rcon_server("secret", "There are 0 of a max of 20 players online");
+    let mut rcon = Rcon::connect(&format!("127.0.0.1:{}", port), "secret").unwrap();
+    assert_eq!(
+        rcon.command("list").unwrap(),
+        "There are 0 of a max of 20 players online"
+    );
+    drop(rcon);
+    assert_eq!(server.join().unwrap(), vec!["list".to_string()]);
+
+    let (port, _server) = mock_rcon_server("secret", "");
+    assert!(Rcon::connect(&format!("127.0.0.1:{}", port), "wrong").is_err());
+}
+
+#[tokio::test]
+async fn running_server_changes_go_over_rcon() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    // Stands in for the game port of a running server
+    let game = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let game_port = game.local_addr().unwrap().port().to_string();
+    let (rcon_port, server) = mock_rcon_server("secret", "Made Steve a server operator");
+    fs::write(
+        dir.join("server.properties"),
+        format!("server-port={}\nenable-rcon=false\n", game_port),
+    )
+    .unwrap();
+    assert!(server_running(&dir));
+
+    let op = PlayerChange::Op {
+        name: "Steve".to_string(),
+        level: 4,
+        bypasses_player_limit: false,
+    };
+    assert!(apply_player_change(&dir, &op)
+        .await
+        .unwrap_err()
+        .to_string()
+        .contains("RCON"));
+
+    configure_properties(
+        &dir,
+        "server.properties",
+        &[
+            ("enable-rcon", "true"),
+            ("rcon.port", &rcon_port.to_string()),
+            ("rcon.password", "secret"),
+        ],
+    )
+    .unwrap();
+    let level_two = PlayerChange::Op {
+        name: "Steve".to_string(),
+        level: 2,
+        bypasses_player_limit: false,
+    };
+    assert!(apply_player_change(&dir, &level_two).await.is_err());
+    assert_eq!(
+        apply_player_change(&dir, &op).await.unwrap(),
+        "Made Steve a server operator"
+    );
+    assert_eq!(server.join().unwrap(), vec!["op Steve".to_string()]);
+    // The files are the server's while it runs
+    assert!(!dir.join("ops.json").exists());
+
+    drop(game);
+    assert!(!server_running(&dir));
+}
+
+// ── NBT / player data ────────────────────────────────────────────
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn compound(entries: Vec<(&str, Tag)>) -> Tag {
+    Tag::Compound(
+        entries
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}
+
+fn item(slot: i8, id: &str, count: i32) -> Tag {
+    compound(vec![
+        ("Slot", Tag::Byte(slot)),
+        ("id", Tag::String(id.to_string())),
+        ("count", Tag::Int(count)),
+    ])
+}
+
+fn modern_player() -> Tag {
+    compound(vec![
+        (
+            "Pos",
+            Tag::List(vec![
+                Tag::Double(10.5),
+                Tag::Double(64.0),
+                Tag::Double(-3.25),
+            ]),
+        ),
+        ("Dimension", Tag::String("minecraft:the_nether".to_string())),
+        ("playerGameType", Tag::Int(0)),
+        ("Health", Tag::Float(18.5)),
+        ("foodLevel", Tag::Int(17)),
+        ("XpLevel", Tag::Int(30)),
+        (
+            "Inventory",
+            Tag::List(vec![
+                item(0, "minecraft:cobblestone", 64),
+                item(1, "minecraft:cobblestone", 32),
+                item(2, "minecraft:torch", 12),
+            ]),
+        ),
+        (
+            "equipment",
+            compound(vec![(
+                "head",
+                compound(vec![
+                    ("id", Tag::String("minecraft:diamond_helmet".to_string())),
+                    ("count", Tag::Int(1)),
+                ]),
+            )]),
+        ),
+        ("EnderItems", Tag::List(vec![])),
+    ])
+}
+
+#[test]
+fn nbt_reads_compressed_and_plain_data() {
+    let root = compound(vec![
+        (
+            "Data",
+            compound(vec![
+                ("LevelName", Tag::String("world".to_string())),
+                ("Time", Tag::Long(123_456_789_000)),
+                ("Heights", Tag::IntArray(vec![1, -2, 3])),
+                ("Seeds", Tag::LongArray(vec![i64::MIN])),
+                ("Raw", Tag::ByteArray(vec![-1, 0, 1])),
+                ("Spawn", Tag::Short(-7)),
+            ]),
+        ),
+        ("Empty", Tag::List(vec![])),
+    ]);
+    let plain = write_nbt("", &root).unwrap();
+    assert_eq!(read_nbt(&plain).unwrap(), (String::new(), root.clone()));
+    assert_eq!(read_nbt(&gzip(&plain)).unwrap().1, root);
+    let zlib = {
+        use std::io::Write;
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&plain).unwrap();
+        encoder.finish().unwrap()
+    };
+    assert_eq!(read_nbt(&zlib).unwrap().1, root);
+
+    assert_eq!(
+        root.path("Data.LevelName").and_then(Tag::as_str),
+        Some("world")
+    );
+    assert_eq!(root.path("Data.Spawn").and_then(Tag::as_i64), Some(-7));
+    assert_eq!(root.path("Data.Missing.Deeper"), None);
+}
+
+#[test]
+fn nbt_rejects_corrupt_data() {
+    let plain = write_nbt("", &compound(vec![("Name", Tag::String("x".to_string()))])).unwrap();
+    assert!(read_nbt(&plain[..plain.len() - 2]).is_err());
+    assert!(read_nbt(&[8, 0, 0, 0, 0]).is_err());
+    // A list claiming two billion entries
+    let mut huge = vec![10, 0, 0, 9, 0, 1, b'L', 1];
+    huge.extend_from_slice(&i32::MAX.to_be_bytes());
+    assert!(read_nbt(&huge).is_err());
+    assert!(read_nbt(&[10, 0, 0, 99, 0, 0]).is_err());
+}
+
+#[test]
+fn nbt_strings_use_modified_utf8() {
+    assert_eq!(decode_modified_utf8(b"plain"), "plain");
+    assert_eq!(decode_modified_utf8(&[b'a', 0xc0, 0x80, b'b']), "a\0b");
+    assert_eq!(decode_modified_utf8("é".as_bytes()), "é");
+    // U+1F600 as two encoded surrogates
+    assert_eq!(
+        decode_modified_utf8(&[0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]),
+        "😀"
+    );
+}
+
+#[test]
+fn player_info_reads_modern_and_legacy_saves() {
+    let info = player_info("uuid", &modern_player());
+    assert_eq!(info.position, Some([10.5, 64.0, -3.25]));
+    assert_eq!(info.dimension.as_deref(), Some("minecraft:the_nether"));
+    assert_eq!(info.health, Some(18.5));
+    assert_eq!(info.inventory.len(), 4);
+    assert_eq!(info.inventory[3].slot, 103);
+
+    let legacy = compound(vec![
+        ("Dimension", Tag::Int(1)),
+        (
+            "Inventory",
+            Tag::List(vec![compound(vec![
+                ("Slot", Tag::Byte(0)),
+                ("id", Tag::String("minecraft:stone".to_string())),
+                ("Count", Tag::Byte(5)),
+            ])]),
+        ),
+        (
+            "bukkit",
+            compound(vec![("lastKnownName", Tag::String("Steve".to_string()))]),
+        ),
+    ]);
+    let info = player_info("uuid", &legacy);
+    assert_eq!(info.dimension.as_deref(), Some("minecraft:the_end"));
+    assert_eq!(info.inventory[0].count, 5);
+    assert_eq!(info.name.as_deref(), Some("Steve"));
+}
+
+#[test]
+fn item_summary_totals_stacks() {
+    let items: Vec<ItemStack> = ["stone", "dirt", "stone", "sand"]
+        .iter()
+        .enumerate()
+        .map(|(i, id)| ItemStack {
+            slot: i as i64,
+            id: format!("minecraft:{}", id),
+            count: 10 + i as i64,
+        })
+        .collect();
+    assert_eq!(item_summary(&items, 2), "22× stone, 13× sand, and 1 more");
+    assert_eq!(item_summary(&[], 8), "empty");
+}
+
+#[test]
+fn list_and_show_players() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("server.properties"), "level-name=survival\n").unwrap();
+    let data_dir = dir.join("survival/playerdata");
+    fs::create_dir_all(&data_dir).unwrap();
+    let steve = "5627dd98-e6be-3c21-b8a8-e92344183641";
+    fs::write(
+        data_dir.join(format!("{}.dat", steve)),
+        gzip(&write_nbt("", &modern_player()).unwrap()),
+    )
+    .unwrap();
+    fs::write(data_dir.join(format!("{}.dat_old", steve)), b"ignored").unwrap();
+    fs::write(
+        dir.join("usercache.json"),
+        format!(
+            r#"[{{"name":"Steve","uuid":"{}","expiresOn":"2030-01-01 00:00:00 +0000"}},
+               {{"name":"Alex","uuid":"ec561538-f3fd-461d-aff5-086b22154bce","expiresOn":"2030-01-01 00:00:00 +0000"}}]"#,
+            steve
+        ),
+    )
+    .unwrap();
+
+    let players = list_players(&dir).unwrap();
+    assert_eq!(players.len(), 2);
+    // Players with saved data come first
+    assert_eq!(players[0].name.as_deref(), Some("Steve"));
+    assert!(players[0].last_saved.is_some());
+    assert_eq!(players[1].name.as_deref(), Some("Alex"));
+
+    let details = player_details(&find_player(&dir, "steve").unwrap());
+    assert!(details.starts_with(&format!("Steve ({})", steve)));
+    assert!(details.contains("Position: 10.5, 64.0, -3.2 in minecraft:the_nether"));
+    assert!(details.contains("Gamemode: Survival"));
+    assert!(details.contains("Health: 18.5/20, food 17/20"));
+    assert!(details.contains("Inventory (3 stacks): 96× cobblestone, 12× torch"));
+    assert!(details.contains("Armor: 1× diamond_helmet"));
+    assert!(details.contains("Ender chest (0 stacks): empty"));
+
+    let alex = find_player(&dir, "ec561538f3fd461daff5086b22154bce").unwrap();
+    assert!(player_details(&alex).contains("No saved player data"));
+    assert!(find_player(&dir, "Herobrine").is_err());
+}
+
+// ── level.dat ────────────────────────────────────────────────────
+
+fn modern_level_dat() -> Tag {
+    compound(vec![(
+        "Data",
+        compound(vec![
+            ("LevelName", Tag::String("survival".to_string())),
+            ("DataVersion", Tag::Int(4189)),
+            (
+                "Version",
+                compound(vec![
+                    ("Id", Tag::Int(4189)),
+                    ("Name", Tag::String("1.21.4".to_string())),
+                ]),
+            ),
+            (
+                "WorldGenSettings",
+                compound(vec![("seed", Tag::Long(-4172144997902289642))]),
+            ),
+            ("SpawnX", Tag::Int(48)),
+            ("SpawnY", Tag::Int(70)),
+            ("SpawnZ", Tag::Int(-112)),
+            ("LastPlayed", Tag::Long(1_700_000_000_000)),
+            ("BorderSize", Tag::Double(2000.0)),
+            ("BorderCenterX", Tag::Double(0.5)),
+            ("BorderCenterZ", Tag::Double(-10.0)),
+            (
+                "GameRules",
+                compound(vec![
+                    ("keepInventory", Tag::String("true".to_string())),
+                    ("randomTickSpeed", Tag::String("3".to_string())),
+                ]),
+            ),
+        ]),
+    )])
+}
+
+#[test]
+fn world_info_reads_level_dat() {
+    let info = world_info(&modern_level_dat());
+    assert_eq!(info.level_name.as_deref(), Some("survival"));
+    assert_eq!(info.seed, Some(-4172144997902289642));
+    assert_eq!(info.data_version, Some(4189));
+    assert_eq!(info.game_version.as_deref(), Some("1.21.4"));
+    assert_eq!(info.spawn, Some([48, 70, -112]));
+    assert_eq!(info.border.as_ref().unwrap().size, 2000.0);
+    assert_eq!(info.border.as_ref().unwrap().damage_per_block, 0.2);
+    assert_eq!(
+        info.game_rules,
+        vec![
+            ("keepInventory".to_string(), "true".to_string()),
+            ("randomTickSpeed".to_string(), "3".to_string()),
+        ]
+    );
+
+    let details = world_details(&info);
+    assert!(details.contains("Seed: -4172144997902289642"));
+    assert!(details.contains("Saved by: Minecraft 1.21.4 (DataVersion 4189)"));
+    assert!(details.contains("Last played: 2023-11-14 22:13 UTC"));
+    assert!(details.contains("Spawn: 48, 70, -112"));
+    assert!(details.contains("World border: 2000 blocks wide, centered on 0.5, -10"));
+    assert!(details.contains("  keepInventory = true"));
+}
+
+#[test]
+fn world_info_reads_older_and_newer_layouts() {
+    // Before 1.16: RandomSeed and no version name
+    let legacy = compound(vec![(
+        "Data",
+        compound(vec![
+            ("RandomSeed", Tag::Long(42)),
+            ("SpawnX", Tag::Int(0)),
+            ("SpawnY", Tag::Int(64)),
+            ("SpawnZ", Tag::Int(0)),
+        ]),
+    )]);
+    let info = world_info(&legacy);
+    assert_eq!(info.seed, Some(42));
+    assert_eq!(info.game_version, None);
+    assert!(world_details(&info).starts_with("world\nSeed: 42\nSpawn: 0, 64, 0"));
+
+    // 1.21.9 and later: a spawn compound
+    let newer = compound(vec![(
+        "Data",
+        compound(vec![(
+            "spawn",
+            compound(vec![
+                ("pos", Tag::IntArray(vec![5, 80, -5])),
+                ("dimension", Tag::String("minecraft:overworld".to_string())),
+            ]),
+        )]),
+    )]);
+    assert_eq!(world_info(&newer).spawn, Some([5, 80, -5]));
+}
+
+#[test]
+fn open_falls_back_to_level_dat_version() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::write(dir.join("server.jar"), b"jar").unwrap();
+    assert!(read_world_info(&dir).is_err());
+    assert!(OxideMC::open(&dir).is_err());
+
+    fs::create_dir_all(dir.join("world")).unwrap();
+    fs::write(
+        dir.join("world/level.dat"),
+        gzip(&write_nbt("", &modern_level_dat()).unwrap()),
+    )
+    .unwrap();
+    assert_eq!(OxideMC::open(&dir).unwrap().version, "1.21.4");
+
+    fs::create_dir_all(dir.join("versions/1.21.5")).unwrap();
+    assert_eq!(OxideMC::open(&dir).unwrap().version, "1.21.5");
+}
+
+// ── world border / game rules ────────────────────────────────────
+
+fn write_level_dat(dir: &std::path::Path, root: &Tag) -> PathBuf {
+    fs::create_dir_all(dir.join("world")).unwrap();
+    let path = dir.join("world/level.dat");
+    write_nbt_file(&path, "", root).unwrap();
+    path
+}
+
+#[test]
+fn nbt_writer_round_trips() {
+    let root = compound(vec![
+        ("Text", Tag::String("a\0b 😀 é".to_string())),
+        ("Empty", Tag::List(vec![])),
+        (
+            "Nested",
+            Tag::List(vec![compound(vec![("x", Tag::Float(1.5))])]),
+        ),
+    ]);
+    let bytes = write_nbt("root", &root).unwrap();
+    assert_eq!(read_nbt(&bytes).unwrap(), ("root".to_string(), root));
+    assert_eq!(encode_modified_utf8("a\0"), vec![b'a', 0xc0, 0x80]);
+    assert_eq!(
+        encode_modified_utf8("😀"),
+        vec![0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]
+    );
+    assert!(write_nbt("", &Tag::List(vec![Tag::Int(1), Tag::Long(2)])).is_err());
+    assert!(write_nbt("", &Tag::String("x".repeat(70_000))).is_err());
+
+    let mut tag = compound(vec![("a", Tag::Int(1))]);
+    tag.set("a", Tag::Int(2));
+    tag.set("b", Tag::Int(3));
+    assert_eq!(tag, compound(vec![("a", Tag::Int(2)), ("b", Tag::Int(3))]));
+}
+
+#[test]
+fn write_nbt_file_keeps_previous_version() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let path = write_level_dat(&dir, &compound(vec![("v", Tag::Int(1))]));
+    write_nbt_file(&path, "", &compound(vec![("v", Tag::Int(2))])).unwrap();
+    assert_eq!(
+        read_nbt_file(&path).unwrap(),
+        compound(vec![("v", Tag::Int(2))])
+    );
+    assert_eq!(
+        read_nbt_file(&dir.join("world/level.dat_old")).unwrap(),
+        compound(vec![("v", Tag::Int(1))])
+    );
+    assert!(!dir.join("world/.level.dat.tmp").exists());
+}
+
+#[test]
+fn world_changes_edit_level_dat_offline() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let rule = |rule: &str, value: &str| WorldChange::GameRule {
+        rule: rule.to_string(),
+        value: value.to_string(),
+    };
+    assert!(apply_world_changes(&dir, &[WorldChange::BorderSize(100.0)]).is_err());
+
+    write_level_dat(&dir, &modern_level_dat());
+    let lines = apply_world_changes(
+        &dir,
+        &[
+            WorldChange::BorderCenter { x: 100.0, z: -50.0 },
+            WorldChange::BorderSize(500.0),
+            rule("keepInventory", "false"),
+            rule("randomTickSpeed", "10"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(lines[2], "Game rule keepInventory set to false");
+    let info = read_world_info(&dir).unwrap();
+    let border = info.border.unwrap();
+    assert_eq!(
+        (border.center_x, border.center_z, border.size),
+        (100.0, -50.0, 500.0)
+    );
+    assert_eq!(
+        info.game_rules,
+        vec![
+            ("keepInventory".to_string(), "false".to_string()),
+            ("randomTickSpeed".to_string(), "10".to_string()),
+        ]
+    );
+    // Everything else in level.dat is untouched
+    assert_eq!(info.seed, Some(-4172144997902289642));
+
+    // One bad change and nothing is written
+    let before = fs::read(dir.join("world/level.dat")).unwrap();
+    for bad in [
+        vec![rule("keepInventory", "true"), rule("noSuchRule", "1")],
+        vec![rule("randomTickSpeed", "fast")],
+        vec![rule("keepInventory", "maybe")],
+        vec![rule("keepInventory", "true\nop Steve")],
+        vec![WorldChange::BorderSize(0.0)],
+        vec![WorldChange::BorderCenter { x: 3e7, z: 0.0 }],
+    ] {
+        assert!(apply_world_changes(&dir, &bad).is_err(), "{:?}", bad);
+    }
+    assert_eq!(fs::read(dir.join("world/level.dat")).unwrap(), before);
+}
+
+#[test]
+fn typed_game_rules_keep_their_tag_types() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    write_level_dat(
+        &dir,
+        &compound(vec![(
+            "Data",
+            compound(vec![(
+                "game_rules",
+                compound(vec![
+                    ("minecraft:keep_inventory", Tag::Byte(0)),
+                    ("minecraft:random_tick_speed", Tag::Int(3)),
+                ]),
+            )]),
+        )]),
+    );
+    let changes = game_rule_changes(&HashMap::from([
+        ("minecraft:keep_inventory".to_string(), "true".to_string()),
+        ("minecraft:random_tick_speed".to_string(), "0".to_string()),
+    ]));
+    apply_world_changes(&dir, &changes).unwrap();
+    let root = read_nbt_file(&dir.join("world/level.dat")).unwrap();
+    assert_eq!(
+        root.path("Data.game_rules"),
+        Some(&compound(vec![
+            ("minecraft:keep_inventory", Tag::Byte(1)),
+            ("minecraft:random_tick_speed", Tag::Int(0)),
+        ]))
+    );
+}
+
+#[test]
+fn world_changes_go_over_rcon_while_running() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let game = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let (rcon_port, server) = mock_rcon_server("secret", "ok");
+    fs::write(
+        dir.join("server.properties"),
+        format!(
+            "server-port={}\nenable-rcon=true\nrcon.port={}\nrcon.password=secret\n",
+            game.local_addr().unwrap().port(),
+            rcon_port
+        ),
+    )
+    .unwrap();
+    apply_world_changes(
+        &dir,
+        &[
+            WorldChange::BorderCenter { x: 0.5, z: 10.0 },
+            WorldChange::BorderSize(1000.0),
+            WorldChange::GameRule {
+                rule: "doDaylightCycle".to_string(),
+                value: "false".to_string(),
+            },
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        server.join().unwrap(),
+        vec![
+            "worldborder center 0.5 10",
+            "worldborder set 1000",
+            "gamerule doDaylightCycle false",
+        ]
+    );
+}
+
+#[test]
+fn preset_gamerules_apply_and_stay_tracked() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    auto_save_preset(&dir, "Vanilla", "1.21.4");
+    let preset_path = tmp.path().join("rules.json");
+    fs::write(
+        &preset_path,
+        r#"{
+            "info": {"name": "rules", "compatible_versions": [], "compatible_platforms": []},
+            "gamerules": {"keepInventory": "true", "randomTickSpeed": "5"}
+        }"#,
+    )
+    .unwrap();
+
+    // No world yet: the rules wait in preset.json
+    load_preset(&dir, &preset_path, "Vanilla", "1.21.4").unwrap();
+    auto_save_preset(&dir, "Vanilla", "1.21.4");
+    assert_eq!(
+        build_preset(&dir, "Vanilla", "1.21.4").gamerules["keepInventory"],
+        "true"
+    );
+
+    // With a world they go into level.dat, and later in-game changes are picked up
+    write_level_dat(&dir, &modern_level_dat());
+    load_preset(&dir, &preset_path, "Vanilla", "1.21.4").unwrap();
+    let info = read_world_info(&dir).unwrap();
+    assert!(info
+        .game_rules
+        .contains(&("randomTickSpeed".to_string(), "5".to_string())));
+    apply_world_changes(
+        &dir,
+        &[WorldChange::GameRule {
+            rule: "randomTickSpeed".to_string(),
+            value: "7".to_string(),
+        }],
+    )
+    .unwrap();
+    auto_save_preset(&dir, "Vanilla", "1.21.4");
+    let saved: Preset =
+        serde_json::from_str(&fs::read_to_string(dir.join("preset.json")).unwrap()).unwrap();
+    assert_eq!(saved.gamerules["randomTickSpeed"], "7");
+
+    // A rule the world doesn't have stops the preset before anything is written
+    fs::write(
+        &preset_path,
+        r#"{
+            "info": {"name": "rules", "compatible_versions": [], "compatible_platforms": []},
+            "settings": {"server.properties": {"difficulty": "hard"}},
+            "gamerules": {"notARule": "true"}
+        }"#,
+    )
+    .unwrap();
+    assert!(load_preset(&dir, &preset_path, "Vanilla", "1.21.4").is_err());
+    assert!(read_property(&dir, "server.properties", "difficulty").is_err());
+
+    // So does a bad setting, before any rule reaches level.dat
+    fs::write(
+        &preset_path,
+        r#"{
+            "info": {"name": "rules", "compatible_versions": [], "compatible_platforms": []},
+            "settings": {"server.properties": {"view-distance": "99"}},
+            "gamerules": {"randomTickSpeed": "3"}
+        }"#,
+    )
+    .unwrap();
+    assert!(load_preset(&dir, &preset_path, "Vanilla", "1.21.4").is_err());
+    assert!(read_world_info(&dir)
+        .unwrap()
+        .game_rules
+        .contains(&("randomTickSpeed".to_string(), "7".to_string())));
+}
+
+// ── world manager ────────────────────────────────────────────────
+
+#[test]
+fn list_worlds_groups_dimensions_and_shows_current() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    write_level_dat(&dir, &modern_level_dat());
+    fs::create_dir_all(dir.join("world_nether")).unwrap();
+    fs::write(dir.join("world_nether/level.dat"), gzip(&[])).unwrap();
+    fs::create_dir_all(dir.join("mods")).unwrap();
+    fs::write(dir.join("server.properties"), "level-name=creative\n").unwrap();
+
+    let worlds = list_worlds(&dir).unwrap();
+    let names: Vec<&str> = worlds.iter().map(|w| w.name.as_str()).collect();
+    assert_eq!(names, vec!["creative", "world"]);
+    assert!(worlds[0].current && worlds[0].info.is_none());
+    assert_eq!(worlds[0].size, 0);
+    let world = &worlds[1];
+    assert!(!world.current);
+    assert_eq!(
+        world.info.as_ref().unwrap().seed,
+        Some(-4172144997902289642)
+    );
+    assert_eq!(
+        world.size,
+        dir_size(&dir.join("world")) + dir_size(&dir.join("world_nether"))
+    );
+}
+
+#[test]
+fn create_and_switch_worlds() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    write_level_dat(&dir, &modern_level_dat());
+
+    create_world(&dir, "flat test", "1234", "minecraft:flat").unwrap();
+    assert_eq!(current_world(&dir), "flat test");
+    assert_eq!(
+        read_property(&dir, "server.properties", "level-seed").unwrap(),
+        "1234"
+    );
+    assert_eq!(
+        read_property(&dir, "server.properties", "level-type").unwrap(),
+        "minecraft:flat"
+    );
+    assert!(create_world(&dir, "world", "", "minecraft:normal").is_err());
+    assert!(create_world(&dir, "../escape", "", "minecraft:normal").is_err());
+    assert!(create_world(&dir, ".hidden", "", "minecraft:normal").is_err());
+    assert!(create_world(&dir, "other", "", "not a type").is_err());
+
+    // Only generated worlds can be switched to
+    assert!(switch_world(&dir, "flat test").is_err());
+    switch_world(&dir, "world").unwrap();
+    assert_eq!(current_world(&dir), "world");
+}
+
+#[test]
+fn zip_world_root_finds_level_dat() {
+    let tmp = TempDir::new().unwrap();
+    let zip = tmp.path().join("w.zip");
+    let root = |entries: &[(&str, &[u8])]| {
+        write_zip(&zip, entries);
+        zip_world_root(&zip.to_path_buf())
+    };
+    assert_eq!(
+        root(&[("level.dat", b""), ("region/r.0.0.mca", b"")]).unwrap(),
+        ""
+    );
+    assert_eq!(
+        root(&[
+            ("MyWorld/level.dat", b""),
+            ("MyWorld/DIM-1/level.dat", b""),
+            ("MyWorld/region/r.0.0.mca", b""),
+        ])
+        .unwrap(),
+        "MyWorld"
+    );
+    assert!(root(&[("readme.txt", b"")]).is_err());
+    assert!(root(&[("a/level.dat", b""), ("b/level.dat", b"")]).is_err());
+}
+
+#[test]
+fn import_world_zip_extracts_into_new_folder() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let zip = dir.join("Skyblock.zip");
+    let level = gzip(&write_nbt("", &modern_level_dat()).unwrap());
+    write_zip(
+        &zip,
+        &[
+            ("Skyblock/level.dat", &level),
+            ("Skyblock/session.lock", b""),
+            ("Skyblock/region/r.0.0.mca", b"chunks"),
+        ],
+    );
+    let root = zip_world_root(&zip).unwrap();
+    assert_eq!(import_name(&zip, &root), "Skyblock");
+    assert_eq!(import_name(&zip, ""), "Skyblock");
+
+    import_world_zip(&dir, &zip, "sky").unwrap();
+    assert_eq!(
+        fs::read(dir.join("sky/region/r.0.0.mca")).unwrap(),
+        b"chunks"
+    );
+    assert!(!dir.join("sky/session.lock").exists());
+    assert!(!dir.join(".import-sky").exists());
+    switch_world(&dir, "sky").unwrap();
+    assert_eq!(
+        read_world_info(&dir).unwrap().seed,
+        Some(-4172144997902289642)
+    );
+
+    assert!(import_world_zip(&dir, &zip, "sky").is_err());
+    write_zip(&zip, &[("readme.txt", b"")]);
+    assert!(import_world_zip(&dir, &zip, "other").is_err());
+    assert!(!dir.join("other").exists());
+}
+
+#[tokio::test]
+async fn reset_world_backs_up_and_keeps_seed() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    write_level_dat(&dir, &modern_level_dat());
+    fs::create_dir_all(dir.join("world_the_end")).unwrap();
+    fs::create_dir_all(dir.join("other")).unwrap();
+
+    let archive = reset_world(&dir, true).await.unwrap();
+    assert!(dir.join("backups").join(&archive).is_file());
+    assert!(!dir.join("world").exists());
+    assert!(!dir.join("world_the_end").exists());
+    assert!(dir.join("other").exists());
+    assert_eq!(
+        read_property(&dir, "server.properties", "level-seed").unwrap(),
+        "-4172144997902289642"
+    );
+    // Nothing left to back up, so nothing is deleted either
+    assert!(reset_world(&dir, false).await.is_err());
+}
+
+#[tokio::test]
+async fn reset_world_clears_seed_for_a_new_one() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    write_level_dat(&dir, &modern_level_dat());
+    fs::write(dir.join("server.properties"), "level-seed=12345\n").unwrap();
+
+    reset_world(&dir, false).await.unwrap();
+    assert!(!dir.join("world").exists());
+    assert_eq!(
+        read_property(&dir, "server.properties", "level-seed").unwrap(),
+        ""
+    );
+}
+
+/// Hold `session.lock` the way a running server does, with a record lock that closing
+/// other handles to the file doesn't release.
+#[cfg(target_os = "linux")]
+fn lock_session(world: &std::path::Path) -> fs::File {
+    use std::os::fd::AsRawFd;
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .read(true)
+        .write(true)
+        .open(world.join("session.lock"))
+        .unwrap();
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = libc::F_WRLCK as _;
+    lock.l_whence = libc::SEEK_SET as _;
+    assert_eq!(
+        unsafe { libc::fcntl(file.as_raw_fd(), libc::F_OFD_SETLK, &mut lock) },
+        0
+    );
+    file
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn destructive_changes_wait_for_the_session_lock() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    write_level_dat(&dir, &modern_level_dat());
+    fs::write(dir.join("world").join("session.lock"), "").unwrap();
+    assert!(!world_locked(&dir.join("world")));
+
+    // Nothing answers on the game port, but the world is open
+    let lock = lock_session(&dir.join("world"));
+    assert!(world_locked(&dir.join("world")));
+    let level = fs::read(dir.join("world").join("level.dat")).unwrap();
+    assert!(apply_world_changes(&dir, &[WorldChange::BorderSize(100.0)]).is_err());
+    assert_eq!(
+        fs::read(dir.join("world").join("level.dat")).unwrap(),
+        level
+    );
+    assert!(prune_regions(&dir, &PruneRule::default(), false).is_err());
+    assert!(reset_world(&dir, false).await.is_err());
+    assert!(dir.join("world").join("level.dat").is_file());
+
+    drop(lock);
+    assert!(!world_locked(&dir.join("world")));
+    apply_world_changes(&dir, &[WorldChange::BorderSize(100.0)]).unwrap();
+}
+
+#[test]
+fn expand_home_only_touches_a_leading_tilde() {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    assert_eq!(expand_home("~/worlds/a.zip"), home.join("worlds/a.zip"));
+    assert_eq!(expand_home("/srv/a~b.zip"), PathBuf::from("/srv/a~b.zip"));
+    assert_eq!(expand_home("~alex/a.zip"), PathBuf::from("~alex/a.zip"));
+}
+
+#[test]
+fn format_size_picks_unit() {
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(2048), "2.0 KB");
+    assert_eq!(format_size(3_565_158), "3.4 MB");
+    assert_eq!(format_size(5 * 1024 * 1024 * 1024), "5.0 GB");
+}
+
+// ── region pruning ───────────────────────────────────────────────
+
+fn region_chunk(x: i32, z: i32, inhabited: i64) -> RegionChunk {
+    use flate2::write::ZlibEncoder;
+    use std::io::Write;
+    let root = compound(vec![
+        ("xPos", Tag::Int(x)),
+        ("zPos", Tag::Int(z)),
+        ("InhabitedTime", Tag::Long(inhabited)),
+    ]);
+    let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&write_nbt("", &root).unwrap()).unwrap();
+    let compressed = encoder.finish().unwrap();
+    let mut data = ((compressed.len() + 1) as u32).to_be_bytes().to_vec();
+    data.push(2);
+    data.extend(compressed);
+    RegionChunk {
+        x,
+        z,
+        timestamp: 1_700_000_000,
+        data,
+    }
+}
+
+fn chunk_coords(path: &PathBuf) -> Vec<(i32, i32)> {
+    read_region(path)
+        .unwrap()
+        .iter()
+        .map(|c| (c.x, c.z))
+        .collect()
+}
+
+#[test]
+fn region_files_round_trip() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join("r.-1.2.mca");
+    assert_eq!(region_coords(&path), Some((-1, 2)));
+    assert_eq!(region_coords(std::path::Path::new("r.0.0.mca.tmp")), None);
+    assert_eq!(region_coords(std::path::Path::new("c.0.0.mcc")), None);
+
+    let chunks = vec![region_chunk(-32, 64, 0), region_chunk(-1, 95, 600)];
+    write_region(&path, &chunks).unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().len(), region_size(&chunks));
+    let read = read_region(&path).unwrap();
+    assert_eq!(read, chunks);
+    assert_eq!(read[1].index(), 31 + 31 * 32);
+    let nbt = read[1].nbt(tmp.path()).unwrap();
+    assert_eq!(inhabited_time(&nbt), Some(600));
+    let legacy = compound(vec![(
+        "Level",
+        compound(vec![("InhabitedTime", Tag::Long(7))]),
+    )]);
+    assert_eq!(inhabited_time(&legacy), Some(7));
+
+    // A header pointing past the end of the file is reported, not trusted
+    let mut bytes = fs::read(&path).unwrap();
+    bytes.truncate(8192 + 10);
+    fs::write(&path, bytes).unwrap();
+    assert!(read_region(&path).is_err());
+    fs::write(&path, b"").unwrap();
+    assert!(read_region(&path).unwrap().is_empty());
+
+    write_region(&path, &[]).unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn prune_regions_drops_unvisited_and_far_chunks() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let world = dir.join("world");
+    for folder in ["region", "entities", "DIM1/region"] {
+        fs::create_dir_all(world.join(folder)).unwrap();
+    }
+    fs::create_dir_all(dir.join("world_nether/DIM-1/region")).unwrap();
+    let overworld = world.join("region/r.0.0.mca");
+    let entities = world.join("entities/r.0.0.mca");
+    let nether = dir.join("world_nether/DIM-1/region/r.-1.0.mca");
+    let end = world.join("DIM1/region/r.0.0.mca");
+    write_region(
+        &overworld,
+        &[
+            region_chunk(0, 0, 5000),
+            region_chunk(1, 0, 0),
+            region_chunk(31, 31, 10_000),
+        ],
+    )
+    .unwrap();
+    write_region(&entities, &[region_chunk(0, 0, 0), region_chunk(1, 0, 0)]).unwrap();
+    write_region(&nether, &[region_chunk(-32, 0, 0)]).unwrap();
+    write_region(&end, &[region_chunk(0, 0, 9000)]).unwrap();
+
+    let names: Vec<&str> = world_dimensions(&dir).iter().map(|d| d.name).collect();
+    assert_eq!(names, vec!["Overworld", "Nether", "The End"]);
+
+    let unvisited = PruneRule {
+        min_inhabited_ticks: Some(1200),
+        ..Default::default()
+    };
+    let before = fs::read(&overworld).unwrap();
+    let dry = prune_regions(&dir, &unvisited, true).unwrap();
+    assert_eq!(
+        dry.iter()
+            .map(|r| (r.chunks, r.removed))
+            .collect::<Vec<_>>(),
+        vec![(3, 1), (1, 1), (1, 0)]
+    );
+    assert!(dry[0].bytes_after < dry[0].bytes_before);
+    assert_eq!(dry[1].bytes_after, 0);
+    assert!(prune_summary(&dry, true).contains("Overworld: would remove 1 of 3 chunks"));
+    assert_eq!(fs::read(&overworld).unwrap(), before);
+    assert!(nether.exists());
+
+    let done = prune_regions(&dir, &unvisited, false).unwrap();
+    assert_eq!(done, dry);
+    assert_eq!(chunk_coords(&overworld), vec![(0, 0), (31, 31)]);
+    assert_eq!(chunk_coords(&entities), vec![(0, 0)]);
+    assert!(!nether.exists());
+    assert_eq!(chunk_coords(&end), vec![(0, 0)]);
+
+    // The radius counts from the center; (31, 31) is ~700 blocks out
+    let nearby = PruneRule {
+        radius: Some(100.0),
+        ..Default::default()
+    };
+    prune_regions(&dir, &nearby, false).unwrap();
+    assert_eq!(chunk_coords(&overworld), vec![(0, 0)]);
+
+    assert!(prune_regions(&dir, &PruneRule::default(), true).is_err());
+    let game = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    fs::write(
+        dir.join("server.properties"),
+        format!("server-port={}\n", game.local_addr().unwrap().port()),
+    )
+    .unwrap();
+    assert!(prune_regions(&dir, &unvisited, true).is_err());
+}
+
+#[test]
+fn prune_radius_scales_in_the_nether() {
+    let rule = PruneRule {
+        radius: Some(800.0),
+        center: (0.0, 0.0),
+        ..Default::default()
+    };
+    // 800 overworld blocks are 100 in the Nether
+    assert!(!rule.drops(40, 0, None, 1.0));
+    assert!(rule.drops(40, 0, None, 0.125));
+    assert!(!rule.drops(5, 0, None, 0.125));
+}
+
+// ── pre-generation ───────────────────────────────────────────────
+
+#[test]
+fn parse_chunky_progress_lines() {
+    let running = parse_chunky_line(
+        "[12:00:01] [Server thread/INFO]: [Chunky] Task running for world. Processed: 1024 chunks (2.05%), ETA: 0:04:10, Rate: 195.3 cps, Current: 12, -3",
+    )
+    .unwrap();
+    assert_eq!(
+        running,
+        ChunkyProgress {
+            state: ChunkyState::Running,
+            world: "world".to_string(),
+            chunks: 1024,
+            percent: 2.05,
+            eta: Some("0:04:10".to_string()),
+            rate: Some(195.3),
+        }
+    );
+    let finished = parse_chunky_line(
+        "[12:09:30] [Chunky-minecraft:overworld Thread/INFO]: Task finished for minecraft:overworld. Processed: 50000 chunks (100.00%), Total time: 0:09:29",
+    )
+    .unwrap();
+    assert_eq!(finished.state, ChunkyState::Finished);
+    assert_eq!(finished.world, "minecraft:overworld");
+    assert_eq!((finished.chunks, finished.percent), (50000, 100.0));
+    assert_eq!(finished.eta, None);
+    let stopped = parse_chunky_line("[Chunky] Task stopped for world.").unwrap();
+    assert_eq!(
+        (stopped.state, stopped.world.as_str()),
+        (ChunkyState::Stopped, "world")
+    );
+    assert_eq!(
+        parse_chunky_line("[Server thread/INFO]: Done (3.2s)!"),
+        None
+    );
+}
+
+#[test]
+fn pregen_commands_match_border_to_area() {
+    assert_eq!(
+        pregen_commands((100.0, -50.0), 2500.0),
+        vec![
+            "worldborder center 100 -50",
+            "worldborder set 5000",
+            "chunky center 100 -50",
+            "chunky radius 2500",
+            "chunky start",
+        ]
+    );
+}
+
+#[test]
+fn chunky_goes_where_the_platform_loads_it() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    assert_eq!(
+        chunky_install_dir(&dir, "Paper").unwrap(),
+        (dir.join("plugins"), "paper")
+    );
+    assert_eq!(
+        chunky_install_dir(&dir, "Fabric").unwrap(),
+        (dir.join("mods"), "fabric")
+    );
+    assert!(chunky_install_dir(&dir, "Vanilla").is_err());
+
+    fs::create_dir_all(dir.join("plugins")).unwrap();
+    write_zip(
+        &dir.join("plugins/Other.jar"),
+        &[("plugin.yml", b"name: ChunkyBorder\nversion: 1\n")],
+    );
+    assert!(!chunky_installed(&dir.join("plugins")));
+    write_zip(
+        &dir.join("plugins/Chunky-1.4.jar"),
+        &[("plugin.yml", b"name: Chunky\nversion: 1.4.0\n")],
+    );
+    assert!(chunky_installed(&dir.join("plugins")));
+}
+
+#[test]
+fn log_tail_follows_new_lines_and_rotation() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    fs::create_dir_all(dir.join("logs")).unwrap();
+    let log = dir.join("logs/latest.log");
+    fs::write(&log, "old line\n").unwrap();
+    let mut tail = LogTail::new(&dir);
+    assert!(tail.read_lines().is_empty());
+
+    let append = |text: &str| {
+        use std::io::Write;
+        let mut file = fs::OpenOptions::new().append(true).open(&log).unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    };
+    append("first\nsec");
+    assert_eq!(tail.read_lines(), vec!["first"]);
+    append("ond\r\n");
+    assert_eq!(tail.read_lines(), vec!["second"]);
+
+    // The server starts a fresh latest.log when it boots
+    fs::remove_file(&log).unwrap();
+    fs::write(&log, "new\n").unwrap();
+    assert_eq!(tail.read_lines(), vec!["new"]);
+}
+
+#[tokio::test]
+async fn pregen_runs_chunky_over_rcon_and_stops_the_server() {
+    let tmp = TempDir::new().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let game = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let (rcon_port, server) = mock_rcon_server("secret", "ok");
+    fs::write(
+        dir.join("server.properties"),
+        format!(
+            "server-port={}\nenable-rcon=true\nrcon.port={}\nrcon.password=secret\n",
+            game.local_addr().unwrap().port(),
+            rcon_port
+        ),
+    )
+    .unwrap();
+    fs::create_dir_all(dir.join("plugins")).unwrap();
+    write_zip(
+        &dir.join("plugins/Chunky.jar"),
+        &[("plugin.yml", b"name: Chunky\nversion: 1.4.0\n")],
+    );
+    fs::create_dir_all(dir.join("logs")).unwrap();
+    let log = dir.join("logs/latest.log");
+    // An earlier run's result must not end this one
+    fs::write(
+        &log,
+        "[Chunky] Task finished for world. Processed: 1 chunks (100.00%), Total time: 0:00:01\n",
+    )
+    .unwrap();
+
+    let writer = {
+        let log = log.clone();
+        std::thread::spawn(move || {
+            use std::io::Write;
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            let mut file = fs::OpenOptions::new().append(true).open(&log).unwrap();
+            writeln!(file, "[Chunky] Task running for world. Processed: 500 chunks (50.00%), ETA: 0:00:05, Rate: 100.0 cps, Current: 1, 2").unwrap();
+            writeln!(file, "[Chunky] Task finished for world. Processed: 1000 chunks (100.00%), Total time: 0:00:10").unwrap();
+        })
+    };
+    let mut seen = vec![];
+    let done = run_pregen(&dir, "Paper", "1.21.4", (0.0, 0.0), 500.0, |p| {
+        seen.push(p.state)
+    })
+    .await
+    .unwrap();
+    writer.join().unwrap();
+    assert_eq!(done.chunks, 1000);
+    assert_eq!(*seen.last().unwrap(), ChunkyState::Finished);
+    assert_eq!(
+        server.join().unwrap(),
+        vec![
+            "worldborder center 0 0",
+            "worldborder set 1000",
+            "chunky center 0 0",
+            "chunky radius 500",
+            "chunky start",
+            "stop",
+        ]
+    );
+
+    // Chunky can't be added to a running server
+    fs::remove_file(dir.join("plugins/Chunky.jar")).unwrap();
+    assert!(
+        run_pregen(&dir, "Paper", "1.21.4", (0.0, 0.0), 500.0, |_| {})
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("stop the server")
+    );
+    assert!(run_pregen(&dir, "Paper", "1.21.4", (0.0, 0.0), 0.0, |_| {})
+        .await
+        .is_err());
+}
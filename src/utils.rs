@@ -1,18 +1,25 @@
-use crate::config::{read_oxide_config, read_property};
+use crate::config::read_property;
+use crate::storage::backup_backend;
 use cliclack::spinner;
+use sha1::{Digest, Sha1};
 use std::error::Error;
-use std::fs::{self, create_dir_all};
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-pub fn expand_path(path: &str) -> Result<PathBuf, String> {
-    let p: PathBuf;
-    if path.starts_with('~') {
-        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        p = PathBuf::from(home).join(path.trim_start_matches('~').trim_start_matches('/'));
-    } else {
-        p = PathBuf::from(path);
+/// `path` with a leading `~` replaced by the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
     }
+}
+
+pub fn expand_path(path: &str) -> Result<PathBuf, String> {
+    let p = expand_home(path);
     if p.exists() {
         if p.is_dir() {
             Ok(p.canonicalize().unwrap_or(p))
@@ -40,7 +47,7 @@ pub fn list_entries(dir: &PathBuf) -> Result<Vec<String>, Box<dyn Error>> {
     Ok(entries)
 }
 
-pub fn get_platform(dir: &PathBuf) -> Result<String, String> {
+pub fn get_platform(dir: &Path) -> Result<String, String> {
     let dir =
         expand_path(dir.to_str().unwrap()).map_err(|e| format!("Failed to expand path: {}", e))?;
     let jar_path = dir.join("server.jar");
@@ -49,19 +56,122 @@ pub fn get_platform(dir: &PathBuf) -> Result<String, String> {
     }
 
     if dir.join("plugins").exists() {
-        return Ok("Paper".to_string());
+        Ok("Paper".to_string())
     } else if dir.join("mods").exists() {
         if dir.join(".fabric").exists() {
-            return Ok("Fabric".to_string());
+            Ok("Fabric".to_string())
         } else {
-            return Ok("Forge".to_string());
+            Ok("Forge".to_string())
         }
     } else {
-        return Ok("Vanilla".to_string());
+        Ok("Vanilla".to_string())
+    }
+}
+
+/// SHA-1 of a file as lowercase hex — the hash Modrinth's version-file endpoints expect.
+pub fn sha1_file(path: &PathBuf) -> Result<String, Box<dyn Error>> {
+    Ok(to_hex(&Sha1::digest(fs::read(path)?)))
+}
+
+/// Write `contents` to a temporary file next to `path` and rename it over `path`, so
+/// readers (the server included) never see a half-written file.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), Box<dyn Error>> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name));
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// Lowercase hex encoding of a byte slice (hash digests, signatures).
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Shortens `text` to at most `max` characters, ending with "…" when cut. Counts chars, not
+/// bytes, so multi-byte descriptions can't split a code point.
+pub fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Thousands-separated count, e.g. 1234567 -> "1,234,567".
+pub fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
     }
+    out
 }
 
-pub fn backup_world(dir: &PathBuf) -> Result<(), Box<dyn Error>> {
+/// Fuzzy match `query` against `text` as a case-insensitive subsequence. Higher scores mean
+/// a closer match: consecutive runs and matches at word starts count extra, skipped
+/// characters count against. `None` if some query character doesn't appear in order.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = pos + text[pos..].iter().position(|c| *c == q)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        score -= (found - pos) as i64;
+        previous = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+/// Total size of the files under `path`.
+pub fn dir_size(path: &PathBuf) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| match e.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&e.path()),
+            Ok(_) => e.metadata().map(|m| m.len()).unwrap_or(0),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Byte count in the largest fitting unit, e.g. "512 B", "3.4 MB".
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Archive the current world and hand it to the configured storage backend, returning the
+/// archive's name.
+pub async fn backup_world(dir: &PathBuf) -> Result<String, Box<dyn Error>> {
     let level_name = read_property(dir, "server.properties", "level-name")
         .unwrap_or_else(|_| "world".to_string());
     let world_dir = dir.join(&level_name);
@@ -73,34 +183,55 @@ pub fn backup_world(dir: &PathBuf) -> Result<(), Box<dyn Error>> {
         .duration_since(std::time::UNIX_EPOCH)?
         .as_secs();
 
-    let backups_dir = read_oxide_config(dir, "backup_dir")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| dir.join("backups"));
-    create_dir_all(&backups_dir)?;
-
+    let backend = backup_backend(dir)?;
     let backup_name = format!("{}_{}.tar.gz", level_name, timestamp);
-    let backup_path = backups_dir.join(&backup_name);
+    let archive_path = std::env::temp_dir().join(&backup_name);
 
     let sp = spinner();
     sp.start(format!(
         "Backing up '{}' to {}...",
         level_name,
-        backup_path.display()
+        backend.describe()
     ));
 
+    // Paper and Spigot keep the Nether and End in sibling folders
+    let folders: Vec<String> = [
+        level_name.clone(),
+        format!("{}_nether", level_name),
+        format!("{}_the_end", level_name),
+    ]
+    .into_iter()
+    .filter(|folder| dir.join(folder).exists())
+    .collect();
+
     let status = Command::new("tar")
         .arg("-czf")
-        .arg(&backup_path)
+        .arg(&archive_path)
         .arg("-C")
         .arg(dir)
-        .arg(&level_name)
+        .args(&folders)
         .status()?;
 
-    if status.success() {
-        sp.stop(format!("Backup saved to {}", backup_path.display()));
-        Ok(())
-    } else {
+    if !status.success() {
         sp.stop("Backup failed!".to_string());
-        Err("tar command failed".into())
+        let _ = fs::remove_file(&archive_path);
+        return Err("tar command failed".into());
+    }
+
+    let stored = backend.store(&archive_path, &backup_name).await;
+    let _ = fs::remove_file(&archive_path);
+    match stored {
+        Ok(()) => {
+            sp.stop(format!(
+                "Backup saved to {} as {}",
+                backend.describe(),
+                backup_name
+            ));
+            Ok(backup_name)
+        }
+        Err(e) => {
+            sp.stop("Backup failed!".to_string());
+            Err(e)
+        }
     }
 }
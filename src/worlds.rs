@@ -0,0 +1,245 @@
+use crate::config::{configure_properties, read_property};
+use crate::level::{read_world_info, world_dir, world_info, WorldInfo};
+use crate::modpack::extract_zip_dir;
+use crate::nbt::read_nbt_file;
+use crate::rcon::server_running;
+use crate::schema::check_property;
+use crate::utils::{backup_world, dir_size};
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// A world folder in the server directory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorldFolder {
+    pub name: String,
+    /// Whether `level-name` points at it.
+    pub current: bool,
+    /// `None` for the current world before the server has generated it.
+    pub info: Option<WorldInfo>,
+    /// Bytes on disk, Nether and End folders included.
+    pub size: u64,
+}
+
+/// The current `level-name`.
+pub fn current_world(dir: &Path) -> String {
+    read_property(dir, "server.properties", "level-name").unwrap_or_else(|_| "world".to_string())
+}
+
+/// `name` and the `name_nether` / `name_the_end` folders Paper and Spigot add, where present.
+pub fn world_folders_of(dir: &Path, name: &str) -> Vec<PathBuf> {
+    [
+        name.to_string(),
+        format!("{}_nether", name),
+        format!("{}_the_end", name),
+    ]
+    .iter()
+    .map(|folder| dir.join(folder))
+    .filter(|path| path.is_dir())
+    .collect()
+}
+
+/// Every folder holding a `level.dat`, plus the current world even if it isn't generated
+/// yet. Paper's `_nether` and `_the_end` folders count as part of their overworld.
+pub fn list_worlds(dir: &PathBuf) -> Result<Vec<WorldFolder>, Box<dyn Error>> {
+    let current = current_world(dir);
+    let mut names: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().join("level.dat").is_file())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .collect();
+    names.retain(|name| {
+        let base = name
+            .strip_suffix("_nether")
+            .or_else(|| name.strip_suffix("_the_end"));
+        !base.is_some_and(|base| dir.join(base).join("level.dat").is_file())
+    });
+    if !names.contains(&current) {
+        names.push(current.clone());
+    }
+    names.sort();
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let info = read_nbt_file(&dir.join(&name).join("level.dat"))
+                .ok()
+                .map(|root| world_info(&root));
+            let size = world_folders_of(dir, &name).iter().map(dir_size).sum();
+            WorldFolder {
+                current: name == current,
+                name,
+                info,
+                size,
+            }
+        })
+        .collect())
+}
+
+/// A world name usable as a folder next to `server.jar`.
+pub fn valid_world_name(name: &str) -> Result<(), String> {
+    let ok = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | ' ' | '.'));
+    if ok {
+        Ok(())
+    } else {
+        Err("Use letters, digits, spaces, '-', '_' or '.' (not at the start)".to_string())
+    }
+}
+
+/// Error out while the server is up, for changes it would overwrite or trip over. The game
+/// port alone misses a server started with `--port`, still loading or still saving on its
+/// way down, so the world's session lock counts too.
+pub fn refuse_while_running(dir: &Path, action: &str) -> Result<(), Box<dyn Error>> {
+    if server_running(dir) || world_locked(&world_dir(dir)) {
+        return Err(format!("Stop the server before you {}", action).into());
+    }
+    Ok(())
+}
+
+/// Whether a server has `world` open. Minecraft holds a lock on `session.lock` from loading
+/// the world until it's saved on shutdown.
+pub fn world_locked(world: &Path) -> bool {
+    File::open(world.join("session.lock")).is_ok_and(|file| session_lock_held(&file))
+}
+
+/// Java locks the file with `fcntl`, so ask about a conflicting record lock without taking
+/// one. Open file description locks see this process's locks as well as other processes'.
+#[cfg(unix)]
+fn session_lock_held(file: &File) -> bool {
+    use std::os::fd::AsRawFd;
+    #[cfg(target_os = "linux")]
+    let command = libc::F_OFD_GETLK;
+    #[cfg(not(target_os = "linux"))]
+    let command = libc::F_GETLK;
+    // SAFETY: flock is plain data and fcntl only writes into the struct it's given
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = libc::F_WRLCK as _;
+    lock.l_whence = libc::SEEK_SET as _;
+    let result = unsafe { libc::fcntl(file.as_raw_fd(), command, &mut lock) };
+    result == 0 && lock.l_type as libc::c_int != libc::F_UNLCK
+}
+
+/// Windows locks are mandatory, so a lock the server holds makes ours fail.
+#[cfg(not(unix))]
+fn session_lock_held(file: &File) -> bool {
+    matches!(file.try_lock(), Err(fs::TryLockError::WouldBlock))
+}
+
+/// Point `level-name` at a new folder with the given seed and type; the server generates
+/// the world there on its next start.
+pub fn create_world(
+    dir: &Path,
+    name: &str,
+    seed: &str,
+    level_type: &str,
+) -> Result<(), Box<dyn Error>> {
+    valid_world_name(name)?;
+    check_property("level-type", level_type)?;
+    if !world_folders_of(dir, name).is_empty() {
+        return Err(format!("A world named '{}' already exists", name).into());
+    }
+    configure_properties(
+        dir,
+        "server.properties",
+        &[
+            ("level-name", name),
+            ("level-seed", seed),
+            ("level-type", level_type),
+        ],
+    )
+}
+
+/// Make an existing world folder the one the server loads.
+pub fn switch_world(dir: &Path, name: &str) -> Result<(), Box<dyn Error>> {
+    valid_world_name(name)?;
+    if !dir.join(name).join("level.dat").is_file() {
+        return Err(format!("'{}' is not a world (no level.dat)", name).into());
+    }
+    configure_properties(dir, "server.properties", &[("level-name", name)])
+}
+
+/// Back the current world up to the configured backup target, then delete it so the server
+/// generates a new one. With `keep_seed`, the old seed is written to `level-seed` first.
+/// Returns the backup's name.
+pub async fn reset_world(dir: &PathBuf, keep_seed: bool) -> Result<String, Box<dyn Error>> {
+    refuse_while_running(dir, "reset the world")?;
+    let seed = read_world_info(dir).ok().and_then(|info| info.seed);
+    let archive = backup_world(dir).await?;
+    // An empty seed has the server pick a new one
+    let seed = match (keep_seed, seed) {
+        (true, Some(seed)) => Some(seed.to_string()),
+        (true, None) => None,
+        (false, _) => Some(String::new()),
+    };
+    if let Some(seed) = seed {
+        configure_properties(dir, "server.properties", &[("level-seed", &seed)])?;
+    }
+    for folder in world_folders_of(dir, &current_world(dir)) {
+        fs::remove_dir_all(folder)?;
+    }
+    Ok(archive)
+}
+
+/// Folder inside a world zip that holds `level.dat`: the zip root or a single top folder.
+pub fn zip_world_root(archive_path: &PathBuf) -> Result<String, Box<dyn Error>> {
+    let archive = ZipArchive::new(File::open(archive_path)?)?;
+    let mut roots: Vec<PathBuf> = archive
+        .file_names()
+        .filter_map(|name| name.ok())
+        .map(|name| PathBuf::from(name.as_ref()))
+        .filter(|p| p.file_name().is_some_and(|n| n == "level.dat"))
+        .map(|p| p.parent().unwrap_or(Path::new("")).to_path_buf())
+        .collect();
+    let depth = roots.iter().map(|p| p.components().count()).min();
+    roots.retain(|p| Some(p.components().count()) == depth);
+    match roots.as_slice() {
+        [] => Err("The zip has no level.dat, so it isn't a Minecraft world".into()),
+        [root] => Ok(root.to_string_lossy().replace('\\', "/")),
+        _ => Err("The zip holds several worlds; import them one at a time".into()),
+    }
+}
+
+/// Unpack a world zip into a new folder named `name`. The world is extracted next to its
+/// final place first, so a failed import leaves nothing behind.
+pub fn import_world_zip(
+    dir: &Path,
+    archive_path: &PathBuf,
+    name: &str,
+) -> Result<(), Box<dyn Error>> {
+    valid_world_name(name)?;
+    let target = dir.join(name);
+    if target.exists() {
+        return Err(format!("A world named '{}' already exists", name).into());
+    }
+    let root = zip_world_root(archive_path)?;
+    let staging = dir.join(format!(".import-{}", name));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    let extracted = extract_zip_dir(archive_path, &root, &staging).and_then(|_| {
+        if staging.join("level.dat").is_file() {
+            Ok(())
+        } else {
+            Err("level.dat could not be extracted".into())
+        }
+    });
+    if let Err(e) = extracted {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    // Worlds are saved by the server; a copied session lock would only confuse it
+    let _ = fs::remove_file(staging.join("session.lock"));
+    fs::rename(&staging, &target)?;
+    Ok(())
+}
+
+/// World name suggested for an imported zip: its top folder, or the file name.
+pub fn import_name(archive_path: &Path, root: &str) -> String {
+    let from_root = root.rsplit('/').next().filter(|n| !n.is_empty());
+    let from_file = archive_path.file_stem().and_then(|s| s.to_str());
+    from_root.or(from_file).unwrap_or("imported").to_string()
+}
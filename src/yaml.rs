@@ -0,0 +1,267 @@
+use serde_yaml::Value;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+/// Indentation used for keys this module adds; Bukkit, Spigot and Paper all write 2 spaces.
+const INDENT: usize = 2;
+
+/// Scalar at a dotted path (`world-settings.default.mob-spawn-range`). Sections and lists
+/// come back as YAML text.
+pub fn yaml_get(text: &str, path: &str) -> Result<Option<String>, String> {
+    let root: Value = serde_yaml::from_str(text).map_err(|e| e.to_string())?;
+    let mut node = &root;
+    for key in path.split('.') {
+        match node.get(key) {
+            Some(child) => node = child,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(match node {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other)
+            .map_err(|e| e.to_string())?
+            .trim_end()
+            .to_string(),
+    }))
+}
+
+/// Set the scalar at a dotted path, creating missing sections. Edits the text line by line
+/// so comments and layout survive; files using flow style (`{a: 1}`) are re-serialised
+/// instead, which drops their comments.
+pub fn yaml_set(text: &str, path: &str, value: &str) -> Result<String, String> {
+    let keys: Vec<&str> = path.split('.').collect();
+    if keys.iter().any(|k| k.is_empty()) {
+        return Err(format!("Invalid path '{}'", path));
+    }
+    match set_in_lines(text, &keys, value) {
+        Some(result) => result,
+        None => set_in_value(text, &keys, value),
+    }
+}
+
+/// Line-based edit. `None` when the file's shape is beyond a simple block mapping.
+fn set_in_lines(text: &str, keys: &[&str], value: &str) -> Option<Result<String, String>> {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let (mut start, mut end) = (0, lines.len());
+    let mut parent_indent: Option<usize> = None;
+
+    for (depth, key) in keys.iter().enumerate() {
+        let last = depth + 1 == keys.len();
+        let found = (start..end).find(|&i| {
+            key_of(&lines[i]).is_some_and(|(indent, k)| {
+                k == *key
+                    && parent_indent.is_none_or(|p| indent > p)
+                    && is_block_level(&lines, start, i, indent)
+            })
+        });
+        let Some(i) = found else {
+            // Add the rest of the path after the last line of this block
+            let indent = block_indent(&lines, start, end)
+                .unwrap_or_else(|| parent_indent.map_or(0, |p| p + INDENT));
+            let insert_at = (start..end)
+                .rev()
+                .find(|&i| is_content(&lines[i]))
+                .map_or(start, |i| i + 1);
+            let mut added = Vec::new();
+            for (offset, k) in keys[depth..].iter().enumerate() {
+                let pad = " ".repeat(indent + offset * INDENT);
+                if depth + offset + 1 == keys.len() {
+                    added.push(format!("{}{}: {}", pad, k, format_scalar(value)));
+                } else {
+                    added.push(format!("{}{}:", pad, k));
+                }
+            }
+            lines.splice(insert_at..insert_at, added);
+            return Some(Ok(join_lines(&lines, text)));
+        };
+
+        let (indent, _) = key_of(&lines[i])?;
+        let (rest, comment) = split_value(&lines[i], indent);
+        // Lists may sit at the same indent as their key
+        let child_end = (i + 1..end)
+            .find(|&j| {
+                let line = &lines[j];
+                let child_indent = leading_spaces(line);
+                is_content(line)
+                    && (child_indent < indent
+                        || (child_indent == indent && !line.trim_start().starts_with('-')))
+            })
+            .unwrap_or(end);
+        let has_children = (i + 1..child_end).any(|j| is_content(&lines[j]));
+
+        if last {
+            if has_children || rest.starts_with('|') || rest.starts_with('>') {
+                return Some(Err(format!(
+                    "'{}' is a section, not a single value",
+                    keys.join(".")
+                )));
+            }
+            let mut line = format!(
+                "{}: {}",
+                &lines[i][..indent + raw_key_len(&lines[i], indent)],
+                format_scalar(value)
+            );
+            if let Some(comment) = comment {
+                line.push(' ');
+                line.push_str(comment);
+            }
+            lines[i] = line;
+            return Some(Ok(join_lines(&lines, text)));
+        }
+        if !rest.is_empty() {
+            // `key: {}` or `key: value` where a section is needed
+            return None;
+        }
+        parent_indent = Some(indent);
+        start = i + 1;
+        end = child_end;
+    }
+    None
+}
+
+/// Fallback: parse, set, and write back without comments.
+fn set_in_value(text: &str, keys: &[&str], value: &str) -> Result<String, String> {
+    let mut root: Value = if text.trim().is_empty() {
+        Value::Mapping(Default::default())
+    } else {
+        serde_yaml::from_str(text).map_err(|e| e.to_string())?
+    };
+    let mut node = &mut root;
+    for key in &keys[..keys.len() - 1] {
+        let map = node
+            .as_mapping_mut()
+            .ok_or_else(|| format!("'{}' is not a section", key))?;
+        node = map
+            .entry(Value::String(key.to_string()))
+            .or_insert_with(|| Value::Mapping(Default::default()));
+    }
+    let map = node
+        .as_mapping_mut()
+        .ok_or_else(|| format!("Can't set '{}' here", keys.join(".")))?;
+    let parsed: Value =
+        serde_yaml::from_str(&format_scalar(value)).unwrap_or(Value::String(value.to_string()));
+    map.insert(Value::String(keys[keys.len() - 1].to_string()), parsed);
+    serde_yaml::to_string(&root).map_err(|e| e.to_string())
+}
+
+/// Write a value as a plain scalar when YAML reads it back unchanged, quoted otherwise.
+pub fn format_scalar(value: &str) -> String {
+    // Numbers and booleans keep their YAML type
+    if let Ok(Value::Number(_) | Value::Bool(_)) = serde_yaml::from_str::<Value>(value) {
+        return value.to_string();
+    }
+    // SnakeYAML, which Bukkit uses, still reads YAML 1.1 booleans
+    let yaml11_bool = ["y", "n", "yes", "no", "on", "off"]
+        .iter()
+        .any(|b| b.eq_ignore_ascii_case(value));
+    let plain_ok = !value.is_empty()
+        && !yaml11_bool
+        && value.trim() == value
+        && !value.starts_with([
+            '&', '*', '!', '|', '>', '\'', '"', '%', '@', '`', '{', '[', '-', '?', '#', ',',
+        ])
+        && !value.contains(": ")
+        && !value.contains(" #")
+        && !value.ends_with(':');
+    match serde_yaml::from_str::<Value>(value) {
+        Ok(Value::String(s)) if plain_ok && s == value => value.to_string(),
+        _ => format!("'{}'", value.replace('\'', "''")),
+    }
+}
+
+fn leading_spaces(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Non-blank, non-comment line.
+fn is_content(line: &str) -> bool {
+    let t = line.trim();
+    !t.is_empty() && !t.starts_with('#') && t != "---"
+}
+
+/// Indent and key of a `key:` line (quoted keys unquoted). List items aren't keys.
+fn key_of(line: &str) -> Option<(usize, &str)> {
+    if !is_content(line) {
+        return None;
+    }
+    let indent = leading_spaces(line);
+    let body = &line[indent..];
+    if body.starts_with("- ") || body == "-" {
+        return None;
+    }
+    let len = raw_key_len(line, indent);
+    let raw = &body[..len];
+    let after = &body[len..];
+    if !(after.starts_with(": ") || after == ":" || after.starts_with(":\t")) {
+        return None;
+    }
+    Some((indent, raw.trim_matches(|c| c == '\'' || c == '"')))
+}
+
+/// Length of the key text (quotes included) starting at `indent`.
+fn raw_key_len(line: &str, indent: usize) -> usize {
+    let body = &line[indent..];
+    if let Some(quote) = body.chars().next().filter(|c| *c == '\'' || *c == '"') {
+        return body[1..].find(quote).map_or(body.len(), |end| end + 2);
+    }
+    body.find(": ")
+        .or_else(|| body.strip_suffix(':').map(str::len))
+        .or_else(|| body.find(":\t"))
+        .unwrap_or(body.len())
+}
+
+/// Value text and trailing comment of a `key: value  # comment` line.
+fn split_value(line: &str, indent: usize) -> (&str, Option<&str>) {
+    let after_key = &line[indent + raw_key_len(line, indent)..];
+    let rest = after_key.strip_prefix(':').unwrap_or(after_key);
+    let mut quote: Option<char> = None;
+    for (i, c) in rest.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if i == 0 || rest[..i].ends_with([' ', '\t']) => {
+                return (rest[..i].trim(), Some(&rest[i..]));
+            }
+            _ => {}
+        }
+    }
+    (rest.trim(), None)
+}
+
+/// Indentation of the keys directly inside `start..end`.
+fn block_indent(lines: &[String], start: usize, end: usize) -> Option<usize> {
+    (start..end)
+        .filter(|&i| key_of(&lines[i]).is_some())
+        .map(|i| leading_spaces(&lines[i]))
+        .min()
+}
+
+/// Whether line `i` is a key of the block starting at `start`, not of a deeper one.
+fn is_block_level(lines: &[String], start: usize, i: usize, indent: usize) -> bool {
+    block_indent(lines, start, i + 1) == Some(indent)
+}
+
+fn join_lines(lines: &[String], original: &str) -> String {
+    let mut out = lines.join("\n");
+    if original.ends_with('\n') || original.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+/// Read a value from a YAML file by dotted path; `None` if the file or key is missing.
+pub fn read_yaml_value(file: &PathBuf, path: &str) -> Result<Option<String>, Box<dyn Error>> {
+    if !file.exists() {
+        return Ok(None);
+    }
+    Ok(yaml_get(&fs::read_to_string(file)?, path)?)
+}
+
+/// Whether a settings file (a key of `Preset.settings`) is YAML rather than properties.
+pub fn is_yaml_file(name: &str) -> bool {
+    name.ends_with(".yml") || name.ends_with(".yaml")
+}